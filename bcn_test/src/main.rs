@@ -266,6 +266,8 @@ where
         layers: 1,
         mipmaps: 1,
         image_format,
+        layout: image_dds::DataLayout::Tight,
+        mip_order: image_dds::MipOrder::LargestFirst,
         data,
     }
 }
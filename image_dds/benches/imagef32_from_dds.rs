@@ -1,5 +1,5 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use image_dds::{imagef32_from_dds, ImageFormat, Surface};
+use image_dds::{imagef32_from_dds, DataLayout, ImageFormat, MipOrder, Surface};
 
 fn criterion_benchmark(c: &mut Criterion) {
     // Overestimate the surface size to avoid errors.
@@ -10,6 +10,8 @@ fn criterion_benchmark(c: &mut Criterion) {
         layers: 1,
         mipmaps: 1,
         image_format: ImageFormat::BC7RgbaUnorm,
+        layout: DataLayout::Tight,
+        mip_order: MipOrder::LargestFirst,
         data: vec![0u8; 512 * 512 * 2],
     };
     let dds = surface.to_dds().unwrap();
@@ -14,6 +14,8 @@ fuzz_target!(|input: (image_dds::ImageFormat, &[u8])| {
         layers: 1,
         mipmaps: 1,
         image_format,
+        layout: image_dds::DataLayout::Tight,
+        mip_order: image_dds::MipOrder::LargestFirst,
         data,
     };
 
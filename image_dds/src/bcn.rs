@@ -1,16 +1,19 @@
+#[cfg(feature = "intel-tex")]
+mod alpha;
 mod decode;
-#[cfg(feature = "encode")]
+#[cfg(feature = "intel-tex")]
 mod encode;
+#[cfg(feature = "intel-tex")]
+mod refine;
 
-pub use decode::decode_bcn;
-#[cfg(feature = "encode")]
-pub use encode::encode_bcn;
+pub use decode::{bc6h_mode_is_valid, bc7_mode_is_valid, decode_bcn, decode_bcn_into};
+#[cfg(feature = "intel-tex")]
+pub use encode::{encode_bcn, BcnEncode};
 
 // All BCN formats use 4x4 pixel blocks.
 const BLOCK_WIDTH: usize = 4;
 const BLOCK_HEIGHT: usize = 4;
 const CHANNELS: usize = 4;
-const ELEMENTS_PER_BLOCK: usize = BLOCK_WIDTH * BLOCK_HEIGHT * CHANNELS;
 
 pub struct Bc1;
 pub struct Bc2;
@@ -21,3 +24,9 @@ pub struct Bc5;
 pub struct Bc5S;
 pub struct Bc6;
 pub struct Bc7;
+// Only decode the alpha block of BC2 or BC3 and leave the color channels as zero.
+pub struct Bc2Alpha;
+pub struct Bc3Alpha;
+// EAC is an ETC2 format but uses the same 4x4 pixel blocks.
+pub struct EacR11;
+pub struct EacRg11;
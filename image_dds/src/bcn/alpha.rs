@@ -0,0 +1,134 @@
+// Convert the interpolation mode of the 8 byte alpha blocks used by BC3 and BC4.
+// intel_tex chooses the mode for each block, so forcing a mode refits the endpoints.
+use super::{BLOCK_HEIGHT, BLOCK_WIDTH};
+use crate::AlphaBlockMode;
+
+/// Convert each 8 byte alpha block at the start of each block in `blocks` to use `mode`.
+///
+/// The source alpha values are read from `value(x, y)` for each pixel.
+pub fn set_alpha_block_mode<F>(
+    width: u32,
+    height: u32,
+    value: F,
+    blocks: &mut [u8],
+    block_size: usize,
+    mode: AlphaBlockMode,
+) where
+    F: Fn(usize, usize) -> u8,
+{
+    if mode == AlphaBlockMode::Automatic {
+        return;
+    }
+
+    let blocks_wide = width as usize / BLOCK_WIDTH;
+    let blocks_high = height as usize / BLOCK_HEIGHT;
+
+    for (i, block) in blocks
+        .chunks_exact_mut(block_size)
+        .take(blocks_wide * blocks_high)
+        .enumerate()
+    {
+        let x = (i % blocks_wide) * BLOCK_WIDTH;
+        let y = (i / blocks_wide) * BLOCK_HEIGHT;
+        let values: [u8; 16] =
+            std::array::from_fn(|j| value(x + j % BLOCK_WIDTH, y + j / BLOCK_WIDTH));
+
+        let (a0, a1) = (block[0], block[1]);
+        let endpoints = match mode {
+            AlphaBlockMode::Automatic => None,
+            AlphaBlockMode::EightValues => (a0 <= a1).then(|| eight_value_endpoints(&values)),
+            AlphaBlockMode::SixValues => (a0 > a1).then(|| six_value_endpoints(&values)),
+        };
+
+        if let Some((a0, a1)) = endpoints {
+            block[..8].copy_from_slice(&encode_alpha_block(&values, a0, a1).to_le_bytes());
+        }
+    }
+}
+
+fn eight_value_endpoints(values: &[u8; 16]) -> (u8, u8) {
+    // The first endpoint must be larger to select this mode.
+    let min = *values.iter().min().unwrap();
+    let max = *values.iter().max().unwrap();
+    if min < max {
+        (max, min)
+    } else if max < 255 {
+        (max + 1, max)
+    } else {
+        (max, max - 1)
+    }
+}
+
+fn six_value_endpoints(values: &[u8; 16]) -> (u8, u8) {
+    // 0 and 255 are stored explicitly, so only fit the remaining values.
+    let middle = values.iter().copied().filter(|v| *v != 0 && *v != 255);
+    let min = middle.clone().min().unwrap_or(0);
+    let max = middle.max().unwrap_or(0);
+    (min, max)
+}
+
+fn encode_alpha_block(values: &[u8; 16], a0: u8, a1: u8) -> u64 {
+    let palette = alpha_palette(a0, a1);
+
+    let mut block = a0 as u64 | (a1 as u64) << 8;
+    for (i, value) in values.iter().enumerate() {
+        let index = (0..8)
+            .min_by_key(|j| (palette[*j] as i32 - *value as i32).abs())
+            .unwrap();
+        block |= (index as u64) << (16 + i * 3);
+    }
+    block
+}
+
+fn alpha_palette(a0: u8, a1: u8) -> [u8; 8] {
+    // Match the interpolation used for decoding.
+    let (a0, a1) = (a0 as u32, a1 as u32);
+    let palette: [u32; 8] = if a0 > a1 {
+        std::array::from_fn(|i| match i {
+            0 => a0,
+            1 => a1,
+            _ => ((8 - i as u32) * a0 + (i as u32 - 1) * a1 + 1) / 7,
+        })
+    } else {
+        std::array::from_fn(|i| match i {
+            0 => a0,
+            1 => a1,
+            6 => 0,
+            7 => 255,
+            _ => ((6 - i as u32) * a0 + (i as u32 - 1) * a1 + 1) / 5,
+        })
+    };
+    palette.map(|a| a as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eight_value_endpoints_single_value() {
+        assert_eq!((1, 0), eight_value_endpoints(&[0; 16]));
+        assert_eq!((255, 254), eight_value_endpoints(&[255; 16]));
+    }
+
+    #[test]
+    fn six_value_endpoints_extremes() {
+        let mut values = [0; 16];
+        values[1] = 255;
+        values[2] = 64;
+        values[3] = 128;
+        assert_eq!((64, 128), six_value_endpoints(&values));
+        assert_eq!((0, 0), six_value_endpoints(&[255; 16]));
+    }
+
+    #[test]
+    fn encode_alpha_block_exact() {
+        let values = std::array::from_fn(|i| [0, 255, 64, 128][i % 4]);
+        let block = encode_alpha_block(&values, 64, 128);
+        let palette = alpha_palette(64, 128);
+        for (i, value) in values.iter().enumerate() {
+            let index = (block >> (16 + i * 3)) & 0b111;
+            assert_eq!(*value, palette[index as usize]);
+        }
+    }
+}
@@ -2,7 +2,10 @@ use bytemuck::Pod;
 
 use crate::{error::SurfaceError, mip_size, rgba::convert::snorm8_to_unorm8};
 
-use super::{Bc1, Bc2, Bc3, Bc4, Bc4S, Bc5, Bc5S, Bc6, Bc7, BLOCK_HEIGHT, BLOCK_WIDTH, CHANNELS};
+use super::{
+    Bc1, Bc2, Bc2Alpha, Bc3, Bc3Alpha, Bc4, Bc4S, Bc5, Bc5S, Bc6, Bc7, EacR11, EacRg11,
+    BLOCK_HEIGHT, BLOCK_WIDTH, CHANNELS,
+};
 
 pub trait BcnDecode<Pixel> {
     type CompressedBlock;
@@ -14,7 +17,15 @@ pub trait BcnDecode<Pixel> {
 pub trait ReadBlock {
     const SIZE_IN_BYTES: usize;
 
+    #[cfg_attr(feature = "perf-unchecked", allow(dead_code))]
     fn read_block(data: &[u8], offset: usize) -> Self;
+
+    /// Read a block without checking bounds.
+    ///
+    /// # Safety
+    /// `offset + Self::SIZE_IN_BYTES` must not exceed `data.len()`.
+    #[cfg(feature = "perf-unchecked")]
+    unsafe fn read_block_unchecked(data: &[u8], offset: usize) -> Self;
 }
 
 impl ReadBlock for [u8; 8] {
@@ -23,6 +34,12 @@ impl ReadBlock for [u8; 8] {
     fn read_block(data: &[u8], offset: usize) -> Self {
         data[offset..offset + 8].try_into().unwrap()
     }
+
+    #[cfg(feature = "perf-unchecked")]
+    unsafe fn read_block_unchecked(data: &[u8], offset: usize) -> Self {
+        debug_assert!(offset + 8 <= data.len());
+        data.as_ptr().add(offset).cast::<[u8; 8]>().read()
+    }
 }
 
 impl ReadBlock for [u8; 16] {
@@ -31,6 +48,12 @@ impl ReadBlock for [u8; 16] {
     fn read_block(data: &[u8], offset: usize) -> Self {
         data[offset..offset + 16].try_into().unwrap()
     }
+
+    #[cfg(feature = "perf-unchecked")]
+    unsafe fn read_block_unchecked(data: &[u8], offset: usize) -> Self {
+        debug_assert!(offset + 16 <= data.len());
+        data.as_ptr().add(offset).cast::<[u8; 16]>().read()
+    }
 }
 
 impl BcnDecode<[u8; 4]> for Bc1 {
@@ -81,6 +104,69 @@ impl BcnDecode<[u8; 4]> for Bc3 {
     }
 }
 
+impl BcnDecode<[u8; 4]> for Bc2Alpha {
+    type CompressedBlock = [u8; 16];
+
+    fn decompress_block(block: &[u8; 16]) -> [[[u8; 4]; BLOCK_WIDTH]; BLOCK_HEIGHT] {
+        let mut decompressed = [[[0u8; 4]; BLOCK_WIDTH]; BLOCK_HEIGHT];
+
+        // Each row stores 4-bit alpha values in a little endian u16.
+        for (y, row) in decompressed.iter_mut().enumerate() {
+            let alpha = u16::from_le_bytes([block[y * 2], block[y * 2 + 1]]);
+            for (x, pixel) in row.iter_mut().enumerate() {
+                pixel[3] = ((alpha >> (4 * x)) & 0x0F) as u8 * 17;
+            }
+        }
+
+        decompressed
+    }
+}
+
+impl BcnDecode<[u8; 4]> for Bc3Alpha {
+    type CompressedBlock = [u8; 16];
+
+    fn decompress_block(block: &[u8; 16]) -> [[[u8; 4]; BLOCK_WIDTH]; BLOCK_HEIGHT] {
+        // Use the same interpolation as bcdec for BC3 to match decoding to RGBA8.
+        let a0 = block[0] as u32;
+        let a1 = block[1] as u32;
+        let alpha = if a0 > a1 {
+            [
+                a0,
+                a1,
+                (6 * a0 + a1 + 1) / 7,
+                (5 * a0 + 2 * a1 + 1) / 7,
+                (4 * a0 + 3 * a1 + 1) / 7,
+                (3 * a0 + 4 * a1 + 1) / 7,
+                (2 * a0 + 5 * a1 + 1) / 7,
+                (a0 + 6 * a1 + 1) / 7,
+            ]
+        } else {
+            [
+                a0,
+                a1,
+                (4 * a0 + a1 + 1) / 5,
+                (3 * a0 + 2 * a1 + 1) / 5,
+                (2 * a0 + 3 * a1 + 1) / 5,
+                (a0 + 4 * a1 + 1) / 5,
+                0,
+                255,
+            ]
+        };
+
+        // The 3-bit indices start after the two endpoints.
+        let mut indices = u64::from_le_bytes(block[..8].try_into().unwrap()) >> 16;
+        let mut decompressed = [[[0u8; 4]; BLOCK_WIDTH]; BLOCK_HEIGHT];
+        for row in decompressed.iter_mut() {
+            for pixel in row.iter_mut() {
+                pixel[3] = alpha[(indices & 0x07) as usize] as u8;
+                indices >>= 3;
+            }
+        }
+
+        decompressed
+    }
+}
+
 impl BcnDecode<[u8; 4]> for Bc4 {
     type CompressedBlock = [u8; 8];
 
@@ -319,13 +405,186 @@ impl BcnDecode<[u8; 4]> for Bc7 {
     }
 }
 
+// The modifiers for each table index shared by EAC and ETC2 alpha blocks.
+const EAC_MODIFIERS: [[i32; 8]; 16] = [
+    [-3, -6, -9, -15, 2, 5, 8, 14],
+    [-3, -7, -10, -13, 2, 6, 9, 12],
+    [-2, -5, -8, -13, 1, 4, 7, 12],
+    [-2, -4, -6, -13, 1, 3, 5, 12],
+    [-3, -6, -8, -12, 2, 5, 7, 11],
+    [-3, -7, -9, -11, 2, 6, 8, 10],
+    [-4, -7, -8, -11, 3, 6, 7, 10],
+    [-3, -5, -8, -11, 2, 4, 7, 10],
+    [-2, -6, -8, -10, 1, 5, 7, 9],
+    [-2, -5, -8, -10, 1, 4, 7, 9],
+    [-2, -4, -8, -10, 1, 3, 7, 9],
+    [-2, -5, -7, -10, 1, 4, 6, 9],
+    [-3, -4, -7, -10, 2, 3, 6, 9],
+    [-1, -2, -3, -10, 0, 1, 2, 9],
+    [-4, -6, -8, -9, 3, 5, 7, 8],
+    [-3, -5, -7, -9, 2, 4, 6, 8],
+];
+
+const EAC11_MAX: u16 = 2047;
+
+/// Decode the unsigned 11-bit values of a single EAC channel block.
+fn eac11_block(block: &[u8; 8]) -> [[u16; BLOCK_WIDTH]; BLOCK_HEIGHT] {
+    let bits = u64::from_be_bytes(*block);
+    let base = block[0] as i32 * 8 + 4;
+    let multiplier = (block[1] >> 4) as i32;
+    let modifiers = EAC_MODIFIERS[(block[1] & 0xF) as usize];
+
+    // Indices are stored in column-major order starting from the most significant bits.
+    let mut values = [[0u16; BLOCK_WIDTH]; BLOCK_HEIGHT];
+    for i in 0..BLOCK_WIDTH * BLOCK_HEIGHT {
+        let index = (bits >> (45 - 3 * i)) as usize & 0b111;
+        // A multiplier of 0 uses the unscaled modifiers for more precision.
+        let modifier = if multiplier == 0 {
+            modifiers[index]
+        } else {
+            modifiers[index] * multiplier * 8
+        };
+        values[i % BLOCK_HEIGHT][i / BLOCK_HEIGHT] =
+            (base + modifier).clamp(0, EAC11_MAX as i32) as u16;
+    }
+    values
+}
+
+fn eac11_to_unorm8(value: u16) -> u8 {
+    ((value as u32 * 255 + EAC11_MAX as u32 / 2) / EAC11_MAX as u32) as u8
+}
+
+fn eac11_to_f32(value: u16) -> f32 {
+    value as f32 / EAC11_MAX as f32
+}
+
+impl BcnDecode<[u8; 4]> for EacR11 {
+    type CompressedBlock = [u8; 8];
+
+    fn decompress_block(block: &[u8; 8]) -> [[[u8; 4]; BLOCK_WIDTH]; BLOCK_HEIGHT] {
+        // Use grayscale to match BC4.
+        eac11_block(block).map(|row| {
+            row.map(|r| {
+                let r = eac11_to_unorm8(r);
+                [r, r, r, 255u8]
+            })
+        })
+    }
+}
+
+impl BcnDecode<[f32; 4]> for EacR11 {
+    type CompressedBlock = [u8; 8];
+
+    fn decompress_block(block: &[u8; 8]) -> [[[f32; 4]; BLOCK_WIDTH]; BLOCK_HEIGHT] {
+        // Use grayscale to match BC4.
+        eac11_block(block).map(|row| {
+            row.map(|r| {
+                let r = eac11_to_f32(r);
+                [r, r, r, 1.0]
+            })
+        })
+    }
+}
+
+impl BcnDecode<[u8; 4]> for EacRg11 {
+    type CompressedBlock = [u8; 16];
+
+    fn decompress_block(block: &[u8; 16]) -> [[[u8; 4]; BLOCK_WIDTH]; BLOCK_HEIGHT] {
+        // The red block is followed by the green block.
+        let r = eac11_block(block[..8].try_into().unwrap());
+        let g = eac11_block(block[8..].try_into().unwrap());
+
+        // Zero the blue channel to match BC5.
+        std::array::from_fn(|y| {
+            std::array::from_fn(|x| {
+                [
+                    eac11_to_unorm8(r[y][x]),
+                    eac11_to_unorm8(g[y][x]),
+                    0u8,
+                    255u8,
+                ]
+            })
+        })
+    }
+}
+
+impl BcnDecode<[f32; 4]> for EacRg11 {
+    type CompressedBlock = [u8; 16];
+
+    fn decompress_block(block: &[u8; 16]) -> [[[f32; 4]; BLOCK_WIDTH]; BLOCK_HEIGHT] {
+        // The red block is followed by the green block.
+        let r = eac11_block(block[..8].try_into().unwrap());
+        let g = eac11_block(block[8..].try_into().unwrap());
+
+        // Zero the blue channel to match BC5.
+        std::array::from_fn(|y| {
+            std::array::from_fn(|x| [eac11_to_f32(r[y][x]), eac11_to_f32(g[y][x]), 0.0, 1.0])
+        })
+    }
+}
+
 /// Decompress the bytes in `data` to the uncompressed RGBA8 format.
 pub fn decode_bcn<F, T>(width: u32, height: u32, data: &[u8]) -> Result<Vec<T>, SurfaceError>
 where
-    T: Copy + Default + Pod,
+    T: Copy + Default + Pod + Send,
     F: BcnDecode<[T; 4]>,
     F::CompressedBlock: ReadBlock,
 {
+    check_data_length::<F::CompressedBlock>(width, height, data)?;
+
+    let mut rgba = vec![T::default(); width as usize * height as usize * CHANNELS];
+    decode_bcn_into::<F, T>(width, height, data, &mut rgba)?;
+    Ok(rgba)
+}
+
+/// Decompress the bytes in `data` to the uncompressed RGBA8 format and write the result to `rgba`.
+///
+/// The length of `rgba` should be `width * height * 4`.
+pub fn decode_bcn_into<F, T>(
+    width: u32,
+    height: u32,
+    data: &[u8],
+    rgba: &mut [T],
+) -> Result<(), SurfaceError>
+where
+    T: Copy + Default + Pod + Send,
+    F: BcnDecode<[T; 4]>,
+    F::CompressedBlock: ReadBlock,
+{
+    check_data_length::<F::CompressedBlock>(width, height, data)?;
+
+    debug_assert_eq!(width as usize * height as usize * CHANNELS, rgba.len());
+    if rgba.is_empty() {
+        return Ok(());
+    }
+
+    // BCN formats lay out blocks in row-major order.
+    // Each row of blocks updates up to 4 rows of the RGBA surface.
+    let rgba_rows_size = width as usize * BLOCK_HEIGHT * CHANNELS;
+    let block_row_size = (width as usize).div_ceil(BLOCK_WIDTH) * F::CompressedBlock::SIZE_IN_BYTES;
+
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+
+        rgba.par_chunks_mut(rgba_rows_size)
+            .zip(data.par_chunks(block_row_size))
+            .for_each(|(rgba_rows, blocks)| decode_block_row::<F, T>(rgba_rows, blocks, width));
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    rgba.chunks_mut(rgba_rows_size)
+        .zip(data.chunks(block_row_size))
+        .for_each(|(rgba_rows, blocks)| decode_block_row::<F, T>(rgba_rows, blocks, width));
+
+    Ok(())
+}
+
+fn check_data_length<B: ReadBlock>(
+    width: u32,
+    height: u32,
+    data: &[u8],
+) -> Result<(), SurfaceError> {
     // Validate surface dimensions to check for potential overflow.
     let expected_size = mip_size(
         width as usize,
@@ -334,7 +593,7 @@ where
         BLOCK_WIDTH,
         BLOCK_HEIGHT,
         1,
-        F::CompressedBlock::SIZE_IN_BYTES,
+        B::SIZE_IN_BYTES,
     )
     .ok_or(SurfaceError::PixelCountWouldOverflow {
         width,
@@ -352,31 +611,48 @@ where
         });
     }
 
-    let mut rgba = vec![T::default(); width as usize * height as usize * CHANNELS];
+    Ok(())
+}
+
+/// Returns `false` if the BC6H `block` uses one of the reserved modes.
+///
+/// Blocks with reserved modes decode to zeros for the RGB channels.
+pub fn bc6h_mode_is_valid(block: &[u8]) -> bool {
+    // Modes with 5 bits ending in 10011, 10111, 11011, and 11111 are reserved.
+    !matches!(block[0] & 0b11111, 0b10011 | 0b10111 | 0b11011 | 0b11111)
+}
+
+/// Returns `false` if the BC7 `block` does not set any of the mode bits.
+///
+/// Blocks without a mode decode to transparent black.
+pub fn bc7_mode_is_valid(block: &[u8]) -> bool {
+    // The mode is the index of the first set bit in the first byte.
+    block[0] != 0
+}
+
+fn decode_block_row<F, T>(rgba_rows: &mut [T], blocks: &[u8], width: u32)
+where
+    T: Copy + Default + Pod,
+    F: BcnDecode<[T; 4]>,
+    F::CompressedBlock: ReadBlock,
+{
+    let width = width as usize;
+    let height = rgba_rows.len() / (width * CHANNELS);
 
-    // BCN formats lay out blocks in row-major order.
-    // TODO: calculate x and y using division and mod?
     let mut block_start = 0;
-    for y in (0..height).step_by(BLOCK_HEIGHT) {
-        for x in (0..width).step_by(BLOCK_WIDTH) {
-            let block = F::CompressedBlock::read_block(data, block_start);
-            let decompressed_block = F::decompress_block(&block);
-
-            // Each block is 4x4, so we need to update multiple rows.
-            put_rgba_block(
-                &mut rgba,
-                decompressed_block,
-                x as usize,
-                y as usize,
-                width as usize,
-                height as usize,
-            );
-
-            block_start += F::CompressedBlock::SIZE_IN_BYTES;
-        }
+    for x in (0..width).step_by(BLOCK_WIDTH) {
+        // SAFETY: decode_bcn checks that data contains every block in each row of blocks.
+        #[cfg(feature = "perf-unchecked")]
+        let block = unsafe { F::CompressedBlock::read_block_unchecked(blocks, block_start) };
+        #[cfg(not(feature = "perf-unchecked"))]
+        let block = F::CompressedBlock::read_block(blocks, block_start);
+        let decompressed_block = F::decompress_block(&block);
+
+        // Each block is 4x4, so we need to update multiple rows.
+        put_rgba_block(rgba_rows, decompressed_block, x, 0, width, height);
+
+        block_start += F::CompressedBlock::SIZE_IN_BYTES;
     }
-
-    Ok(rgba)
 }
 
 fn put_rgba_block<T: Pod>(
@@ -407,6 +683,113 @@ fn put_rgba_block<T: Pod>(
 mod tests {
     use super::*;
 
+    fn check_decompress_bcn<F, P>(block: &F::CompressedBlock, expected: P)
+    where
+        F: BcnDecode<P>,
+        P: PartialEq + std::fmt::Debug + Copy,
+    {
+        assert_eq!(
+            [[expected; BLOCK_WIDTH]; BLOCK_HEIGHT],
+            F::decompress_block(block)
+        );
+    }
+
+    // White endpoint and black endpoint with all indices selecting the first endpoint.
+    const BC1_WHITE: [u8; 8] = [255, 255, 0, 0, 0, 0, 0, 0];
+    const BC4_WHITE: [u8; 8] = [255, 0, 0, 0, 0, 0, 0, 0];
+
+    #[test]
+    fn bc1_decompress() {
+        check_decompress_bcn::<Bc1, _>(&BC1_WHITE, [255u8; 4]);
+        // Equal endpoints use the 3 color mode.
+        check_decompress_bcn::<Bc1, _>(&[0u8; 8], [0u8, 0, 0, 255]);
+    }
+
+    #[test]
+    fn bc2_decompress() {
+        let block = [[255u8; 8], BC1_WHITE].concat().try_into().unwrap();
+        check_decompress_bcn::<Bc2, _>(&block, [255u8; 4]);
+        check_decompress_bcn::<Bc2, _>(&[0u8; 16], [0u8; 4]);
+    }
+
+    #[test]
+    fn bc3_decompress() {
+        let block = [BC4_WHITE, BC1_WHITE].concat().try_into().unwrap();
+        check_decompress_bcn::<Bc3, _>(&block, [255u8; 4]);
+        check_decompress_bcn::<Bc3, _>(&[0u8; 16], [0u8; 4]);
+    }
+
+    #[test]
+    fn bc4_decompress() {
+        check_decompress_bcn::<Bc4, _>(&BC4_WHITE, [255u8; 4]);
+        check_decompress_bcn::<Bc4, _>(&[0u8; 8], [0u8, 0, 0, 255]);
+    }
+
+    #[test]
+    fn bc5_decompress() {
+        let block = [BC4_WHITE, [0u8; 8]].concat().try_into().unwrap();
+        check_decompress_bcn::<Bc5, _>(&block, [255u8, 0, 0, 255]);
+    }
+
+    #[test]
+    fn bc6_decompress() {
+        check_decompress_bcn::<Bc6, _>(&[0u8; 16], [0.0f32, 0.0, 0.0, 1.0]);
+        check_decompress_bcn::<Bc6, _>(&[0u8; 16], [0u8, 0, 0, 255]);
+    }
+
+    #[test]
+    fn bc7_decompress() {
+        // Mode 6 with black endpoints.
+        let mut block = [0u8; 16];
+        block[0] = 1 << 6;
+        check_decompress_bcn::<Bc7, _>(&block, [0u8; 4]);
+    }
+
+    #[test]
+    fn eac11_block_indices() {
+        // Base 128 with multiplier 1 and table 0.
+        // The second pixel in column-major order is the first pixel of the second row.
+        let bits = 128u64 << 56 | 1 << 52 | 7 << 42;
+        let values = eac11_block(&bits.to_be_bytes());
+        assert_eq!(1028 - 24, values[0][0]);
+        assert_eq!(1028 + 112, values[1][0]);
+        assert_eq!(1028 - 24, values[0][1]);
+    }
+
+    #[test]
+    fn eac11_block_zero_multiplier_clamped() {
+        // A multiplier of 0 does not scale the modifiers.
+        let bits = 255u64 << 56 | 0xFFFF_FFFF_FFFF;
+        assert_eq!([[2047; 4]; 4], eac11_block(&bits.to_be_bytes()));
+
+        let bits = 2u64 << 56;
+        assert_eq!([[20 - 3; 4]; 4], eac11_block(&bits.to_be_bytes()));
+    }
+
+    #[test]
+    fn decode_eac_rg11() {
+        let r = 255u64 << 56 | 0xFFFF_FFFF_FFFF;
+        let mut block = [0u8; 16];
+        block[..8].copy_from_slice(&r.to_be_bytes());
+        let rgba = decode_bcn::<EacRg11, u8>(4, 4, &block).unwrap();
+        assert_eq!([255u8, 0, 0, 255].repeat(16), rgba);
+    }
+
+    #[test]
+    fn decode_bc4_block_rows() {
+        // Each block has a single value with partial blocks on the right and bottom edges.
+        let data: Vec<_> = [10u8, 20, 30, 40]
+            .iter()
+            .flat_map(|v| [*v, *v, 0, 0, 0, 0, 0, 0])
+            .collect();
+        let rgba = decode_bcn::<Bc4, u8>(5, 5, &data).unwrap();
+        let r = |x: usize, y: usize| rgba[(y * 5 + x) * 4];
+        assert_eq!(10, r(3, 3));
+        assert_eq!(20, r(4, 0));
+        assert_eq!(30, r(0, 4));
+        assert_eq!(40, r(4, 4));
+    }
+
     #[test]
     fn put_rgba_block_4x4() {
         // Write an entire block.
@@ -1,300 +1,66 @@
-use crate::{mip_size, Quality, SurfaceError};
-use half::f16;
-
-use super::{
-    Bc1, Bc2, Bc3, Bc4, Bc5, Bc6, Bc7, BLOCK_HEIGHT, BLOCK_WIDTH, CHANNELS, ELEMENTS_PER_BLOCK,
-};
-
-// Quality modes are optimized for a balance of speed and quality.
-impl From<Quality> for intel_tex_2::bc6h::EncodeSettings {
-    fn from(value: Quality) -> Self {
-        // TODO: Test quality settings and speed for bc6h.
-        match value {
-            Quality::Fast => intel_tex_2::bc6h::very_fast_settings(),
-            Quality::Normal => intel_tex_2::bc6h::basic_settings(),
-            Quality::Slow => intel_tex_2::bc6h::slow_settings(),
-        }
-    }
-}
+use crate::{mip_size, EncodeOptions, Quality, SurfaceError};
 
-impl From<Quality> for intel_tex_2::bc7::EncodeSettings {
-    fn from(value: Quality) -> Self {
-        // bc7 has almost imperceptible errors even at ultra_fast
-        // 4k rgba ultra fast (2s), very fast (7s), fast (12s)
-        match value {
-            Quality::Fast => intel_tex_2::bc7::alpha_ultra_fast_settings(),
-            Quality::Normal => intel_tex_2::bc7::alpha_very_fast_settings(),
-            Quality::Slow => intel_tex_2::bc7::alpha_fast_settings(),
-        }
-    }
-}
+use super::{BLOCK_HEIGHT, BLOCK_WIDTH, CHANNELS};
+
+// Each backend implements BcnEncode for the BCN formats it supports.
+mod intel_tex;
 
 pub trait BcnEncode<T> {
     // TODO: How to handle depth with intel-tex-rs-2?
+    /// Compress `rgba_data` with `stride` elements between the start of each row.
     fn compress_surface(
         width: u32,
         height: u32,
         rgba_data: &[T],
+        stride: usize,
         quality: Quality,
+        options: &EncodeOptions,
     ) -> Result<Vec<u8>, SurfaceError>;
 }
 
-impl BcnEncode<u8> for Bc1 {
-    fn compress_surface(
-        width: u32,
-        height: u32,
-        rgba8_data: &[u8],
-        _: Quality,
-    ) -> Result<Vec<u8>, SurfaceError> {
-        // RGBA with 4 bytes per pixel.
-        let surface = intel_tex_2::RgbaSurface {
-            width,
-            height,
-            stride: width * CHANNELS as u32,
-            data: rgba8_data,
-        };
-
-        Ok(intel_tex_2::bc1::compress_blocks(&surface))
-    }
-}
-
-impl BcnEncode<u8> for Bc2 {
-    fn compress_surface(
-        width: u32,
-        height: u32,
-        rgba8_data: &[u8],
-        _: Quality,
-    ) -> Result<Vec<u8>, SurfaceError> {
-        // RGBA with 4 bytes per pixel.
-        let surface = intel_tex_2::RgbaSurface {
-            width,
-            height,
-            stride: width * CHANNELS as u32,
-            data: rgba8_data,
-        };
-
-        // BC2 is rarely used and not supported by intel_tex.
-        // The RGB block and mode is identical BC3, so we only need to encode alpha.
-        // https://learn.microsoft.com/en-us/windows/win32/direct3d10/d3d10-graphics-programming-guide-resources-block-compression#bc2
-        let bc3_data = intel_tex_2::bc3::compress_blocks(&surface);
-
-        let mut data = Vec::new();
-
-        // TODO: Tests for this.
-        // TODO: Test surfaces not divisible by block dimensions.
-        let mut block_index = 0;
-        for y in (0..height).step_by(BLOCK_HEIGHT) {
-            for x in (0..width).step_by(BLOCK_WIDTH) {
-                let bc2_block =
-                    encode_bc2_block(x, y, width, height, rgba8_data, &bc3_data, block_index);
-                data.extend_from_slice(&bc2_block.to_le_bytes());
-
-                block_index += 1;
-            }
-        }
-
-        Ok(data)
-    }
-}
-
-fn encode_bc2_block(
-    x: u32,
-    y: u32,
-    width: u32,
-    height: u32,
-    rgba8_data: &[u8],
-    bc3_data: &[u8],
-    block_index: usize,
-) -> u128 {
-    let alpha = sharp_alpha_block(x, y, width, height, rgba8_data);
-
-    let block_size = 16;
-    let block_start = block_index * block_size;
-    let bc3_block_data = bc3_data[block_start..block_start + block_size]
-        .try_into()
-        .unwrap();
-    let bc3_color_block = u128::from_le_bytes(bc3_block_data) >> u64::BITS;
-
-    (bc3_color_block << u64::BITS) | alpha as u128
-}
-
-fn sharp_alpha_block(x: u32, y: u32, width: u32, height: u32, rgba8_data: &[u8]) -> u64 {
-    // TODO: Use a byte array for better clarity?
-    let mut alpha = 0u64;
-    for i in 0..BLOCK_HEIGHT {
-        for j in 0..BLOCK_WIDTH {
-            // TODO: Is there a simpler way of doing this?
-            let x_final = (x as usize + i).min(width.saturating_sub(1) as usize);
-            let y_final = (y as usize + j).min(height.saturating_sub(1) as usize);
-            let input_index = (y_final * width as usize + x_final) * CHANNELS + 3;
-
-            let output_index = j * BLOCK_HEIGHT + i;
-
-            // 4-bit alpha for each pixel.
-            alpha |= ((rgba8_data[input_index] / 17) as u64) << (output_index * 4);
-        }
-    }
-    alpha
-}
-
-impl BcnEncode<u8> for Bc3 {
-    fn compress_surface(
-        width: u32,
-        height: u32,
-        rgba8_data: &[u8],
-        _: Quality,
-    ) -> Result<Vec<u8>, SurfaceError> {
-        // RGBA with 4 bytes per pixel.
-        let surface = intel_tex_2::RgbaSurface {
-            width,
-            height,
-            stride: width * CHANNELS as u32,
-            data: rgba8_data,
-        };
-
-        Ok(intel_tex_2::bc3::compress_blocks(&surface))
-    }
-}
-
-impl BcnEncode<u8> for Bc4 {
-    fn compress_surface(
-        width: u32,
-        height: u32,
-        rgba8_data: &[u8],
-        _: Quality,
-    ) -> Result<Vec<u8>, SurfaceError> {
-        // R8 with 4 bytes per pixel.
-        let r8_data: Vec<_> = rgba8_data.chunks_exact(4).map(|p| p[0]).collect();
-        let surface = intel_tex_2::RSurface {
-            width,
-            height,
-            stride: width,
-            data: &r8_data,
-        };
-
-        Ok(intel_tex_2::bc4::compress_blocks(&surface))
-    }
-}
-
-impl BcnEncode<u8> for Bc5 {
-    fn compress_surface(
-        width: u32,
-        height: u32,
-        rgba8_data: &[u8],
-        _: Quality,
-    ) -> Result<Vec<u8>, SurfaceError> {
-        // RG8 with 2 bytes per pixel.
-        let rg8_data: Vec<_> = rgba8_data
-            .chunks_exact(4)
-            .flat_map(|p| [p[0], p[1]])
-            .collect();
-        let surface = intel_tex_2::RgSurface {
-            width,
-            height,
-            stride: width * 2,
-            data: &rg8_data,
-        };
-
-        Ok(intel_tex_2::bc5::compress_blocks(&surface))
-    }
-}
-
-impl BcnEncode<f32> for Bc6 {
-    fn compress_surface(
-        width: u32,
-        height: u32,
-        rgba8_data: &[f32],
-        quality: Quality,
-    ) -> Result<Vec<u8>, SurfaceError> {
-        // The BC6H encoder expects the data to be in half precision floating point.
-        // This differs from the other formats that expect [u8; 4] for each pixel.
-        let f16_data: Vec<f16> = rgba8_data.iter().copied().map(f16::from_f32).collect();
-
-        let surface = intel_tex_2::RgbaSurface {
-            width,
-            height,
-            stride: width * 4 * std::mem::size_of::<f16>() as u32,
-            data: bytemuck::cast_slice(&f16_data),
-        };
-
-        Ok(intel_tex_2::bc6h::compress_blocks(
-            &quality.into(),
-            &surface,
-        ))
-    }
-}
-
-impl BcnEncode<u8> for Bc6 {
-    fn compress_surface(
-        width: u32,
-        height: u32,
-        rgba8_data: &[u8],
-        quality: Quality,
-    ) -> Result<Vec<u8>, SurfaceError> {
-        // The BC6H encoder expects the data to be in half precision floating point.
-        // This differs from the other formats that expect [u8; 4] for each pixel.
-        let f16_data: Vec<f16> = rgba8_data
-            .iter()
-            .map(|v| f16::from_f32(*v as f32 / 255.0))
-            .collect();
-
-        let surface = intel_tex_2::RgbaSurface {
-            width,
-            height,
-            stride: width * 4 * std::mem::size_of::<f16>() as u32,
-            data: bytemuck::cast_slice(&f16_data),
-        };
-
-        Ok(intel_tex_2::bc6h::compress_blocks(
-            &quality.into(),
-            &surface,
-        ))
-    }
-}
-
-impl BcnEncode<u8> for Bc7 {
-    fn compress_surface(
-        width: u32,
-        height: u32,
-        rgba8_data: &[u8],
-        quality: Quality,
-    ) -> Result<Vec<u8>, SurfaceError> {
-        // RGBA with 4 bytes per pixel.
-        let surface = intel_tex_2::RgbaSurface {
-            width,
-            height,
-            stride: width * CHANNELS as u32,
-            data: rgba8_data,
-        };
-
-        Ok(intel_tex_2::bc7::compress_blocks(&quality.into(), &surface))
-    }
-}
-
+/// Encode `data` with `stride` elements between the start of each row.
+///
+/// Rows may be padded, so `stride` can be larger than `width * 4`.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(skip_all, fields(format = std::any::type_name::<F>(), width, height))
+)]
 pub fn encode_bcn<F, T>(
     width: u32,
     height: u32,
     data: &[T],
+    stride: usize,
     quality: Quality,
+    options: &EncodeOptions,
 ) -> Result<Vec<u8>, SurfaceError>
 where
     F: BcnEncode<T>,
+    T: Sync,
 {
     // Surface dimensions are not validated yet and may cause overflow.
-    let expected_size = mip_size(
+    let overflow = || SurfaceError::PixelCountWouldOverflow {
+        width,
+        height,
+        depth: 1,
+    };
+    let row_size = mip_size(
         width as usize,
-        height as usize,
+        1,
         1,
         BLOCK_WIDTH,
-        BLOCK_HEIGHT,
         1,
-        ELEMENTS_PER_BLOCK,
+        1,
+        BLOCK_WIDTH * CHANNELS,
     )
-    .ok_or(SurfaceError::PixelCountWouldOverflow {
-        width,
-        height,
-        depth: 1,
-    })?;
+    .ok_or_else(overflow)?;
+    debug_assert!(stride >= row_size, "rows should not overlap");
+
+    // Only the last row can omit its padding.
+    let rows = height.div_ceil(BLOCK_HEIGHT as u32).max(1) as usize * BLOCK_HEIGHT;
+    let expected_size = stride
+        .checked_mul(rows - 1)
+        .and_then(|size| size.checked_add(row_size))
+        .ok_or_else(overflow)?;
 
     // The surface must be a multiple of the block dimensions for safety.
     if data.len() < expected_size {
@@ -304,7 +70,59 @@ where
         });
     }
 
-    F::compress_surface(width, height, data, quality)
+    compress_strips::<F, T>(width, height, data, stride, quality, options)
+}
+
+#[cfg(feature = "rayon")]
+fn compress_strips<F, T>(
+    width: u32,
+    height: u32,
+    data: &[T],
+    stride: usize,
+    quality: Quality,
+    options: &EncodeOptions,
+) -> Result<Vec<u8>, SurfaceError>
+where
+    F: BcnEncode<T>,
+    T: Sync,
+{
+    use rayon::prelude::*;
+
+    // Blocks are stored in row-major order, so each strip of block rows can be encoded separately.
+    let strip_height = options
+        .parallel_block_rows
+        .max(1)
+        .saturating_mul(BLOCK_HEIGHT as u32);
+    if strip_height >= height {
+        return F::compress_surface(width, height, data, stride, quality, options);
+    }
+
+    let strips = (0..height)
+        .step_by(strip_height as usize)
+        .collect::<Vec<_>>()
+        .into_par_iter()
+        .map(|y| {
+            let strip_data = &data[y as usize * stride..];
+            let strip_height = strip_height.min(height - y);
+            F::compress_surface(width, strip_height, strip_data, stride, quality, options)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(strips.concat())
+}
+
+#[cfg(not(feature = "rayon"))]
+fn compress_strips<F, T>(
+    width: u32,
+    height: u32,
+    data: &[T],
+    stride: usize,
+    quality: Quality,
+    options: &EncodeOptions,
+) -> Result<Vec<u8>, SurfaceError>
+where
+    F: BcnEncode<T>,
+{
+    F::compress_surface(width, height, data, stride, quality, options)
 }
 
 // TODO: Rework these tests.
@@ -313,12 +131,17 @@ where
 mod tests {
     use super::*;
 
+    use crate::bcn::{Bc1, Bc2, Bc3, Bc4, Bc5, Bc6, Bc7};
+    use crate::AlphaBlockMode;
+
+    const ELEMENTS_PER_BLOCK: usize = BLOCK_WIDTH * BLOCK_HEIGHT * CHANNELS;
+
     // TODO: Create tests for data length since we can't know what the compressed blocks should be?
     // TODO: Test edge cases and type conversions?
     // TODO: Add tests for validating the input length.
     // TODO: Will compression fail for certain pixel values (test with fuzz tests?)
     fn check_compress_bcn<T: BcnEncode<u8>>(rgba: &[u8], quality: Quality) {
-        encode_bcn::<T, u8>(4, 4, rgba, quality).unwrap();
+        encode_bcn::<T, u8>(4, 4, rgba, 4 * 4, quality, &EncodeOptions::default()).unwrap();
     }
 
     #[test]
@@ -329,6 +152,75 @@ mod tests {
         check_compress_bcn::<Bc1>(&rgba, Quality::Slow);
     }
 
+    #[test]
+    fn bc1_compress_palette() {
+        let rgba: Vec<_> = (0..8 * 8)
+            .flat_map(|i| [(i * 4) as u8, 255 - (i * 3) as u8, (i % 8 * 32) as u8, 255])
+            .collect();
+        let palette = vec![[255, 0, 0], [0, 255, 0], [0, 0, 255], [255, 255, 255]];
+        let options = EncodeOptions {
+            bc1_palette: Some(palette.clone()),
+            ..Default::default()
+        };
+        let bc1 = encode_bcn::<Bc1, u8>(8, 8, &rgba, 8 * 4, Quality::Fast, &options).unwrap();
+        let decoded = crate::bcn::decode_bcn::<Bc1, u8>(8, 8, &bc1).unwrap();
+
+        // Decode each pair of endpoints with all 4 indices.
+        // These palette colors are exactly representable in 5:6:5 bits.
+        let endpoints = [0xF800u16, 0x07E0, 0x001F, 0xFFFF];
+        let mut colors = Vec::new();
+        for c0 in endpoints {
+            for c1 in endpoints.iter().filter(|c1| c0 > **c1) {
+                let block = c0 as u64 | (*c1 as u64) << 16 | 0xE4E4E4E4 << 32;
+                let decoded =
+                    crate::bcn::decode_bcn::<Bc1, u8>(4, 4, &block.to_le_bytes()).unwrap();
+                colors.extend(decoded.chunks_exact(4).map(|c| c.to_vec()));
+            }
+        }
+
+        for pixel in decoded.chunks_exact(4) {
+            assert!(colors.iter().any(|c| c == pixel));
+        }
+    }
+
+    #[test]
+    fn bc1_compress_four_color() {
+        // Solid colors may use equal endpoints or the 3 color mode by default.
+        for color in [[0u8, 0, 0, 255], [255, 255, 255, 255], [64, 128, 192, 255]] {
+            let rgba = color.repeat(8 * 8);
+            let options = EncodeOptions {
+                bc1_four_color: true,
+                ..Default::default()
+            };
+            let bc1 = encode_bcn::<Bc1, u8>(8, 8, &rgba, 8 * 4, Quality::Fast, &options).unwrap();
+            for block in bc1.chunks_exact(8) {
+                let c0 = u16::from_le_bytes([block[0], block[1]]);
+                let c1 = u16::from_le_bytes([block[2], block[3]]);
+                assert!(c0 > c1);
+            }
+        }
+    }
+
+    #[test]
+    fn bc1_compress_palette_four_color() {
+        // Solid palette colors use the nearest other palette color for the 4 color mode.
+        let rgba = [255u8, 0, 0, 255].repeat(8 * 8);
+        let options = EncodeOptions {
+            bc1_palette: Some(vec![[255, 0, 0], [0, 0, 255], [128, 0, 0]]),
+            bc1_four_color: true,
+            ..Default::default()
+        };
+        let bc1 = encode_bcn::<Bc1, u8>(8, 8, &rgba, 8 * 4, Quality::Fast, &options).unwrap();
+        for block in bc1.chunks_exact(8) {
+            let c0 = u16::from_le_bytes([block[0], block[1]]);
+            let c1 = u16::from_le_bytes([block[2], block[3]]);
+            assert_eq!((0xF800, 0x8000), (c0, c1));
+        }
+
+        let decoded = crate::bcn::decode_bcn::<Bc1, u8>(8, 8, &bc1).unwrap();
+        assert_eq!(rgba, decoded);
+    }
+
     #[test]
     fn bc2_compress() {
         let rgba = vec![64u8; ELEMENTS_PER_BLOCK];
@@ -353,6 +245,29 @@ mod tests {
         check_compress_bcn::<Bc4>(&rgba, Quality::Slow);
     }
 
+    #[test]
+    fn bc4_compress_alpha_block_mode() {
+        // A smooth ramp benefits from more interpolated values.
+        let rgba: Vec<_> = (0..16).flat_map(|i| [i * 8 + 4; 4]).collect();
+        let encode = |alpha_block_mode| {
+            let options = EncodeOptions {
+                alpha_block_mode,
+                ..Default::default()
+            };
+            encode_bcn::<Bc4, u8>(4, 4, &rgba, 4 * 4, Quality::Fast, &options).unwrap()
+        };
+
+        let eight = encode(AlphaBlockMode::EightValues);
+        assert!(eight[0] > eight[1]);
+        let decoded = crate::bcn::decode_bcn::<Bc4, u8>(4, 4, &eight).unwrap();
+        for (a, b) in rgba.chunks_exact(4).zip(decoded.chunks_exact(4)) {
+            assert!(a[0].abs_diff(b[0]) <= 10);
+        }
+
+        let six = encode(AlphaBlockMode::SixValues);
+        assert!(six[0] <= six[1]);
+    }
+
     #[test]
     fn bc5_compress() {
         let rgba = vec![64u8; ELEMENTS_PER_BLOCK];
@@ -369,6 +284,114 @@ mod tests {
         check_compress_bcn::<Bc6>(&rgba, Quality::Slow);
     }
 
+    #[test]
+    fn bc6_compress_slow_error() {
+        // An HDR sky gradient with a bright sun that doesn't fit exactly in a single block.
+        let rgba: Vec<_> = (0..32 * 32)
+            .flat_map(|i| {
+                let (x, y) = ((i % 32) as f32 / 32.0, (i / 32) as f32 / 32.0);
+                let sun = 200.0 * (-40.0 * ((x - 0.7).powi(2) + (y - 0.3).powi(2)).sqrt()).exp();
+                [
+                    0.2 + y * 2.0 + sun,
+                    0.4 + y + sun * 0.9,
+                    1.5 - y + sun * 0.7,
+                    1.0,
+                ]
+            })
+            .collect();
+
+        // Compare values in log space since BC6H precision is relative to the magnitude.
+        let error = |quality| {
+            let bc6 =
+                encode_bcn::<Bc6, f32>(32, 32, &rgba, 32 * 4, quality, &EncodeOptions::default())
+                    .unwrap();
+            let decoded = crate::bcn::decode_bcn::<Bc6, f32>(32, 32, &bc6).unwrap();
+            rgba.chunks_exact(4)
+                .zip(decoded.chunks_exact(4))
+                .flat_map(|(a, b)| (0..3).map(|c| (a[c].ln_1p() - b[c].ln_1p()).powi(2)))
+                .sum::<f32>()
+        };
+
+        assert!(error(Quality::Slow) < error(Quality::Fast));
+    }
+
+    #[test]
+    fn compress_parallel_block_rows() {
+        // Strips should produce the same blocks as encoding the entire surface.
+        let rgba: Vec<_> = (0..8 * 12 * 4).map(|i| i as u8).collect();
+        let options = EncodeOptions {
+            parallel_block_rows: 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            encode_bcn::<Bc3, u8>(8, 12, &rgba, 8 * 4, Quality::Fast, &Default::default()),
+            encode_bcn::<Bc3, u8>(8, 12, &rgba, 8 * 4, Quality::Fast, &options)
+        );
+    }
+
+    #[test]
+    fn compress_padded_stride() {
+        // Encode the left block of an 8x4 image with different colors in the padding.
+        let rgba: Vec<_> = (0..8 * 4)
+            .flat_map(|i| {
+                if i % 8 < 4 {
+                    [1, 2, 3, 4]
+                } else {
+                    [i as u8; 4]
+                }
+            })
+            .collect();
+
+        // The last row does not need to include its padding.
+        let bc7 = encode_bcn::<Bc7, u8>(
+            4,
+            4,
+            &rgba[..rgba.len() - 16],
+            8 * 4,
+            Quality::Fast,
+            &EncodeOptions::default(),
+        )
+        .unwrap();
+        let decoded = crate::bcn::decode_bcn::<Bc7, u8>(4, 4, &bc7).unwrap();
+        assert_eq!([1, 2, 3, 4].repeat(16), decoded);
+
+        let bc4 =
+            encode_bcn::<Bc4, u8>(4, 4, &rgba, 8 * 4, Quality::Fast, &Default::default()).unwrap();
+        let tight = vec![1u8; ELEMENTS_PER_BLOCK];
+        assert_eq!(
+            encode_bcn::<Bc4, u8>(
+                4,
+                4,
+                &tight,
+                4 * 4,
+                Quality::Fast,
+                &EncodeOptions::default()
+            )
+            .unwrap(),
+            bc4
+        );
+    }
+
+    #[test]
+    fn bc7_compress_single_color_blocks() {
+        // The right block has different colors and uses the normal encoder.
+        let rgba: Vec<_> = (0..8 * 4)
+            .flat_map(|i| {
+                if i % 8 < 4 {
+                    [1, 2, 3, 4]
+                } else {
+                    [i as u8; 4]
+                }
+            })
+            .collect();
+        let bc7 =
+            encode_bcn::<Bc7, u8>(8, 4, &rgba, 8 * 4, Quality::Fast, &EncodeOptions::default())
+                .unwrap();
+
+        let decoded = crate::bcn::decode_bcn::<Bc7, u8>(4, 4, &bc7[..16]).unwrap();
+        assert_eq!([1, 2, 3, 4].repeat(16), decoded);
+    }
+
     #[test]
     fn bc7_compress() {
         let rgba = vec![64u8; ELEMENTS_PER_BLOCK];
@@ -0,0 +1,542 @@
+// Encoding using Intel's ISPC texture compressor through intel_tex_2.
+use half::f16;
+
+use crate::{ContentHint, EncodeOptions, Quality, SurfaceError};
+
+use super::super::{
+    alpha::set_alpha_block_mode,
+    refine::{
+        force_four_color_blocks, refine_color_blocks, snap_color_blocks_to_palette, UNIFORM_WEIGHTS,
+    },
+    Bc1, Bc2, Bc3, Bc4, Bc5, Bc6, Bc7, BLOCK_HEIGHT, BLOCK_WIDTH, CHANNELS,
+};
+use super::BcnEncode;
+
+// Quality modes are optimized for a balance of speed and quality.
+impl From<Quality> for intel_tex_2::bc6h::EncodeSettings {
+    fn from(value: Quality) -> Self {
+        // 1024x1024 hdr rgb (very fast 13ms, very 110ms, basic 300ms, slow 1.2s, very slow 3s)
+        // very fast has noticeably higher error on detailed images than the other settings.
+        // very slow takes more than twice as long as slow for almost no improvement.
+        match value {
+            Quality::Fast => intel_tex_2::bc6h::very_settings(),
+            Quality::Normal => intel_tex_2::bc6h::basic_settings(),
+            Quality::Slow | Quality::Lossless => intel_tex_2::bc6h::slow_settings(),
+        }
+    }
+}
+
+impl From<Quality> for intel_tex_2::bc7::EncodeSettings {
+    fn from(value: Quality) -> Self {
+        // bc7 has almost imperceptible errors even at ultra_fast
+        // 4k rgba ultra fast (2s), very fast (7s), fast (12s)
+        // intel_tex does not expose channel weights for bc7,
+        // so errors in RGB and alpha always contribute equally.
+        match value {
+            Quality::Fast => intel_tex_2::bc7::alpha_ultra_fast_settings(),
+            Quality::Normal => intel_tex_2::bc7::alpha_very_fast_settings(),
+            Quality::Slow | Quality::Lossless => intel_tex_2::bc7::alpha_fast_settings(),
+        }
+    }
+}
+
+fn bc7_settings(
+    quality: Quality,
+    modes: u8,
+) -> Result<intel_tex_2::bc7::EncodeSettings, SurfaceError> {
+    let mut settings: intel_tex_2::bc7::EncodeSettings = quality.into();
+
+    // intel_tex only toggles groups of modes.
+    // A group can only be used if all of its modes are enabled.
+    // Mode 2 is the only mode that can be skipped on its own.
+    let enabled = |mode: u8| modes & (1 << mode) != 0;
+    let groups = [
+        enabled(0),
+        enabled(1) && enabled(3) && enabled(7),
+        enabled(4) && enabled(5),
+        enabled(6),
+    ];
+    if !groups.contains(&true) {
+        return Err(SurfaceError::UnsupportedBc7Modes { modes });
+    }
+
+    // Fall back to any enabled group if the quality preset disables all of them.
+    let mut selection = [0, 1, 2, 3].map(|i| settings.mode_selection[i] && groups[i]);
+    if !selection.contains(&true) {
+        selection = groups;
+    }
+
+    settings.mode_selection = selection;
+    settings.skip_mode2 |= !enabled(2);
+    Ok(settings)
+}
+
+// Normal maps favor accurate X and Y since Z is often reconstructed or less important for shading.
+fn color_weights(options: &EncodeOptions) -> [i32; 3] {
+    match options.content_hint {
+        ContentHint::Color => UNIFORM_WEIGHTS,
+        ContentHint::NormalMap => [4, 4, 1],
+    }
+}
+
+impl BcnEncode<u8> for Bc1 {
+    fn compress_surface(
+        width: u32,
+        height: u32,
+        rgba8_data: &[u8],
+        stride: usize,
+        quality: Quality,
+        options: &EncodeOptions,
+    ) -> Result<Vec<u8>, SurfaceError> {
+        // RGBA with 4 bytes per pixel.
+        let surface = intel_tex_2::RgbaSurface {
+            width,
+            height,
+            stride: stride as u32,
+            data: rgba8_data,
+        };
+
+        let mut data = intel_tex_2::bc1::compress_blocks(&surface);
+        if quality == Quality::Slow {
+            refine_color_blocks(&surface, &mut data, 8, 0, false, color_weights(options));
+        }
+        if let Some(palette) = &options.bc1_palette {
+            // Snapping already uses the 4 color mode and keeps the endpoints in the palette.
+            let four_color = options.bc1_four_color;
+            snap_color_blocks_to_palette(&surface, &mut data, 8, 0, palette, four_color);
+        } else if options.bc1_four_color {
+            force_four_color_blocks(&surface, &mut data, 8, 0, color_weights(options));
+        }
+        Ok(data)
+    }
+}
+
+impl BcnEncode<u8> for Bc2 {
+    fn compress_surface(
+        width: u32,
+        height: u32,
+        rgba8_data: &[u8],
+        stride: usize,
+        quality: Quality,
+        options: &EncodeOptions,
+    ) -> Result<Vec<u8>, SurfaceError> {
+        // RGBA with 4 bytes per pixel.
+        let surface = intel_tex_2::RgbaSurface {
+            width,
+            height,
+            stride: stride as u32,
+            data: rgba8_data,
+        };
+
+        // BC2 is rarely used and not supported by intel_tex.
+        // The RGB block and mode is identical BC3, so we only need to encode alpha.
+        // https://learn.microsoft.com/en-us/windows/win32/direct3d10/d3d10-graphics-programming-guide-resources-block-compression#bc2
+        let mut bc3_data = intel_tex_2::bc3::compress_blocks(&surface);
+        if quality == Quality::Slow {
+            refine_color_blocks(&surface, &mut bc3_data, 16, 8, true, color_weights(options));
+        }
+
+        let mut data = Vec::new();
+
+        // TODO: Tests for this.
+        // TODO: Test surfaces not divisible by block dimensions.
+        let mut block_index = 0;
+        for y in (0..height).step_by(BLOCK_HEIGHT) {
+            for x in (0..width).step_by(BLOCK_WIDTH) {
+                let bc2_block = encode_bc2_block(x, y, &surface, &bc3_data, block_index);
+                data.extend_from_slice(&bc2_block.to_le_bytes());
+
+                block_index += 1;
+            }
+        }
+
+        Ok(data)
+    }
+}
+
+fn encode_bc2_block(
+    x: u32,
+    y: u32,
+    surface: &intel_tex_2::RgbaSurface,
+    bc3_data: &[u8],
+    block_index: usize,
+) -> u128 {
+    let alpha = sharp_alpha_block(x, y, surface);
+
+    let block_size = 16;
+    let block_start = block_index * block_size;
+    let bc3_block_data = bc3_data[block_start..block_start + block_size]
+        .try_into()
+        .unwrap();
+    let bc3_color_block = u128::from_le_bytes(bc3_block_data) >> u64::BITS;
+
+    (bc3_color_block << u64::BITS) | alpha as u128
+}
+
+fn sharp_alpha_block(x: u32, y: u32, surface: &intel_tex_2::RgbaSurface) -> u64 {
+    // TODO: Use a byte array for better clarity?
+    let mut alpha = 0u64;
+    for i in 0..BLOCK_HEIGHT {
+        for j in 0..BLOCK_WIDTH {
+            // TODO: Is there a simpler way of doing this?
+            let x_final = (x as usize + i).min(surface.width.saturating_sub(1) as usize);
+            let y_final = (y as usize + j).min(surface.height.saturating_sub(1) as usize);
+            let input_index = y_final * surface.stride as usize + x_final * CHANNELS + 3;
+
+            let output_index = j * BLOCK_HEIGHT + i;
+
+            // 4-bit alpha for each pixel.
+            alpha |= ((surface.data[input_index] / 17) as u64) << (output_index * 4);
+        }
+    }
+    alpha
+}
+
+impl BcnEncode<u8> for Bc3 {
+    fn compress_surface(
+        width: u32,
+        height: u32,
+        rgba8_data: &[u8],
+        stride: usize,
+        quality: Quality,
+        options: &EncodeOptions,
+    ) -> Result<Vec<u8>, SurfaceError> {
+        // RGBA with 4 bytes per pixel.
+        let surface = intel_tex_2::RgbaSurface {
+            width,
+            height,
+            stride: stride as u32,
+            data: rgba8_data,
+        };
+
+        // The RGB data is stored after the 8 byte alpha block.
+        let mut data = intel_tex_2::bc3::compress_blocks(&surface);
+        if quality == Quality::Slow {
+            refine_color_blocks(&surface, &mut data, 16, 8, true, color_weights(options));
+        }
+
+        let alpha = |x, y| rgba8_data[y * stride + x * CHANNELS + 3];
+        set_alpha_block_mode(
+            width,
+            height,
+            alpha,
+            &mut data,
+            16,
+            options.alpha_block_mode,
+        );
+        Ok(data)
+    }
+}
+
+impl BcnEncode<u8> for Bc4 {
+    fn compress_surface(
+        width: u32,
+        height: u32,
+        rgba8_data: &[u8],
+        stride: usize,
+        _: Quality,
+        options: &EncodeOptions,
+    ) -> Result<Vec<u8>, SurfaceError> {
+        // R8 with 4 bytes per pixel.
+        let r8_data: Vec<_> = pixels(width, height, rgba8_data, stride)
+            .map(|p| p[0])
+            .collect();
+        let surface = intel_tex_2::RSurface {
+            width,
+            height,
+            stride: width,
+            data: &r8_data,
+        };
+
+        let mut data = intel_tex_2::bc4::compress_blocks(&surface);
+
+        let red = |x, y| r8_data[y * width as usize + x];
+        set_alpha_block_mode(width, height, red, &mut data, 8, options.alpha_block_mode);
+        Ok(data)
+    }
+}
+
+impl BcnEncode<u8> for Bc5 {
+    fn compress_surface(
+        width: u32,
+        height: u32,
+        rgba8_data: &[u8],
+        stride: usize,
+        _: Quality,
+        _: &EncodeOptions,
+    ) -> Result<Vec<u8>, SurfaceError> {
+        // RG8 with 2 bytes per pixel.
+        let rg8_data: Vec<_> = pixels(width, height, rgba8_data, stride)
+            .flat_map(|p| [p[0], p[1]])
+            .collect();
+        let surface = intel_tex_2::RgSurface {
+            width,
+            height,
+            stride: width * 2,
+            data: &rg8_data,
+        };
+
+        Ok(intel_tex_2::bc5::compress_blocks(&surface))
+    }
+}
+
+// Iterate over the RGBA pixels in each row while skipping any padding between rows.
+fn pixels<T>(width: u32, height: u32, data: &[T], stride: usize) -> impl Iterator<Item = &[T]> {
+    data.chunks(stride)
+        .take(height as usize)
+        .flat_map(move |row| row[..width as usize * CHANNELS].chunks_exact(CHANNELS))
+}
+
+impl BcnEncode<f32> for Bc6 {
+    fn compress_surface(
+        width: u32,
+        height: u32,
+        rgba8_data: &[f32],
+        stride: usize,
+        quality: Quality,
+        options: &EncodeOptions,
+    ) -> Result<Vec<u8>, SurfaceError> {
+        // The BC6H encoder expects the data to be in half precision floating point.
+        // This differs from the other formats that expect [u8; 4] for each pixel.
+        let scale = options.bc6_scale();
+        let f16_data: Vec<f16> = pixels(width, height, rgba8_data, stride)
+            .flatten()
+            .map(|v| f16::from_f32(v * scale))
+            .collect();
+
+        let surface = intel_tex_2::RgbaSurface {
+            width,
+            height,
+            stride: width * 4 * std::mem::size_of::<f16>() as u32,
+            data: bytemuck::cast_slice(&f16_data),
+        };
+
+        Ok(intel_tex_2::bc6h::compress_blocks(
+            &quality.into(),
+            &surface,
+        ))
+    }
+}
+
+impl BcnEncode<f16> for Bc6 {
+    fn compress_surface(
+        width: u32,
+        height: u32,
+        rgba16_data: &[f16],
+        stride: usize,
+        quality: Quality,
+        options: &EncodeOptions,
+    ) -> Result<Vec<u8>, SurfaceError> {
+        // The BC6H encoder already expects half precision floating point.
+        let scaled: Vec<f16>;
+        let rgba16_data = if options.bc6_exposure != 0 {
+            let scale = options.bc6_scale();
+            scaled = rgba16_data
+                .iter()
+                .map(|v| f16::from_f32(v.to_f32() * scale))
+                .collect();
+            &scaled
+        } else {
+            rgba16_data
+        };
+
+        let surface = intel_tex_2::RgbaSurface {
+            width,
+            height,
+            stride: (stride * std::mem::size_of::<f16>()) as u32,
+            data: bytemuck::cast_slice(rgba16_data),
+        };
+
+        Ok(intel_tex_2::bc6h::compress_blocks(
+            &quality.into(),
+            &surface,
+        ))
+    }
+}
+
+impl BcnEncode<u8> for Bc6 {
+    fn compress_surface(
+        width: u32,
+        height: u32,
+        rgba8_data: &[u8],
+        stride: usize,
+        quality: Quality,
+        options: &EncodeOptions,
+    ) -> Result<Vec<u8>, SurfaceError> {
+        // The BC6H encoder expects the data to be in half precision floating point.
+        // This differs from the other formats that expect [u8; 4] for each pixel.
+        let scale = options.bc6_scale();
+        let f16_data: Vec<f16> = pixels(width, height, rgba8_data, stride)
+            .flatten()
+            .map(|v| f16::from_f32(*v as f32 / 255.0 * scale))
+            .collect();
+
+        let surface = intel_tex_2::RgbaSurface {
+            width,
+            height,
+            stride: width * 4 * std::mem::size_of::<f16>() as u32,
+            data: bytemuck::cast_slice(&f16_data),
+        };
+
+        Ok(intel_tex_2::bc6h::compress_blocks(
+            &quality.into(),
+            &surface,
+        ))
+    }
+}
+
+impl BcnEncode<u8> for Bc7 {
+    fn compress_surface(
+        width: u32,
+        height: u32,
+        rgba8_data: &[u8],
+        stride: usize,
+        quality: Quality,
+        options: &EncodeOptions,
+    ) -> Result<Vec<u8>, SurfaceError> {
+        // RGBA with 4 bytes per pixel.
+        let surface = intel_tex_2::RgbaSurface {
+            width,
+            height,
+            stride: stride as u32,
+            data: rgba8_data,
+        };
+
+        let settings = bc7_settings(quality, options.bc7_modes)?;
+        let mut data = intel_tex_2::bc7::compress_blocks(&settings, &surface);
+
+        // Blocks with a single color can be encoded exactly using mode 5.
+        if options.bc7_modes & (1 << 5) != 0 {
+            replace_single_color_bc7_blocks(&surface, &mut data);
+        }
+
+        Ok(data)
+    }
+}
+
+fn replace_single_color_bc7_blocks(surface: &intel_tex_2::RgbaSurface, bc7_data: &mut [u8]) {
+    let blocks_wide = surface.width as usize / BLOCK_WIDTH;
+
+    for (i, block) in bc7_data.chunks_exact_mut(16).enumerate() {
+        let x = (i % blocks_wide) * BLOCK_WIDTH;
+        let y = (i / blocks_wide) * BLOCK_HEIGHT;
+
+        let pixel = |j: usize| {
+            let index =
+                (y + j / BLOCK_WIDTH) * surface.stride as usize + (x + j % BLOCK_WIDTH) * CHANNELS;
+            &surface.data[index..index + CHANNELS]
+        };
+        if (1..BLOCK_WIDTH * BLOCK_HEIGHT).all(|j| pixel(j) == pixel(0)) {
+            let rgba = pixel(0).try_into().unwrap();
+            block.copy_from_slice(&bc7_single_color_block(rgba).to_le_bytes());
+        }
+    }
+}
+
+// Mode 5 color endpoints for each 8-bit value when interpolating with index 1.
+const BC7_MODE5_SINGLE_COLOR: [[u8; 2]; 256] = bc7_mode5_single_color_table();
+
+const fn bc7_mode5_single_color_table() -> [[u8; 2]; 256] {
+    const fn expand7(e: i32) -> i32 {
+        (e << 1) | (e >> 6)
+    }
+
+    let mut table = [[0u8; 2]; 256];
+    let mut value = 0;
+    while value < 256 {
+        // Prefer endpoints that are close together.
+        // An exact match always exists with the first endpoint near the value.
+        let mut best_difference = i32::MAX;
+        let mut e0 = if value / 2 >= 2 { value / 2 - 2 } else { 0 };
+        while e0 <= value / 2 + 2 && e0 < 128 {
+            let mut e1 = 0;
+            while e1 < 128 {
+                let interpolated = ((64 - 21) * expand7(e0) + 21 * expand7(e1) + 32) >> 6;
+                let difference = if e0 > e1 { e0 - e1 } else { e1 - e0 };
+                if interpolated == value && difference < best_difference {
+                    table[value as usize] = [e0 as u8, e1 as u8];
+                    best_difference = difference;
+                }
+                e1 += 1;
+            }
+            e0 += 1;
+        }
+        value += 1;
+    }
+    table
+}
+
+fn bc7_single_color_block(rgba: [u8; 4]) -> u128 {
+    // Mode 5 with no channel rotation.
+    let mut block = 1u128 << 5;
+    let mut offset = 8;
+
+    // 7-bit color endpoints ordered R0, R1, G0, G1, B0, B1.
+    for c in &rgba[..3] {
+        for e in BC7_MODE5_SINGLE_COLOR[*c as usize] {
+            block |= (e as u128) << offset;
+            offset += 7;
+        }
+    }
+
+    // 8-bit alpha endpoints use the exact value.
+    block |= (rgba[3] as u128) << offset;
+    block |= (rgba[3] as u128) << (offset + 8);
+    offset += 16;
+
+    // Every pixel uses color index 1.
+    // The first index omits its most significant bit.
+    block |= 1 << offset;
+    offset += 1;
+    for _ in 1..16 {
+        block |= 1 << offset;
+        offset += 2;
+    }
+
+    // The alpha indices are all 0.
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bc7_single_color_block_exact() {
+        for i in 0..=255u8 {
+            let rgba = [i, 255 - i, i / 3, i ^ 0x55];
+            let bc7 = bc7_single_color_block(rgba).to_le_bytes();
+            let decoded = crate::bcn::decode_bcn::<Bc7, u8>(4, 4, &bc7).unwrap();
+            assert_eq!(rgba.repeat(16), decoded);
+        }
+    }
+
+    #[test]
+    fn bc7_settings_all_modes() {
+        let settings = bc7_settings(Quality::Slow, 0xFF).unwrap();
+        let expected: intel_tex_2::bc7::EncodeSettings = Quality::Slow.into();
+        assert_eq!(expected.mode_selection, settings.mode_selection);
+        assert_eq!(expected.skip_mode2, settings.skip_mode2);
+    }
+
+    #[test]
+    fn bc7_settings_exclude_mode0() {
+        let settings = bc7_settings(Quality::Slow, 0b1111_1110).unwrap();
+        assert!(!settings.mode_selection[0]);
+    }
+
+    #[test]
+    fn bc7_settings_only_mode0() {
+        let settings = bc7_settings(Quality::Fast, 0b0000_0001).unwrap();
+        assert_eq!([true, false, false, false], settings.mode_selection);
+        assert!(settings.skip_mode2);
+    }
+
+    #[test]
+    fn bc7_settings_no_modes() {
+        assert_eq!(
+            Some(SurfaceError::UnsupportedBc7Modes { modes: 0b1000_0000 }),
+            bc7_settings(Quality::Fast, 0b1000_0000).err()
+        );
+    }
+}
@@ -0,0 +1,464 @@
+// An additional refinement pass for the RGB endpoints of BC1 and BC3 blocks.
+// intel_tex does not have quality settings for these formats,
+// so higher qualities try to improve on the blocks it produces.
+use super::{BLOCK_HEIGHT, BLOCK_WIDTH, CHANNELS};
+
+// Each refinement step can move the endpoints closer to a local minimum.
+const REFINE_ITERATIONS: usize = 2;
+
+/// Equal error weights for the R, G, and B channels.
+pub const UNIFORM_WEIGHTS: [i32; 3] = [1, 1, 1];
+
+/// Refine the RGB endpoints and indices for each 8 byte color block at `color_offset` in `blocks`.
+///
+/// The color blocks for BC3 always use 4 colors, so `always_four_color` should only be `false` for BC1.
+/// The squared error for each channel is scaled by `weights`.
+pub fn refine_color_blocks(
+    surface: &intel_tex_2::RgbaSurface,
+    blocks: &mut [u8],
+    block_size: usize,
+    color_offset: usize,
+    always_four_color: bool,
+    weights: [i32; 3],
+) {
+    let blocks_wide = surface.width as usize / BLOCK_WIDTH;
+    let blocks_high = surface.height as usize / BLOCK_HEIGHT;
+
+    for (i, block) in blocks
+        .chunks_exact_mut(block_size)
+        .take(blocks_wide * blocks_high)
+        .enumerate()
+    {
+        let x = (i % blocks_wide) * BLOCK_WIDTH;
+        let y = (i / blocks_wide) * BLOCK_HEIGHT;
+        let pixels = block_pixels(surface, x, y);
+
+        let color = &mut block[color_offset..color_offset + 8];
+        let refined = refine_color_block(
+            &pixels,
+            u64::from_le_bytes(color.try_into().unwrap()),
+            always_four_color,
+            weights,
+        );
+        color.copy_from_slice(&refined.to_le_bytes());
+    }
+}
+
+/// Replace the RGB endpoints for each color block with the nearest colors in `palette`
+/// and recalculate the indices.
+///
+/// If `four_color` is `true`, equal endpoints are replaced with two different palette colors when possible
+/// so that the block uses the 4 color mode.
+pub fn snap_color_blocks_to_palette(
+    surface: &intel_tex_2::RgbaSurface,
+    blocks: &mut [u8],
+    block_size: usize,
+    color_offset: usize,
+    palette: &[[u8; 3]],
+    four_color: bool,
+) {
+    // Compare colors after quantizing to match the decoded values.
+    let palette: Vec<_> = palette
+        .iter()
+        .map(|c| pack_565(c.map(|c| c as f32)))
+        .collect();
+
+    let blocks_wide = surface.width as usize / BLOCK_WIDTH;
+    let blocks_high = surface.height as usize / BLOCK_HEIGHT;
+
+    for (i, block) in blocks
+        .chunks_exact_mut(block_size)
+        .take(blocks_wide * blocks_high)
+        .enumerate()
+    {
+        let x = (i % blocks_wide) * BLOCK_WIDTH;
+        let y = (i / blocks_wide) * BLOCK_HEIGHT;
+        let pixels = block_pixels(surface, x, y);
+
+        let color = &mut block[color_offset..color_offset + 8];
+        let (c0, c1, _) = unpack_block(u64::from_le_bytes(color.try_into().unwrap()));
+        let c0 = nearest_color(&palette, c0);
+        let mut c1 = nearest_color(&palette, c1);
+        if four_color && c0 == c1 {
+            // Changing an endpoint by one step would no longer be a palette color.
+            c1 = nearest_other_color(&palette, c0).unwrap_or(c1);
+        }
+        let snapped = encode_block(&pixels, c0, c1, UNIFORM_WEIGHTS);
+        color.copy_from_slice(&snapped.to_le_bytes());
+    }
+}
+
+/// Convert each color block at `color_offset` in `blocks` using the 3 color mode
+/// to the 4 color mode and recalculate the indices.
+pub fn force_four_color_blocks(
+    surface: &intel_tex_2::RgbaSurface,
+    blocks: &mut [u8],
+    block_size: usize,
+    color_offset: usize,
+    weights: [i32; 3],
+) {
+    let blocks_wide = surface.width as usize / BLOCK_WIDTH;
+    let blocks_high = surface.height as usize / BLOCK_HEIGHT;
+
+    for (i, block) in blocks
+        .chunks_exact_mut(block_size)
+        .take(blocks_wide * blocks_high)
+        .enumerate()
+    {
+        let x = (i % blocks_wide) * BLOCK_WIDTH;
+        let y = (i / blocks_wide) * BLOCK_HEIGHT;
+        let pixels = block_pixels(surface, x, y);
+
+        let color = &mut block[color_offset..color_offset + 8];
+        let block = four_color_block(
+            &pixels,
+            u64::from_le_bytes(color.try_into().unwrap()),
+            weights,
+        );
+        color.copy_from_slice(&block.to_le_bytes());
+    }
+}
+
+fn four_color_block(pixels: &[[i32; 3]; 16], block: u64, weights: [i32; 3]) -> u64 {
+    let (c0, c1, _) = unpack_block(block);
+    if c0 > c1 {
+        return block;
+    }
+
+    // Equal endpoints need to differ by one step to select the 4 color mode.
+    let (c0, c1) = match c0.cmp(&c1) {
+        std::cmp::Ordering::Equal if c0 == 0 => (1, 0),
+        std::cmp::Ordering::Equal => (c0, c1 - 1),
+        _ => (c1, c0),
+    };
+    encode_block(pixels, c0, c1, weights)
+}
+
+fn nearest_color(palette: &[u16], color: u16) -> u16 {
+    let rgb = unpack_565(color);
+    palette
+        .iter()
+        .copied()
+        .min_by_key(|c| distance(&unpack_565(*c), &rgb, UNIFORM_WEIGHTS))
+        .unwrap_or(color)
+}
+
+fn nearest_other_color(palette: &[u16], color: u16) -> Option<u16> {
+    let rgb = unpack_565(color);
+    palette
+        .iter()
+        .copied()
+        .filter(|c| *c != color)
+        .min_by_key(|c| distance(&unpack_565(*c), &rgb, UNIFORM_WEIGHTS))
+}
+
+fn block_pixels(surface: &intel_tex_2::RgbaSurface, x: usize, y: usize) -> [[i32; 3]; 16] {
+    let mut pixels = [[0; 3]; 16];
+    for (i, pixel) in pixels.iter_mut().enumerate() {
+        let index =
+            (y + i / BLOCK_WIDTH) * surface.stride as usize + (x + i % BLOCK_WIDTH) * CHANNELS;
+        *pixel = [0, 1, 2].map(|c| surface.data[index + c] as i32);
+    }
+    pixels
+}
+
+fn refine_color_block(
+    pixels: &[[i32; 3]; 16],
+    block: u64,
+    always_four_color: bool,
+    weights: [i32; 3],
+) -> u64 {
+    let mut best = block;
+    let mut best_error = block_error(pixels, block, always_four_color, weights);
+
+    // Refine the existing indices as well as a simple fit to the range of the pixels.
+    // Starting from the existing block only works for 4 color blocks.
+    let (c0, c1, _) = unpack_block(block);
+    let mut candidates = vec![range_fit(pixels, weights)];
+    if always_four_color || c0 > c1 {
+        candidates.push(block);
+    }
+
+    for mut candidate in candidates {
+        for _ in 0..REFINE_ITERATIONS {
+            let error = block_error(pixels, candidate, true, weights);
+            if error < best_error {
+                best = candidate;
+                best_error = error;
+            }
+
+            match least_squares_fit(pixels, candidate, weights) {
+                Some(refined) => candidate = refined,
+                None => break,
+            }
+        }
+
+        let error = block_error(pixels, candidate, true, weights);
+        if error < best_error {
+            best = candidate;
+            best_error = error;
+        }
+    }
+
+    best
+}
+
+fn range_fit(pixels: &[[i32; 3]; 16], weights: [i32; 3]) -> u64 {
+    // Use the pixels with the smallest and largest projection onto the bounding box diagonal.
+    let min = [0, 1, 2].map(|c| pixels.iter().map(|p| p[c]).min().unwrap());
+    let max = [0, 1, 2].map(|c| pixels.iter().map(|p| p[c]).max().unwrap());
+    let axis = [0, 1, 2].map(|c| max[c] - min[c]);
+
+    let project = |p: &&[i32; 3]| p[0] * axis[0] + p[1] * axis[1] + p[2] * axis[2];
+    let start = pixels.iter().min_by_key(project).unwrap();
+    let end = pixels.iter().max_by_key(project).unwrap();
+
+    let to_f32 = |p: &[i32; 3]| p.map(|c| c as f32);
+    encode_block(
+        pixels,
+        pack_565(to_f32(end)),
+        pack_565(to_f32(start)),
+        weights,
+    )
+}
+
+fn least_squares_fit(pixels: &[[i32; 3]; 16], block: u64, weights: [i32; 3]) -> Option<u64> {
+    // Solve for endpoints a and b that minimize the error of (1 - t) * a + t * b for each pixel.
+    let (_, _, indices) = unpack_block(block);
+
+    let mut alpha2 = 0.0;
+    let mut beta2 = 0.0;
+    let mut alphabeta = 0.0;
+    let mut alphax = [0.0; 3];
+    let mut betax = [0.0; 3];
+    for (i, pixel) in pixels.iter().enumerate() {
+        let t = [0.0, 1.0, 1.0 / 3.0, 2.0 / 3.0][((indices >> (i * 2)) & 0b11) as usize];
+        let alpha = 1.0 - t;
+        let beta = t;
+
+        alpha2 += alpha * alpha;
+        beta2 += beta * beta;
+        alphabeta += alpha * beta;
+        for c in 0..3 {
+            alphax[c] += alpha * pixel[c] as f32;
+            betax[c] += beta * pixel[c] as f32;
+        }
+    }
+
+    let determinant = alpha2 * beta2 - alphabeta * alphabeta;
+    if determinant.abs() < f32::EPSILON {
+        // All pixels use the same weight, so there is no unique solution.
+        return None;
+    }
+
+    let a = [0, 1, 2].map(|c| (alphax[c] * beta2 - betax[c] * alphabeta) / determinant);
+    let b = [0, 1, 2].map(|c| (betax[c] * alpha2 - alphax[c] * alphabeta) / determinant);
+    Some(encode_block(pixels, pack_565(a), pack_565(b), weights))
+}
+
+fn encode_block(pixels: &[[i32; 3]; 16], c0: u16, c1: u16, weights: [i32; 3]) -> u64 {
+    // 4 color mode requires c0 > c1, so swap the endpoints if needed.
+    // Equal endpoints can only use index 0 since index 3 is transparent for BC1.
+    let (c0, c1) = if c0 < c1 { (c1, c0) } else { (c0, c1) };
+    let palette = palette(c0, c1, true);
+
+    let mut indices = 0u32;
+    if c0 != c1 {
+        for (i, pixel) in pixels.iter().enumerate() {
+            let index = (0..4)
+                .min_by_key(|j| distance(pixel, &palette[*j], weights))
+                .unwrap();
+            indices |= (index as u32) << (i * 2);
+        }
+    }
+
+    c0 as u64 | (c1 as u64) << 16 | (indices as u64) << 32
+}
+
+fn block_error(
+    pixels: &[[i32; 3]; 16],
+    block: u64,
+    always_four_color: bool,
+    weights: [i32; 3],
+) -> i32 {
+    let (c0, c1, indices) = unpack_block(block);
+    let palette = palette(c0, c1, always_four_color || c0 > c1);
+
+    pixels
+        .iter()
+        .enumerate()
+        .map(|(i, pixel)| {
+            distance(
+                pixel,
+                &palette[((indices >> (i * 2)) & 0b11) as usize],
+                weights,
+            )
+        })
+        .sum()
+}
+
+fn unpack_block(block: u64) -> (u16, u16, u32) {
+    (block as u16, (block >> 16) as u16, (block >> 32) as u32)
+}
+
+fn palette(c0: u16, c1: u16, four_color: bool) -> [[i32; 3]; 4] {
+    // Match the color interpolation used for decoding.
+    let p0 = unpack_565(c0);
+    let p1 = unpack_565(c1);
+    if four_color {
+        [
+            p0,
+            p1,
+            [0, 1, 2].map(|c| (2 * p0[c] + p1[c]) / 3),
+            [0, 1, 2].map(|c| (p0[c] + 2 * p1[c]) / 3),
+        ]
+    } else {
+        // The last color is transparent black.
+        [p0, p1, [0, 1, 2].map(|c| (p0[c] + p1[c]) / 2), [0; 3]]
+    }
+}
+
+fn unpack_565(color: u16) -> [i32; 3] {
+    let r = ((color >> 11) & 0x1F) as i32;
+    let g = ((color >> 5) & 0x3F) as i32;
+    let b = (color & 0x1F) as i32;
+    [
+        (r * 527 + 23) >> 6,
+        (g * 259 + 33) >> 6,
+        (b * 527 + 23) >> 6,
+    ]
+}
+
+fn pack_565(color: [f32; 3]) -> u16 {
+    let quantize = |x: f32, max: f32| (x / 255.0 * max).round().clamp(0.0, max) as u16;
+    quantize(color[0], 31.0) << 11 | quantize(color[1], 63.0) << 5 | quantize(color[2], 31.0)
+}
+
+fn distance(a: &[i32; 3], b: &[i32; 3], weights: [i32; 3]) -> i32 {
+    (0..3).map(|c| weights[c] * (a[c] - b[c]).pow(2)).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gradient() -> [[i32; 3]; 16] {
+        std::array::from_fn(|i| [i as i32 * 16, 255 - i as i32 * 8, 64])
+    }
+
+    #[test]
+    fn refine_color_block_improves_error() {
+        let pixels = gradient();
+
+        // Start from the widest possible range with all pixels using the first endpoint.
+        let block = 0xFFFFu64;
+        let refined = refine_color_block(&pixels, block, true, UNIFORM_WEIGHTS);
+
+        assert!(
+            block_error(&pixels, refined, true, UNIFORM_WEIGHTS)
+                < block_error(&pixels, block, true, UNIFORM_WEIGHTS)
+        );
+    }
+
+    #[test]
+    fn refine_color_blocks_improves_error() {
+        // A smooth 8x8 gradient in BC3 blocks starting from white and black endpoints.
+        let rgba: Vec<_> = (0..8 * 8)
+            .flat_map(|i| [(i * 4) as u8, 255 - (i * 3) as u8, (i % 8 * 32) as u8, 255])
+            .collect();
+        let surface = intel_tex_2::RgbaSurface {
+            data: &rgba,
+            width: 8,
+            height: 8,
+            stride: 8 * 4,
+        };
+        let block = 0xFFFFu64.to_le_bytes();
+        let mut blocks = [[0u8; 8], block].concat().repeat(4);
+        refine_color_blocks(&surface, &mut blocks, 16, 8, true, UNIFORM_WEIGHTS);
+
+        for (i, refined) in blocks.chunks_exact(16).enumerate() {
+            let pixels = block_pixels(&surface, i % 2 * 4, i / 2 * 4);
+            let refined = u64::from_le_bytes(refined[8..].try_into().unwrap());
+            assert!(
+                block_error(&pixels, refined, true, UNIFORM_WEIGHTS)
+                    < block_error(&pixels, u64::from_le_bytes(block), true, UNIFORM_WEIGHTS)
+            );
+        }
+    }
+
+    #[test]
+    fn refine_color_block_four_color() {
+        let pixels = gradient();
+        let (c0, c1, _) = unpack_block(refine_color_block(&pixels, 0, false, UNIFORM_WEIGHTS));
+        assert!(c0 > c1);
+    }
+
+    #[test]
+    fn four_color_block_swap_endpoints() {
+        let pixels = gradient();
+        let block = encode_block(&pixels, 0xF800, 0x07E0, UNIFORM_WEIGHTS);
+        let (c0, c1, indices) = unpack_block(block);
+
+        // Swapping the endpoints selects the 3 color mode without using index 3.
+        let three_color = c1 as u64 | (c0 as u64) << 16 | (indices as u64) << 32;
+        let four_color = four_color_block(&pixels, three_color, UNIFORM_WEIGHTS);
+        assert_eq!(block, four_color);
+        assert_eq!(block, four_color_block(&pixels, block, UNIFORM_WEIGHTS));
+    }
+
+    #[test]
+    fn four_color_block_equal_endpoints() {
+        let pixels = [[0; 3]; 16];
+        let (c0, c1, _) = unpack_block(four_color_block(&pixels, 0, UNIFORM_WEIGHTS));
+        assert!(c0 > c1);
+
+        let pixels = [[255; 3]; 16];
+        let block = four_color_block(&pixels, 0xFFFF_FFFF, UNIFORM_WEIGHTS);
+        let (c0, c1, _) = unpack_block(block);
+        assert!(c0 > c1);
+        assert_eq!(0, block_error(&pixels, block, true, UNIFORM_WEIGHTS));
+    }
+
+    #[test]
+    fn four_color_block_transparent() {
+        // Transparent pixels using index 3 of the 3 color mode become opaque.
+        let pixels = [[0; 3]; 16];
+        let three_color = 0x07E0 | 0xF800 << 16 | 0xFFFF_FFFF << 32;
+        let (c0, c1, _) = unpack_block(four_color_block(&pixels, three_color, UNIFORM_WEIGHTS));
+        assert!(c0 > c1);
+    }
+
+    #[test]
+    fn nearest_color_palette() {
+        let palette = [pack_565([255.0, 0.0, 0.0]), pack_565([0.0, 0.0, 255.0])];
+        assert_eq!(
+            palette[0],
+            nearest_color(&palette, pack_565([200.0, 0.0, 100.0]))
+        );
+        assert_eq!(
+            palette[1],
+            nearest_color(&palette, pack_565([0.0, 100.0, 200.0]))
+        );
+    }
+
+    #[test]
+    fn refine_color_block_solid() {
+        let pixels = [[255, 0, 0]; 16];
+        let refined = refine_color_block(&pixels, 0, false, UNIFORM_WEIGHTS);
+        assert_eq!(0, block_error(&pixels, refined, false, UNIFORM_WEIGHTS));
+    }
+
+    #[test]
+    fn refine_color_block_weights() {
+        // Weighting red and green should favor their accuracy over blue.
+        let pixels =
+            std::array::from_fn(|i| [i as i32 * 16, 255 - i as i32 * 16, (i as i32 * 37) % 256]);
+        let weights = [4, 4, 1];
+        let uniform = refine_color_block(&pixels, 0xFFFF, true, UNIFORM_WEIGHTS);
+        let weighted = refine_color_block(&pixels, 0xFFFF, true, weights);
+        assert!(
+            block_error(&pixels, weighted, true, [1, 1, 0])
+                <= block_error(&pixels, uniform, true, [1, 1, 0])
+        );
+    }
+}
@@ -0,0 +1,272 @@
+//! C bindings for encoding and decoding single 2D surfaces.
+//!
+//! Build a library for linking with `cargo rustc --features capi --crate-type cdylib`
+//! or `--crate-type staticlib`.
+//! Image formats use the `u32` value of [ImageFormat].
+//! Quality values are `0` for [Quality::Fast], `1` for [Quality::Normal],
+//! `2` for [Quality::Slow], and `3` for [Quality::Lossless].
+//!
+//! Output buffers are allocated by Rust and must be freed with [image_dds_free].
+use strum::IntoEnumIterator;
+
+use crate::{max_mipmap_count, ImageFormat, Mipmaps, Quality, Surface, SurfaceError, SurfaceRgba8};
+
+/// The result of a C API function.
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ImageDdsStatus {
+    Success = 0,
+    /// A required pointer was null.
+    NullPointer = 1,
+    /// The format code does not match any [ImageFormat].
+    InvalidFormat = 2,
+    /// The quality code does not match any [Quality].
+    InvalidQuality = 3,
+    /// The dimensions are zero, overflow, or are not supported by the format.
+    InvalidDimensions = 4,
+    /// The input data is too small for the dimensions and format.
+    NotEnoughData = 5,
+    /// The format is not supported for encoding with the given quality.
+    UnsupportedFormat = 6,
+    /// Any other error from encoding or decoding.
+    SurfaceError = 7,
+    /// The mipmap count is larger than the dimensions support.
+    InvalidMipmapCount = 8,
+}
+
+impl From<SurfaceError> for ImageDdsStatus {
+    fn from(value: SurfaceError) -> Self {
+        match value {
+            SurfaceError::ZeroSizedSurface { .. }
+            | SurfaceError::PixelCountWouldOverflow { .. }
+            | SurfaceError::NonIntegralDimensionsInBlocks { .. } => Self::InvalidDimensions,
+            SurfaceError::NotEnoughData { .. } | SurfaceError::FormatDataMismatch { .. } => {
+                Self::NotEnoughData
+            }
+            SurfaceError::UnsupportedEncodeFormat { .. } | SurfaceError::LossyFormat { .. } => {
+                Self::UnsupportedFormat
+            }
+            SurfaceError::UnexpectedMipmapCount { .. } => Self::InvalidMipmapCount,
+            _ => Self::SurfaceError,
+        }
+    }
+}
+
+/// Encode RGBA8 data for a single 2D surface to the format with value `format_code`.
+///
+/// A `mipmaps` value of `0` generates all mipmaps.
+/// Other values generate exactly `mipmaps` mipmaps including the base level.
+///
+/// # Safety
+/// `data_ptr` must point to at least `data_len` readable bytes.
+/// `out_ptr` and `out_len` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn image_dds_encode_rgba8(
+    width: u32,
+    height: u32,
+    format_code: u32,
+    quality: u32,
+    mipmaps: u32,
+    data_ptr: *const u8,
+    data_len: usize,
+    out_ptr: *mut *mut u8,
+    out_len: *mut usize,
+) -> ImageDdsStatus {
+    if data_ptr.is_null() || out_ptr.is_null() || out_len.is_null() {
+        return ImageDdsStatus::NullPointer;
+    }
+    let Some(format) = image_format(format_code) else {
+        return ImageDdsStatus::InvalidFormat;
+    };
+    let quality = match quality {
+        0 => Quality::Fast,
+        1 => Quality::Normal,
+        2 => Quality::Slow,
+        3 => Quality::Lossless,
+        _ => return ImageDdsStatus::InvalidQuality,
+    };
+    // Zero sized surfaces are reported as invalid dimensions by encoding.
+    if mipmaps > max_mipmap_count(width.max(height)).max(1) {
+        return ImageDdsStatus::InvalidMipmapCount;
+    }
+    let mipmaps = match mipmaps {
+        0 => Mipmaps::GeneratedAutomatic,
+        count => Mipmaps::GeneratedExact(count),
+    };
+
+    let surface = SurfaceRgba8 {
+        width,
+        height,
+        depth: 1,
+        layers: 1,
+        mipmaps: 1,
+        data: std::slice::from_raw_parts(data_ptr, data_len),
+    };
+    match surface.encode(format, quality, mipmaps) {
+        Ok(encoded) => write_output(encoded.data, out_ptr, out_len),
+        Err(e) => e.into(),
+    }
+}
+
+/// Decode the base mip level of a single 2D surface in the format with value `format_code` to RGBA8.
+///
+/// # Safety
+/// `data_ptr` must point to at least `data_len` readable bytes.
+/// `out_ptr` and `out_len` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn image_dds_decode_rgba8(
+    width: u32,
+    height: u32,
+    format_code: u32,
+    data_ptr: *const u8,
+    data_len: usize,
+    out_ptr: *mut *mut u8,
+    out_len: *mut usize,
+) -> ImageDdsStatus {
+    if data_ptr.is_null() || out_ptr.is_null() || out_len.is_null() {
+        return ImageDdsStatus::NullPointer;
+    }
+    let Some(image_format) = image_format(format_code) else {
+        return ImageDdsStatus::InvalidFormat;
+    };
+
+    let surface = Surface {
+        width,
+        height,
+        depth: 1,
+        layers: 1,
+        mipmaps: 1,
+        image_format,
+        layout: Default::default(),
+        mip_order: Default::default(),
+        data: std::slice::from_raw_parts(data_ptr, data_len),
+    };
+    match surface.decode_rgba8() {
+        Ok(decoded) => write_output(decoded.data, out_ptr, out_len),
+        Err(e) => e.into(),
+    }
+}
+
+/// Free a buffer returned by [image_dds_encode_rgba8] or [image_dds_decode_rgba8].
+///
+/// # Safety
+/// `ptr` and `len` must be the values written by a previous call and must not already be freed.
+/// Null pointers are ignored.
+#[no_mangle]
+pub unsafe extern "C" fn image_dds_free(ptr: *mut u8, len: usize) {
+    if !ptr.is_null() {
+        drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr, len)));
+    }
+}
+
+fn image_format(code: u32) -> Option<ImageFormat> {
+    ImageFormat::iter().find(|f| *f as u32 == code)
+}
+
+unsafe fn write_output(
+    data: Vec<u8>,
+    out_ptr: *mut *mut u8,
+    out_len: *mut usize,
+) -> ImageDdsStatus {
+    // Boxed slices have no extra capacity, so the length is enough to free the allocation.
+    let data = Box::into_raw(data.into_boxed_slice());
+    *out_len = data.len();
+    *out_ptr = data.cast();
+    ImageDdsStatus::Success
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_decode_rgba8() {
+        let rgba = [1u8, 2, 3, 4].repeat(4 * 4);
+        let mut encoded = std::ptr::null_mut();
+        let mut encoded_len = 0;
+        let status = unsafe {
+            image_dds_encode_rgba8(
+                4,
+                4,
+                ImageFormat::Rgba8Unorm as u32,
+                3,
+                1,
+                rgba.as_ptr(),
+                rgba.len(),
+                &mut encoded,
+                &mut encoded_len,
+            )
+        };
+        assert_eq!(ImageDdsStatus::Success, status);
+
+        let mut decoded = std::ptr::null_mut();
+        let mut decoded_len = 0;
+        let status = unsafe {
+            image_dds_decode_rgba8(
+                4,
+                4,
+                ImageFormat::Rgba8Unorm as u32,
+                encoded,
+                encoded_len,
+                &mut decoded,
+                &mut decoded_len,
+            )
+        };
+        assert_eq!(ImageDdsStatus::Success, status);
+        assert_eq!(&rgba[..], unsafe {
+            std::slice::from_raw_parts(decoded, decoded_len)
+        });
+
+        unsafe {
+            image_dds_free(encoded, encoded_len);
+            image_dds_free(decoded, decoded_len);
+        }
+    }
+
+    #[test]
+    fn encode_rgba8_errors() {
+        let rgba = [0u8; 4 * 4 * 4];
+        let encode = |format_code, quality, mipmaps, data_len| {
+            let mut out = std::ptr::null_mut();
+            let mut out_len = 0;
+            unsafe {
+                let status = image_dds_encode_rgba8(
+                    4,
+                    4,
+                    format_code,
+                    quality,
+                    mipmaps,
+                    rgba.as_ptr(),
+                    data_len,
+                    &mut out,
+                    &mut out_len,
+                );
+                if status == ImageDdsStatus::Success {
+                    image_dds_free(out, out_len);
+                }
+                status
+            }
+        };
+        let bc7 = ImageFormat::BC7RgbaUnorm as u32;
+        assert_eq!(
+            ImageDdsStatus::InvalidFormat,
+            encode(u32::MAX, 0, 1, rgba.len())
+        );
+        assert_eq!(
+            ImageDdsStatus::InvalidQuality,
+            encode(bc7, 4, 1, rgba.len())
+        );
+        assert_eq!(ImageDdsStatus::NotEnoughData, encode(bc7, 0, 1, 4));
+        assert_eq!(
+            ImageDdsStatus::UnsupportedFormat,
+            encode(bc7, 3, 1, rgba.len())
+        );
+        // 4x4 only has mipmaps for 4x4, 2x2, and 1x1.
+        let rgba8 = ImageFormat::Rgba8Unorm as u32;
+        assert_eq!(ImageDdsStatus::Success, encode(rgba8, 0, 3, rgba.len()));
+        assert_eq!(
+            ImageDdsStatus::InvalidMipmapCount,
+            encode(rgba8, 0, 4, rgba.len())
+        );
+    }
+}
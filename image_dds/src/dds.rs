@@ -1,11 +1,11 @@
 use std::ops::Range;
 
-use ddsfile::{Caps2, D3DFormat, Dds, DxgiFormat, FourCC};
+use ddsfile::{Caps2, D3DFormat, DataFormat, Dds, DxgiFormat, FourCC, MiscFlag};
 use thiserror::Error;
 
 use crate::{
-    CreateImageError, ImageFormat, Mipmaps, Quality, Surface, SurfaceError, SurfaceRgba32Float,
-    SurfaceRgba8,
+    CreateImageError, DataLayout, ImageFormat, MipOrder, Mipmaps, Quality, Surface, SurfaceError,
+    SurfaceRgba32Float, SurfaceRgba8,
 };
 
 /// Errors that can occur when converting to DDS.
@@ -16,8 +16,21 @@ pub enum CreateDdsError {
 
     #[error("error compressing surface: {0}")]
     CompressSurface(#[from] SurfaceError),
+
+    #[error("image format {0:?} is not supported by DDS")]
+    UnsupportedImageFormat(ImageFormat),
+
+    #[error("reserved data length {length} exceeds the maximum of {max} bytes")]
+    ReservedDataTooLong { length: usize, max: usize },
 }
 
+/// The size in bytes of the reserved fields in the DDS header.
+pub const DDS_RESERVED_SIZE: usize = 48;
+
+// Byte ranges of dwReserved1 and dwReserved2 in the serialized header.
+const RESERVED1_RANGE: Range<usize> = 28..72;
+const RESERVED2_RANGE: Range<usize> = 120..124;
+
 #[cfg(feature = "encode")]
 #[cfg(feature = "image")]
 /// Encode `image` to a 2D DDS file with the given `format`.
@@ -72,7 +85,12 @@ impl<T: AsRef<[u8]>> Surface<T> {
     /// Create a DDS file with the same image data and format.
     ///
     /// Creates a DXGI DDS for most formats and D3D DDS for some legacy formats.
+    /// Surfaces with exactly 6 layers are saved as cube maps.
+    /// Other layer counts are saved as texture arrays.
     pub fn to_dds(&self) -> Result<crate::ddsfile::Dds, CreateDdsError> {
+        let is_cubemap = self.layers == 6;
+        let cube_caps2 = is_cubemap.then_some(Caps2::CUBEMAP | Caps2::CUBEMAP_ALLFACES);
+
         let mut dds = dxgi_from_image_format(self.image_format)
             .map(|format| {
                 Dds::new_dxgi(ddsfile::NewDxgiParams {
@@ -85,15 +103,20 @@ impl<T: AsRef<[u8]>> Surface<T> {
                     },
                     format,
                     mipmap_levels: (self.mipmaps > 1).then_some(self.mipmaps),
-                    array_layers: (self.layers > 1 && self.layers != 6).then_some(self.layers),
-                    caps2: (self.layers == 6).then_some(Caps2::CUBEMAP | Caps2::CUBEMAP_ALLFACES),
-                    is_cubemap: self.layers == 6,
+                    array_layers: (self.layers > 1 && !is_cubemap).then_some(self.layers),
+                    caps2: cube_caps2,
+                    is_cubemap,
                     resource_dimension: if self.depth > 1 {
                         ddsfile::D3D10ResourceDimension::Texture3D
                     } else {
                         ddsfile::D3D10ResourceDimension::Texture2D
                     },
-                    alpha_mode: ddsfile::AlphaMode::Straight,
+                    alpha_mode: match self.image_format {
+                        ImageFormat::BC1RgbUnorm | ImageFormat::BC1RgbUnormSrgb => {
+                            ddsfile::AlphaMode::Opaque
+                        }
+                        _ => ddsfile::AlphaMode::Straight,
+                    },
                 })
             })
             .or_else(|| {
@@ -109,15 +132,42 @@ impl<T: AsRef<[u8]>> Surface<T> {
                         },
                         format,
                         mipmap_levels: (self.mipmaps > 1).then_some(self.mipmaps),
-                        caps2: (self.layers == 6)
-                            .then_some(Caps2::CUBEMAP | Caps2::CUBEMAP_ALLFACES),
+                        caps2: cube_caps2,
                     })
                 })
             })
-            .unwrap()?;
+            .ok_or(CreateDdsError::UnsupportedImageFormat(self.image_format))??;
 
-        dds.data = self.data.as_ref().to_vec();
+        dds.data = match (self.layout, self.mip_order) {
+            (DataLayout::Tight, MipOrder::LargestFirst) => self.data.as_ref().to_vec(),
+            // DDS files don't store any padding between rows and start with the base mip level.
+            _ => self.repack(DataLayout::Tight, MipOrder::LargestFirst)?.data,
+        };
+
+        Ok(dds)
+    }
+
+    /// Create the bytes of a DDS file with the same image data and format.
+    ///
+    /// This is equivalent to writing the result of [Surface::to_dds] to a [Vec].
+    pub fn to_dds_bytes(&self) -> Result<Vec<u8>, CreateDdsError> {
+        let dds = self.to_dds()?;
+        let mut bytes = Vec::new();
+        dds.write(&mut bytes)?;
+        Ok(bytes)
+    }
 
+    /// Create a DDS file like [Surface::to_dds] with the reserved header fields set to `reserved`.
+    ///
+    /// See [set_dds_reserved_bytes] for how the bytes are stored.
+    pub fn to_dds_with_reserved(
+        &self,
+        reserved: Option<&[u8]>,
+    ) -> Result<crate::ddsfile::Dds, CreateDdsError> {
+        let mut dds = self.to_dds()?;
+        if let Some(reserved) = reserved {
+            set_dds_reserved_bytes(&mut dds, reserved)?;
+        }
         Ok(dds)
     }
 }
@@ -129,8 +179,9 @@ impl<'a> Surface<&'a [u8]> {
         let height = dds.get_height();
         let depth = dds.get_depth();
         let layers = array_layer_count(dds);
-        let mipmaps = dds.get_num_mipmap_levels();
-        let image_format = dds_image_format(dds).map_err(SurfaceError::UnsupportedDdsFormat)?;
+        // Some DDS files have a mipmap count of 0 instead of 1 for only the base level.
+        let mipmaps = dds.get_num_mipmap_levels().max(1);
+        let image_format = dds_image_format(dds).map_err(unsupported_format_error)?;
 
         Ok(Surface {
             width,
@@ -139,9 +190,62 @@ impl<'a> Surface<&'a [u8]> {
             layers,
             mipmaps,
             image_format,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
             data: &dds.data,
         })
     }
+
+    /// Create a view over the data in `dds` like [Surface::from_dds]
+    /// and also return the reserved header fields.
+    ///
+    /// See [dds_reserved_bytes] for details on the reserved bytes.
+    pub fn from_dds_with_reserved(
+        dds: &'a crate::ddsfile::Dds,
+    ) -> Result<(Self, Option<Vec<u8>>), SurfaceError> {
+        Ok((Self::from_dds(dds)?, dds_reserved_bytes(dds)))
+    }
+}
+
+/// Returns the `dwReserved1` and `dwReserved2` fields of the header of `dds`
+/// as [DDS_RESERVED_SIZE] bytes or `None` if the fields are all zero.
+///
+/// Some tools store custom metadata in these otherwise unused fields.
+pub fn dds_reserved_bytes(dds: &Dds) -> Option<Vec<u8>> {
+    let header = header_bytes(&dds.header)?;
+    let reserved = [&header[RESERVED1_RANGE], &header[RESERVED2_RANGE]].concat();
+    reserved.iter().any(|b| *b != 0).then_some(reserved)
+}
+
+/// Set the `dwReserved1` and `dwReserved2` fields of the header of `dds` to `reserved`.
+///
+/// The bytes fill `dwReserved1` followed by `dwReserved2`.
+/// Data shorter than [DDS_RESERVED_SIZE] bytes is padded with zeros.
+pub fn set_dds_reserved_bytes(dds: &mut Dds, reserved: &[u8]) -> Result<(), CreateDdsError> {
+    if reserved.len() > DDS_RESERVED_SIZE {
+        return Err(CreateDdsError::ReservedDataTooLong {
+            length: reserved.len(),
+            max: DDS_RESERVED_SIZE,
+        });
+    }
+
+    let mut padded = [0u8; DDS_RESERVED_SIZE];
+    padded[..reserved.len()].copy_from_slice(reserved);
+
+    // The reserved fields aren't public, so edit the serialized header instead.
+    let mut header = Vec::new();
+    dds.header.write(&mut header)?;
+    let (reserved1, reserved2) = padded.split_at(RESERVED1_RANGE.len());
+    header[RESERVED1_RANGE].copy_from_slice(reserved1);
+    header[RESERVED2_RANGE].copy_from_slice(reserved2);
+    dds.header = ddsfile::Header::read(header.as_slice())?;
+    Ok(())
+}
+
+fn header_bytes(header: &ddsfile::Header) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    header.write(&mut bytes).ok()?;
+    Some(bytes)
 }
 
 #[cfg(feature = "encode")]
@@ -211,17 +315,121 @@ pub struct DdsFormatInfo {
 
 /// Returns the format of `dds` or `None` if the format is unrecognized.
 pub fn dds_image_format(dds: &Dds) -> Result<ImageFormat, DdsFormatInfo> {
+    image_format_from_header(&dds.header, dds.header10.as_ref())
+}
+
+impl ImageFormat {
+    /// Returns the format from the DDS `header` and optional DX10 `header10`
+    /// and whether the surface is a cube map or `None` if the format is unrecognized.
+    ///
+    /// Legacy DDS files specify the format using RGB bitmasks or a FourCC in the pixel format of `header`.
+    /// DX10 DDS files specify a DXGI format in `header10`.
+    /// This uses the same detection as [dds_image_format] for custom DDS containers.
+    pub fn from_dds_header(
+        header: &ddsfile::Header,
+        header10: Option<&ddsfile::Header10>,
+    ) -> Option<(Self, bool)> {
+        let format = image_format_from_header(header, header10).ok()?;
+        let is_cube = match header10 {
+            Some(header10) => header10.misc_flag.contains(MiscFlag::TEXTURECUBE),
+            None => header.caps2.contains(Caps2::CUBEMAP),
+        };
+        Some((format, is_cube))
+    }
+}
+
+fn image_format_from_header(
+    header: &ddsfile::Header,
+    header10: Option<&ddsfile::Header10>,
+) -> Result<ImageFormat, DdsFormatInfo> {
     // The format can be DXGI, D3D, or specified in the FOURCC.
-    let dxgi = dds.get_dxgi_format();
-    let d3d = dds.get_d3d_format();
-    let fourcc = dds.header.spf.fourcc.clone();
+    let dxgi = match header10 {
+        Some(header10) => Some(header10.dxgi_format),
+        None => DxgiFormat::try_from_pixel_format(&header.spf),
+    };
+    let d3d = D3DFormat::try_from_pixel_format(&header.spf);
+    let fourcc = header.spf.fourcc.clone();
 
     d3d.and_then(image_format_from_d3d)
         .or_else(|| dxgi.and_then(image_format_from_dxgi))
         .or_else(|| fourcc.clone().and_then(image_format_from_fourcc))
+        .map(|format| opaque_image_format(format, header, header10))
         .ok_or(DdsFormatInfo { dxgi, d3d, fourcc })
 }
 
+fn unsupported_format_error(info: DdsFormatInfo) -> SurfaceError {
+    // Report numeric codes in the same order as the format lookup
+    // since these codes are easy to look up.
+    match info {
+        DdsFormatInfo {
+            d3d: Some(format), ..
+        } => SurfaceError::UnsupportedD3dFormat {
+            code: d3d_format_code(format),
+        },
+        DdsFormatInfo {
+            dxgi: Some(format), ..
+        } => SurfaceError::UnsupportedDxgiFormat {
+            code: format as u32,
+        },
+        DdsFormatInfo {
+            fourcc: Some(fourcc),
+            ..
+        } => SurfaceError::UnsupportedFourCC { code: fourcc.0 },
+        info => SurfaceError::UnsupportedDdsFormat(info),
+    }
+}
+
+fn d3d_format_code(format: D3DFormat) -> u32 {
+    // Values from the D3DFORMAT enum in d3d9types.h.
+    // Compressed and floating point formats use the FourCC as their code.
+    match format {
+        D3DFormat::R8G8B8 => 20,
+        D3DFormat::A8R8G8B8 => 21,
+        D3DFormat::X8R8G8B8 => 22,
+        D3DFormat::R5G6B5 => 23,
+        D3DFormat::X1R5G5B5 => 24,
+        D3DFormat::A1R5G5B5 => 25,
+        D3DFormat::A4R4G4B4 => 26,
+        D3DFormat::A8 => 28,
+        D3DFormat::A8R3G3B2 => 29,
+        D3DFormat::X4R4G4B4 => 30,
+        D3DFormat::A2B10G10R10 => 31,
+        D3DFormat::A8B8G8R8 => 32,
+        D3DFormat::X8B8G8R8 => 33,
+        D3DFormat::G16R16 => 34,
+        D3DFormat::A2R10G10B10 => 35,
+        D3DFormat::L8 => 50,
+        D3DFormat::A8L8 => 51,
+        D3DFormat::A4L4 => 52,
+        D3DFormat::L16 => 81,
+        format => format
+            .get_fourcc()
+            .map(|fourcc| fourcc.0)
+            .unwrap_or_default(),
+    }
+}
+
+fn opaque_image_format(
+    format: ImageFormat,
+    header: &ddsfile::Header,
+    header10: Option<&ddsfile::Header10>,
+) -> ImageFormat {
+    // BC1 can only ignore its 1-bit alpha if the header marks the data as opaque.
+    // Legacy files without a DX10 header only have alpha if they set the alpha pixels flag.
+    let is_opaque = match header10 {
+        Some(header10) => header10.alpha_mode == ddsfile::AlphaMode::Opaque,
+        None => !header
+            .spf
+            .flags
+            .contains(ddsfile::PixelFormatFlags::ALPHA_PIXELS),
+    };
+    match format {
+        ImageFormat::BC1RgbaUnorm if is_opaque => ImageFormat::BC1RgbUnorm,
+        ImageFormat::BC1RgbaUnormSrgb if is_opaque => ImageFormat::BC1RgbUnormSrgb,
+        _ => format,
+    }
+}
+
 fn image_format_from_dxgi(format: DxgiFormat) -> Option<ImageFormat> {
     match format {
         DxgiFormat::R8_UNorm => Some(ImageFormat::R8Unorm),
@@ -262,6 +470,8 @@ fn image_format_from_dxgi(format: DxgiFormat) -> Option<ImageFormat> {
         DxgiFormat::R8G8B8A8_SNorm => Some(ImageFormat::Rgba8Snorm),
         DxgiFormat::R32G32B32_Float => Some(ImageFormat::Rgb32Float),
         DxgiFormat::B5G5R5A1_UNorm => Some(ImageFormat::Bgr5A1Unorm),
+        DxgiFormat::R9G9B9E5_SharedExp => Some(ImageFormat::Rgb9e5Ufloat),
+        DxgiFormat::R11G11B10_Float => Some(ImageFormat::Rg11b10Float),
         _ => None,
     }
 }
@@ -349,6 +559,13 @@ fn d3d_from_image_format(value: ImageFormat) -> Option<D3DFormat> {
         ImageFormat::Rgba8Snorm => None,
         ImageFormat::Rgb32Float => None,
         ImageFormat::Bgr5A1Unorm => Some(D3DFormat::A1R5G5B5),
+        ImageFormat::BC1RgbUnorm => Some(D3DFormat::DXT1),
+        ImageFormat::BC1RgbUnormSrgb => Some(D3DFormat::DXT1),
+        ImageFormat::Rgb9e5Ufloat => None,
+        ImageFormat::Rg11b10Float => None,
+        ImageFormat::EacR11Unorm => None,
+        ImageFormat::EacRg11Unorm => None,
+        ImageFormat::Rgb8Unorm => None,
     }
 }
 
@@ -393,6 +610,13 @@ fn dxgi_from_image_format(value: ImageFormat) -> Option<DxgiFormat> {
         ImageFormat::Rgba8Snorm => Some(DxgiFormat::R8G8B8A8_SNorm),
         ImageFormat::Rgb32Float => Some(DxgiFormat::R32G32B32_Float),
         ImageFormat::Bgr5A1Unorm => Some(DxgiFormat::B5G5R5A1_UNorm),
+        ImageFormat::BC1RgbUnorm => Some(DxgiFormat::BC1_UNorm),
+        ImageFormat::BC1RgbUnormSrgb => Some(DxgiFormat::BC1_UNorm_sRGB),
+        ImageFormat::Rgb9e5Ufloat => Some(DxgiFormat::R9G9B9E5_SharedExp),
+        ImageFormat::Rg11b10Float => Some(DxgiFormat::R11G11B10_Float),
+        ImageFormat::EacR11Unorm => None,
+        ImageFormat::EacRg11Unorm => None,
+        ImageFormat::Rgb8Unorm => None,
     }
 }
 
@@ -413,12 +637,23 @@ mod tests {
                 layers: 1,
                 mipmaps: 1,
                 image_format,
+                layout: DataLayout::Tight,
+                mip_order: MipOrder::LargestFirst,
                 data: data.as_slice(),
             };
-            assert_eq!(
-                surface,
-                Surface::from_dds(&surface.to_dds().unwrap()).unwrap()
-            );
+            match image_format {
+                // These formats have no DXGI or D3D format.
+                ImageFormat::EacR11Unorm | ImageFormat::EacRg11Unorm | ImageFormat::Rgb8Unorm => {
+                    assert!(matches!(
+                        surface.to_dds(),
+                        Err(CreateDdsError::UnsupportedImageFormat(f)) if f == image_format
+                    ))
+                }
+                _ => assert_eq!(
+                    surface,
+                    Surface::from_dds(&surface.to_dds().unwrap()).unwrap()
+                ),
+            }
         }
     }
 
@@ -433,12 +668,331 @@ mod tests {
                 layers: 6,
                 mipmaps: 1,
                 image_format,
+                layout: DataLayout::Tight,
+                mip_order: MipOrder::LargestFirst,
                 data: data.as_slice(),
             };
-            assert_eq!(
-                surface,
-                Surface::from_dds(&surface.to_dds().unwrap()).unwrap()
-            );
+            match image_format {
+                // These formats have no DXGI or D3D format.
+                ImageFormat::EacR11Unorm | ImageFormat::EacRg11Unorm | ImageFormat::Rgb8Unorm => {
+                    assert!(matches!(
+                        surface.to_dds(),
+                        Err(CreateDdsError::UnsupportedImageFormat(f)) if f == image_format
+                    ))
+                }
+                _ => assert_eq!(
+                    surface,
+                    Surface::from_dds(&surface.to_dds().unwrap()).unwrap()
+                ),
+            }
         }
     }
+
+    #[test]
+    fn dds_from_surface_cube_array() {
+        let mut dds = Dds::new_dxgi(ddsfile::NewDxgiParams {
+            height: 4,
+            width: 4,
+            depth: None,
+            format: DxgiFormat::R8G8B8A8_UNorm,
+            mipmap_levels: None,
+            array_layers: Some(12),
+            caps2: Some(Caps2::CUBEMAP | Caps2::CUBEMAP_ALLFACES),
+            is_cubemap: true,
+            resource_dimension: ddsfile::D3D10ResourceDimension::Texture2D,
+            alpha_mode: ddsfile::AlphaMode::Straight,
+        })
+        .unwrap();
+        dds.data = (0..12).flat_map(|i| [i as u8; 4 * 4 * 4]).collect();
+
+        let surface = Surface::from_dds(&dds).unwrap();
+        assert_eq!(12, surface.layers);
+        assert_eq!(Some(&[8u8; 4 * 4 * 4][..]), surface.get_cube_face(1, 2, 0));
+        assert_eq!(None, surface.get_cube_face(1, 6, 0));
+        assert_eq!(None, surface.get_cube_face(2, 0, 0));
+    }
+
+    #[test]
+    fn dds_to_from_surface_array_12_layers() {
+        // Layer counts other than 6 are not assumed to be cube maps.
+        let data: Vec<_> = (0..12).flat_map(|i| [i as u8; 4 * 4 * 4]).collect();
+        let surface = Surface {
+            width: 4,
+            height: 4,
+            depth: 1,
+            layers: 12,
+            mipmaps: 1,
+            image_format: ImageFormat::Rgba8Unorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: data.as_slice(),
+        };
+
+        let dds = surface.to_dds().unwrap();
+        let header10 = dds.header10.as_ref().unwrap();
+        assert!(!header10.misc_flag.contains(ddsfile::MiscFlag::TEXTURECUBE));
+        assert_eq!(12, header10.array_size);
+        assert!(!dds.header.caps2.contains(Caps2::CUBEMAP));
+        assert_eq!(surface, Surface::from_dds(&dds).unwrap());
+    }
+
+    #[test]
+    fn dds_from_surface_smallest_first() {
+        let surface = Surface {
+            width: 2,
+            height: 2,
+            depth: 1,
+            layers: 1,
+            mipmaps: 2,
+            image_format: ImageFormat::R8Unorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::SmallestFirst,
+            data: vec![5u8, 1, 2, 3, 4],
+        };
+        let dds = surface.to_dds().unwrap();
+        assert_eq!(vec![1, 2, 3, 4, 5], dds.data);
+    }
+
+    #[test]
+    fn dds_from_surface_sub_block_size() {
+        // The header uses the virtual size while the data is still a whole block.
+        let surface = Surface {
+            width: 2,
+            height: 2,
+            depth: 1,
+            layers: 1,
+            mipmaps: 2,
+            image_format: ImageFormat::BC7RgbaUnorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: vec![0u8; 16 * 2],
+        };
+        let dds = surface.to_dds().unwrap();
+        assert_eq!(2, dds.get_width());
+        assert_eq!(2, dds.get_height());
+        assert_eq!(Some(2), dds.header.mip_map_count);
+        assert_eq!(16 * 2, dds.data.len());
+
+        let mut bytes = Vec::new();
+        dds.write(&mut bytes).unwrap();
+        let dds = Dds::read(bytes.as_slice()).unwrap();
+        assert_eq!(surface.as_ref(), Surface::from_dds(&dds).unwrap());
+    }
+
+    #[test]
+    fn dds_bytes_from_surface() {
+        let surface = Surface {
+            width: 4,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format: ImageFormat::BC7RgbaUnorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: vec![1u8; 16],
+        };
+        let bytes = surface.to_dds_bytes().unwrap();
+
+        // Magic, header, DX10 header, and data.
+        assert_eq!(4 + 124 + 20 + 16, bytes.len());
+        assert_eq!(b"DDS ", &bytes[..4]);
+        let dds = Dds::read(bytes.as_slice()).unwrap();
+        assert_eq!(surface.as_ref(), Surface::from_dds(&dds).unwrap());
+    }
+
+    #[test]
+    fn dds_reserved_round_trip() {
+        let surface = Surface {
+            width: 4,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format: ImageFormat::BC7RgbaUnorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: vec![0u8; 16],
+        };
+        let dds = surface.to_dds().unwrap();
+        assert_eq!(None, dds_reserved_bytes(&dds));
+
+        let reserved: Vec<u8> = (1..=DDS_RESERVED_SIZE as u8).collect();
+        let dds = surface.to_dds_with_reserved(Some(&reserved)).unwrap();
+
+        let mut bytes = Vec::new();
+        dds.write(&mut bytes).unwrap();
+        assert_eq!(&reserved[..44], &bytes[4 + 28..4 + 72]);
+        assert_eq!(&reserved[44..], &bytes[4 + 120..4 + 124]);
+
+        let dds = Dds::read(bytes.as_slice()).unwrap();
+        let (new_surface, new_reserved) = Surface::from_dds_with_reserved(&dds).unwrap();
+        assert_eq!(surface.as_ref(), new_surface);
+        assert_eq!(Some(reserved), new_reserved);
+
+        // Writing the DDS again should produce the same header.
+        let mut new_bytes = Vec::new();
+        new_surface
+            .to_dds_with_reserved(new_reserved.as_deref())
+            .unwrap()
+            .write(&mut new_bytes)
+            .unwrap();
+        assert_eq!(bytes, new_bytes);
+    }
+
+    #[test]
+    fn dds_reserved_too_long() {
+        let mut dds = Dds::new_dxgi(ddsfile::NewDxgiParams {
+            height: 4,
+            width: 4,
+            depth: None,
+            format: DxgiFormat::BC7_UNorm,
+            mipmap_levels: None,
+            array_layers: None,
+            caps2: None,
+            is_cubemap: false,
+            resource_dimension: ddsfile::D3D10ResourceDimension::Texture2D,
+            alpha_mode: ddsfile::AlphaMode::Straight,
+        })
+        .unwrap();
+        assert!(matches!(
+            set_dds_reserved_bytes(&mut dds, &[1; DDS_RESERVED_SIZE + 1]),
+            Err(CreateDdsError::ReservedDataTooLong {
+                length: 49,
+                max: 48
+            })
+        ));
+
+        set_dds_reserved_bytes(&mut dds, &[1, 2, 3]).unwrap();
+        let mut expected = vec![0u8; DDS_RESERVED_SIZE];
+        expected[..3].copy_from_slice(&[1, 2, 3]);
+        assert_eq!(Some(expected), dds_reserved_bytes(&dds));
+    }
+
+    #[test]
+    fn image_format_from_dds_header() {
+        // Legacy uncompressed formats use RGB bitmasks.
+        let dds = Dds::new_d3d(ddsfile::NewD3dParams {
+            height: 4,
+            width: 4,
+            depth: None,
+            format: D3DFormat::A8R8G8B8,
+            mipmap_levels: None,
+            caps2: Some(Caps2::CUBEMAP | Caps2::CUBEMAP_ALLFACES),
+        })
+        .unwrap();
+        assert_eq!(
+            Some((ImageFormat::Bgra8Unorm, true)),
+            ImageFormat::from_dds_header(&dds.header, None)
+        );
+
+        // Legacy compressed formats use a FourCC.
+        let mut header = ddsfile::Header::default();
+        header.spf.flags = ddsfile::PixelFormatFlags::FOURCC;
+        header.spf.fourcc = Some(FourCC(ATI2));
+        assert_eq!(
+            Some((ImageFormat::BC5RgUnorm, false)),
+            ImageFormat::from_dds_header(&header, None)
+        );
+
+        let dds = Dds::new_dxgi(ddsfile::NewDxgiParams {
+            height: 4,
+            width: 4,
+            depth: None,
+            format: DxgiFormat::BC1_UNorm_sRGB,
+            mipmap_levels: None,
+            array_layers: None,
+            caps2: None,
+            is_cubemap: true,
+            resource_dimension: ddsfile::D3D10ResourceDimension::Texture2D,
+            alpha_mode: ddsfile::AlphaMode::Opaque,
+        })
+        .unwrap();
+        assert_eq!(
+            Some((ImageFormat::BC1RgbUnormSrgb, true)),
+            ImageFormat::from_dds_header(&dds.header, dds.header10.as_ref())
+        );
+
+        assert_eq!(
+            None,
+            ImageFormat::from_dds_header(&ddsfile::Header::default(), None)
+        );
+    }
+
+    #[test]
+    fn image_format_from_dds_header_legacy_bc1() {
+        // Legacy DXT1 files are opaque unless they set the alpha pixels flag.
+        let mut header = ddsfile::Header::default();
+        header.spf.flags = ddsfile::PixelFormatFlags::FOURCC;
+        header.spf.fourcc = Some(FourCC(FourCC::DXT1));
+        assert_eq!(
+            Some((ImageFormat::BC1RgbUnorm, false)),
+            ImageFormat::from_dds_header(&header, None)
+        );
+
+        header.spf.flags |= ddsfile::PixelFormatFlags::ALPHA_PIXELS;
+        assert_eq!(
+            Some((ImageFormat::BC1RgbaUnorm, false)),
+            ImageFormat::from_dds_header(&header, None)
+        );
+    }
+
+    #[test]
+    fn dds_from_surface_unsupported_dxgi() {
+        let dds = Dds::new_dxgi(ddsfile::NewDxgiParams {
+            height: 4,
+            width: 4,
+            depth: None,
+            format: DxgiFormat::R10G10B10A2_UNorm,
+            mipmap_levels: None,
+            array_layers: None,
+            caps2: None,
+            is_cubemap: false,
+            resource_dimension: ddsfile::D3D10ResourceDimension::Texture2D,
+            alpha_mode: ddsfile::AlphaMode::Straight,
+        })
+        .unwrap();
+
+        assert_eq!(
+            Err(SurfaceError::UnsupportedDxgiFormat { code: 24 }),
+            Surface::from_dds(&dds)
+        );
+    }
+
+    #[test]
+    fn dds_from_surface_unsupported_d3d() {
+        let dds = Dds::new_d3d(ddsfile::NewD3dParams {
+            height: 4,
+            width: 4,
+            depth: None,
+            format: D3DFormat::X8R8G8B8,
+            mipmap_levels: None,
+            caps2: None,
+        })
+        .unwrap();
+
+        assert_eq!(
+            Err(SurfaceError::UnsupportedD3dFormat { code: 22 }),
+            Surface::from_dds(&dds)
+        );
+    }
+
+    #[test]
+    fn dds_from_surface_unsupported_fourcc() {
+        let mut dds = Dds::new_d3d(ddsfile::NewD3dParams {
+            height: 4,
+            width: 4,
+            depth: None,
+            format: D3DFormat::DXT1,
+            mipmap_levels: None,
+            caps2: None,
+        })
+        .unwrap();
+        dds.header.spf.fourcc = Some(FourCC(u32::from_le_bytes(*b"ABCD")));
+
+        assert_eq!(
+            Err(SurfaceError::UnsupportedFourCC { code: 0x44434241 }),
+            Surface::from_dds(&dds)
+        );
+    }
 }
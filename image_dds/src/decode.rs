@@ -1,17 +1,19 @@
 use std::ops::Range;
 
 use crate::{
-    bcn::{self, decode_bcn},
+    bcn::{self, decode_bcn, decode_bcn_into},
+    calculate_offset, downsample_rgba,
     error::SurfaceError,
-    mip_dimension,
+    max_mipmap_count, mip_dimension,
     rgba::{
-        decode_rgba, Bgr5A1, Bgr8, Bgra4, Bgra8, R16Snorm, R8Snorm, Rf16, Rf32, Rg16, Rg16Snorm,
-        Rg8, Rg8Snorm, Rgba16, Rgba16Snorm, Rgba8, Rgba8Snorm, Rgbaf16, Rgbaf32, Rgbf32, Rgf16,
-        Rgf32, R16, R8,
+        decode_rgba, decode_rgba_into, Bgr5A1, Bgr8, Bgra4, Bgra8, R16Snorm, R8Snorm, Rf16, Rf32,
+        Rg11b10, Rg16, Rg16Snorm, Rg8, Rg8Snorm, Rgb8, Rgb9e5, Rgba16, Rgba16Snorm, Rgba8,
+        Rgba8Snorm, Rgbaf16, Rgbaf32, Rgbf32, Rgf16, Rgf32, R16, R8,
     },
-    ImageFormat, Surface, SurfaceRgba32Float, SurfaceRgba8,
+    AddressMode, BlockLocation, DataLayout, DecodeOptions, ImageFormat, MipOrder, Surface,
+    SurfaceRgba32Float, SurfaceRgba8,
 };
-use bcn::{Bc1, Bc2, Bc3, Bc4, Bc4S, Bc5, Bc5S, Bc6, Bc7};
+use bcn::{Bc1, Bc2, Bc2Alpha, Bc3, Bc3Alpha, Bc4, Bc4S, Bc5, Bc5S, Bc6, Bc7, EacR11, EacRg11};
 
 impl<T: AsRef<[u8]>> Surface<T> {
     /// Decode all layers and mipmaps from `surface` to RGBA8.
@@ -19,15 +21,283 @@ impl<T: AsRef<[u8]>> Surface<T> {
         self.decode_layers_mipmaps_rgba8(0..self.layers, 0..self.mipmaps)
     }
 
+    /// Decode all layers and mipmaps from `surface` to RGBA8
+    /// and find any blocks with invalid mode bits.
+    ///
+    /// Invalid blocks usually indicate corrupted data.
+    /// See [Surface::invalid_blocks] for details.
+    pub fn decode_rgba8_strict(&self) -> Result<StrictDecodedRgba8, SurfaceError> {
+        Ok(StrictDecodedRgba8 {
+            surface: self.decode_rgba8()?,
+            invalid_blocks: self.invalid_blocks()?,
+        })
+    }
+
+    /// Find the blocks in all layers and mipmaps with invalid mode bits.
+    ///
+    /// Only BC6H and BC7 have invalid modes.
+    /// The result is always empty for other formats.
+    pub fn invalid_blocks(&self) -> Result<Vec<BlockLocation>, SurfaceError> {
+        check_format_data_length(self)?;
+        self.validate_base()?;
+
+        let is_valid: fn(&[u8]) -> bool = match self.image_format {
+            ImageFormat::BC6hRgbUfloat | ImageFormat::BC6hRgbSfloat => bcn::bc6h_mode_is_valid,
+            ImageFormat::BC7RgbaUnorm | ImageFormat::BC7RgbaUnormSrgb => bcn::bc7_mode_is_valid,
+            _ => return Ok(Vec::new()),
+        };
+
+        let invalid_blocks = self
+            .iter_blocks()
+            .filter(|block| !is_valid(block.data))
+            .map(|block| block.location)
+            .collect();
+
+        Ok(invalid_blocks)
+    }
+
+    /// Returns `true` if every pixel in all layers and mipmaps has an alpha of `255`.
+    ///
+    /// Formats without an alpha channel like [ImageFormat::BC1RgbUnorm] or [ImageFormat::R8Unorm]
+    /// are always opaque and do not need to be decoded.
+    /// RGBA8 and BGRA8 data is checked directly, and other formats are decoded to RGBA8 first.
+    pub fn is_opaque(&self) -> Result<bool, SurfaceError> {
+        check_format_data_length(self)?;
+        self.validate_base()?;
+
+        if !has_alpha_channel(self.image_format) {
+            return Ok(true);
+        }
+
+        match self.image_format {
+            ImageFormat::Rgba8Unorm
+            | ImageFormat::Rgba8UnormSrgb
+            | ImageFormat::Bgra8Unorm
+            | ImageFormat::Bgra8UnormSrgb => {
+                // Uncompressed formats use a block for each pixel.
+                Ok(self.iter_blocks().all(|block| block.data[3] == 255))
+            }
+            _ => Ok(self
+                .decode_rgba8()?
+                .data
+                .chunks_exact(4)
+                .all(|pixel| pixel[3] == 255)),
+        }
+    }
+
+    /// Decode only the alpha channel of all layers and mipmaps to [ImageFormat::R8Unorm].
+    ///
+    /// BC2 and BC3 data only decodes the alpha block and skips the color block.
+    /// Other formats are decoded to RGBA8 first.
+    /// Returns [SurfaceError::MissingAlphaChannel] for formats without alpha like [ImageFormat::BC1RgbUnorm].
+    pub fn decode_alpha_r8(&self) -> Result<Surface<Vec<u8>>, SurfaceError> {
+        if !has_alpha_channel(self.image_format) {
+            return Err(SurfaceError::MissingAlphaChannel {
+                format: self.image_format,
+            });
+        }
+
+        check_format_data_length(self)?;
+        self.validate_base()?;
+
+        let data: Vec<Alpha8> = decode_surface(self, 0..self.layers, 0..self.mipmaps, 1)?;
+
+        Ok(Surface {
+            width: self.width,
+            height: self.height,
+            depth: self.depth,
+            layers: self.layers,
+            mipmaps: self.mipmaps,
+            image_format: ImageFormat::R8Unorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: data.into_iter().map(|a| a.0).collect(),
+        })
+    }
+
+    /// Decode the base mip level of all layers and depth slices
+    /// and compare the result to the `original` RGBA8 data.
+    ///
+    /// The check passes if no channel differs by more than `tolerance`.
+    /// Any mipmaps after the base level are ignored,
+    /// so `original` does not need to contain mipmaps.
+    pub fn verify_roundtrip<U: AsRef<[u8]>>(
+        &self,
+        original: &SurfaceRgba8<U>,
+        tolerance: u8,
+    ) -> Result<RoundtripReport, SurfaceError> {
+        original.validate()?;
+        if (self.width, self.height, self.depth, self.layers)
+            != (
+                original.width,
+                original.height,
+                original.depth,
+                original.layers,
+            )
+        {
+            return Err(SurfaceError::MismatchedDimensions {
+                width: self.width,
+                height: self.height,
+                depth: self.depth,
+                layers: self.layers,
+                expected_width: original.width,
+                expected_height: original.height,
+                expected_depth: original.depth,
+                expected_layers: original.layers,
+            });
+        }
+
+        let decoded = self.decode_layers_mipmaps_rgba8(0..self.layers, 0..1)?;
+
+        let (block_width, block_height, _) = self.image_format.block_dimensions();
+        let blocks_wide = self.width.div_ceil(block_width);
+        let blocks_high = self.height.div_ceil(block_height);
+
+        let mut max_error = [0u8; 4];
+        let mut sum = [0u64; 4];
+        let mut worst_block = BlockLocation {
+            layer: 0,
+            depth_level: 0,
+            mipmap: 0,
+            x: 0,
+            y: 0,
+        };
+        let mut worst_error = 0u64;
+        for layer in 0..self.layers {
+            for depth_level in 0..self.depth {
+                let expected = original
+                    .get(layer, depth_level, 0)
+                    .ok_or(SurfaceError::MipmapDataOutOfBounds { layer, mipmap: 0 })?;
+                let actual = decoded
+                    .get(layer, depth_level, 0)
+                    .ok_or(SurfaceError::MipmapDataOutOfBounds { layer, mipmap: 0 })?;
+
+                let mut block_errors = vec![0u64; blocks_wide as usize * blocks_high as usize];
+                for (i, (a, b)) in expected
+                    .chunks_exact(4)
+                    .zip(actual.chunks_exact(4))
+                    .enumerate()
+                {
+                    let x = i as u32 % self.width / block_width;
+                    let y = i as u32 / self.width / block_height;
+                    for c in 0..4 {
+                        let error = a[c].abs_diff(b[c]);
+                        max_error[c] = max_error[c].max(error);
+                        sum[c] += error as u64;
+                        block_errors[(y * blocks_wide + x) as usize] += (error as u64).pow(2);
+                    }
+                }
+
+                for (i, error) in block_errors.into_iter().enumerate() {
+                    if error > worst_error {
+                        worst_error = error;
+                        worst_block = BlockLocation {
+                            layer,
+                            depth_level,
+                            mipmap: 0,
+                            x: i as u32 % blocks_wide,
+                            y: i as u32 / blocks_wide,
+                        };
+                    }
+                }
+            }
+        }
+
+        let count = self.width as u64 * self.height as u64 * self.depth as u64 * self.layers as u64;
+        Ok(RoundtripReport {
+            max_error,
+            mean_error: sum.map(|s| (s as f64 / count as f64) as f32),
+            worst_block,
+            passed: max_error.iter().all(|e| *e <= tolerance),
+        })
+    }
+
+    /// Decode all layers and mipmaps from `surface` to RGBA8 with additional `options`.
+    pub fn decode_rgba8_with_options(
+        &self,
+        options: &DecodeOptions,
+    ) -> Result<SurfaceRgba8<Vec<u8>>, SurfaceError> {
+        let mut decoded = self.decode_rgba8()?;
+        if let Some(cutoff) = options.alpha_cutoff {
+            for pixel in decoded.data.chunks_exact_mut(4) {
+                pixel[3] = if pixel[3] < cutoff { 0 } else { 255 };
+            }
+        }
+        Ok(decoded)
+    }
+
+    /// Decode all layers and mipmaps from `surface` to RGBA8 and write the result to `output`.
+    ///
+    /// This uses the same data as [Surface::decode_rgba8] without allocating the output,
+    /// so buffers can be reused when decoding many surfaces.
+    /// Fails if `output` is too small. Any data after the decoded surface is left unchanged.
+    pub fn decode_rgba8_into(&self, output: &mut [u8]) -> Result<(), SurfaceError> {
+        check_format_data_length(self)?;
+        self.validate_base()?;
+
+        // Each depth slice of each mipmap is decoded separately.
+        let expected =
+            (0..self.mipmaps)
+                .try_fold(0usize, |sum, m| {
+                    let pixels = (mip_dimension(self.width, m) as usize)
+                        .checked_mul(mip_dimension(self.height, m) as usize)?;
+                    sum.checked_add(pixels)
+                })
+                .and_then(|pixels| pixels.checked_mul(self.depth as usize))
+                .and_then(|pixels| pixels.checked_mul(self.layers as usize))
+                .and_then(|pixels| pixels.checked_mul(4))
+                .ok_or(SurfaceError::PixelCountWouldOverflow {
+                    width: self.width,
+                    height: self.height,
+                    depth: self.depth,
+                })?;
+        let actual = output.len();
+        let output = output
+            .get_mut(..expected)
+            .ok_or(SurfaceError::NotEnoughData { expected, actual })?;
+
+        // Decode each 2D image directly into its range of the output.
+        let mut offset = 0;
+        for_each_mipmap(
+            self,
+            0..self.layers,
+            0..self.mipmaps,
+            |width, height, data| {
+                let size = width as usize * height as usize * 4;
+                decode_rgba8_2d_into(
+                    width,
+                    height,
+                    self.image_format,
+                    data,
+                    &mut output[offset..offset + size],
+                )?;
+                offset += size;
+                Ok(())
+            },
+        )
+    }
+
+    /// Decode all layers and mipmaps from `surface` to RGBA8
+    /// and record if the decoded data is sRGB encoded.
+    ///
+    /// The decoded bytes for sRGB formats should not be treated as linear values.
+    pub fn decode_rgba8_with_color_space(&self) -> Result<DecodedRgba8, SurfaceError> {
+        Ok(DecodedRgba8 {
+            surface: self.decode_rgba8()?,
+            is_srgb: self.image_format.is_srgb(),
+        })
+    }
+
     /// Decode a specific range of layers and mipmaps from `surface` to RGBA8.
     pub fn decode_layers_mipmaps_rgba8(
         &self,
         layers: Range<u32>,
         mipmaps: Range<u32>,
     ) -> Result<SurfaceRgba8<Vec<u8>>, SurfaceError> {
-        self.validate()?;
+        check_format_data_length(self)?;
+        self.validate_base()?;
 
-        let data = decode_surface(self, layers.clone(), mipmaps.clone())?;
+        let data = decode_surface(self, layers.clone(), mipmaps.clone(), 1)?;
 
         Ok(SurfaceRgba8 {
             width: mip_dimension(self.width, mipmaps.start),
@@ -39,6 +309,60 @@ impl<T: AsRef<[u8]>> Surface<T> {
         })
     }
 
+    /// Decode all layers and mipmaps from `surface` to RGBA8
+    /// with each row padded to a multiple of `row_alignment` bytes.
+    ///
+    /// This allows uploading directly to graphics APIs with row pitch requirements
+    /// like the 256 byte alignment used by D3D12.
+    /// A `row_alignment` of `1` uses the same tightly packed data as [Surface::decode_rgba8].
+    pub fn decode_rgba8_row_aligned(
+        &self,
+        row_alignment: u32,
+    ) -> Result<RowAlignedRgba8, SurfaceError> {
+        check_format_data_length(self)?;
+        self.validate_base()?;
+
+        let row_alignment = row_alignment.max(1) as usize;
+        let data = decode_surface(self, 0..self.layers, 0..self.mipmaps, row_alignment)?;
+
+        let row_pitches = (0..self.mipmaps)
+            .map(|mipmap| row_pitch(mip_dimension(self.width, mipmap), row_alignment))
+            .collect();
+
+        Ok(RowAlignedRgba8 {
+            width: self.width,
+            height: self.height,
+            depth: self.depth,
+            layers: self.layers,
+            mipmaps: self.mipmaps,
+            row_pitches,
+            data,
+        })
+    }
+
+    /// Decode the base mip level of each face of a cube map to a separate image.
+    ///
+    /// Faces are ordered +X, -X, +Y, -Y, +Z, -Z.
+    /// Fails if the surface does not have exactly 6 layers.
+    #[cfg(feature = "image")]
+    pub fn decode_faces(&self) -> Result<Vec<image::RgbaImage>, crate::error::CreateImageError> {
+        if self.layers != 6 {
+            return Err(crate::error::CreateImageError::UnexpectedLayerCount {
+                layers: self.layers,
+                expected: 6,
+            });
+        }
+
+        let rgba8 = self.decode_layers_mipmaps_rgba8(0..6, 0..1)?;
+        (0..6)
+            .map(|layer| {
+                rgba8
+                    .get_image(layer, 0, 0)
+                    .ok_or(SurfaceError::MipmapDataOutOfBounds { layer, mipmap: 0 }.into())
+            })
+            .collect()
+    }
+
     /// Decode all layers and mipmaps from `surface` to RGBAF32.
     ///
     /// Non floating point formats are normalized to the range `0.0` to `1.0`.
@@ -54,9 +378,10 @@ impl<T: AsRef<[u8]>> Surface<T> {
         layers: Range<u32>,
         mipmaps: Range<u32>,
     ) -> Result<SurfaceRgba32Float<Vec<f32>>, SurfaceError> {
-        self.validate()?;
+        check_format_data_length(self)?;
+        self.validate_base()?;
 
-        let data = decode_surface(self, layers.clone(), mipmaps.clone())?;
+        let data = decode_surface(self, layers.clone(), mipmaps.clone(), 1)?;
 
         Ok(SurfaceRgba32Float {
             width: mip_dimension(self.width, mipmaps.start),
@@ -67,18 +392,453 @@ impl<T: AsRef<[u8]>> Surface<T> {
             data,
         })
     }
+
+    /// Decode all layers and mipmaps from `surface` to RGBAF32 in the range `0.0` to `1.0`
+    /// for analysis or machine learning pipelines.
+    ///
+    /// Values outside this range from HDR formats like [ImageFormat::BC6hRgbUfloat]
+    /// or signed formats like [ImageFormat::R8Snorm] are clamped if `clamp` is `true`
+    /// and passed through unchanged otherwise.
+    pub fn decode_rgbaf32_normalized(
+        &self,
+        clamp: bool,
+    ) -> Result<SurfaceRgba32Float<Vec<f32>>, SurfaceError> {
+        let mut decoded = self.decode_rgbaf32()?;
+        if clamp {
+            for value in &mut decoded.data {
+                *value = value.clamp(0.0, 1.0);
+            }
+        }
+        Ok(decoded)
+    }
+
+    /// Decode a small RGBA8 preview of the first layer and depth slice
+    /// with a width and height of at most `max_dimension`.
+    ///
+    /// The result has the dimensions of the largest mipmap that fits within `max_dimension`.
+    /// Existing mipmaps are decoded directly when possible,
+    /// so this is much faster than decoding and resizing the base level.
+    pub fn thumbnail(&self, max_dimension: u32) -> Result<SurfaceRgba8<Vec<u8>>, SurfaceError> {
+        let max_dimension = max_dimension.max(1);
+        let target = (0..)
+            .find(|m| {
+                mip_dimension(self.width, *m) <= max_dimension
+                    && mip_dimension(self.height, *m) <= max_dimension
+            })
+            .unwrap_or_default();
+
+        // Generate any smaller mipmaps not present in the surface.
+        let mipmap = target.min(self.mipmaps.saturating_sub(1));
+        let decoded = self.decode_layers_mipmaps_rgba8(0..1, mipmap..mipmap + 1)?;
+
+        let mut width = decoded.width as usize;
+        let mut height = decoded.height as usize;
+        let mut data = decoded
+            .get(0, 0, 0)
+            .ok_or(SurfaceError::MipmapDataOutOfBounds { layer: 0, mipmap })?
+            .to_vec();
+        for _ in mipmap..target {
+            let new_width = (width / 2).max(1);
+            let new_height = (height / 2).max(1);
+            data = downsample_rgba(
+                new_width,
+                new_height,
+                1,
+                width,
+                height,
+                1,
+                &data,
+                AddressMode::Clamp,
+            );
+            width = new_width;
+            height = new_height;
+        }
+
+        Ok(SurfaceRgba8 {
+            width: width as u32,
+            height: height as u32,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            data,
+        })
+    }
+
+    /// Returns `true` if both surfaces have the same dimensions, layers, and mipmaps
+    /// and every decoded RGBAF32 channel differs by at most `tolerance`.
+    ///
+    /// Unlike comparing `data` directly, this ignores the image format, data layout,
+    /// mipmap order, and any padding bytes or pixels outside the surface dimensions.
+    /// Surfaces that fail to decode are never equal.
+    pub fn visually_equals<U: AsRef<[u8]>>(&self, other: &Surface<U>, tolerance: f32) -> bool {
+        if (
+            self.width,
+            self.height,
+            self.depth,
+            self.layers,
+            self.mipmaps,
+        ) != (
+            other.width,
+            other.height,
+            other.depth,
+            other.layers,
+            other.mipmaps,
+        ) {
+            return false;
+        }
+
+        // Decoded mipmaps are always in the default order.
+        match (self.decode_rgbaf32(), other.decode_rgbaf32()) {
+            (Ok(a), Ok(b)) => a
+                .data
+                .iter()
+                .zip(b.data.iter())
+                .all(|(a, b)| a == b || (a - b).abs() <= tolerance),
+            _ => false,
+        }
+    }
+}
+
+/// Decode the base mip level of all layers and depth slices of `a` and `b`
+/// and calculate quality metrics for comparing the two surfaces.
+///
+/// This is intended for comparing the output of different encoders for the same image.
+/// The surfaces must have the same dimensions and layers but can use different formats.
+/// Any mipmaps after the base level are ignored.
+pub fn compare_surfaces<A: AsRef<[u8]>, B: AsRef<[u8]>>(
+    a: &Surface<A>,
+    b: &Surface<B>,
+) -> Result<CompareReport, SurfaceError> {
+    if (a.width, a.height, a.depth, a.layers) != (b.width, b.height, b.depth, b.layers) {
+        return Err(SurfaceError::MismatchedDimensions {
+            width: b.width,
+            height: b.height,
+            depth: b.depth,
+            layers: b.layers,
+            expected_width: a.width,
+            expected_height: a.height,
+            expected_depth: a.depth,
+            expected_layers: a.layers,
+        });
+    }
+
+    let decoded_a = a.decode_layers_mipmaps_rgba8(0..a.layers, 0..1)?;
+    let decoded_b = b.decode_layers_mipmaps_rgba8(0..b.layers, 0..1)?;
+
+    let mut max_difference = [0u8; 4];
+    let mut squared_error = 0u64;
+    for (pixel_a, pixel_b) in decoded_a
+        .data
+        .chunks_exact(4)
+        .zip(decoded_b.data.chunks_exact(4))
+    {
+        for c in 0..4 {
+            let difference = pixel_a[c].abs_diff(pixel_b[c]);
+            max_difference[c] = max_difference[c].max(difference);
+            squared_error += (difference as u64).pow(2);
+        }
+    }
+
+    // Each layer and depth slice is a separate 2D image for SSIM.
+    let slice_size = a.width as usize * a.height as usize * 4;
+    let slices = decoded_a.data.chunks_exact(slice_size);
+    let slice_count = slices.len();
+    let ssim_sum: f64 = slices
+        .zip(decoded_b.data.chunks_exact(slice_size))
+        .map(|(slice_a, slice_b)| ssim(slice_a, slice_b, a.width as usize, a.height as usize))
+        .sum();
+
+    let count = decoded_a.data.len();
+    Ok(CompareReport {
+        psnr: psnr((squared_error as f64 / count as f64) as f32),
+        ssim: (ssim_sum / slice_count as f64) as f32,
+        max_difference,
+    })
+}
+
+/// The mean SSIM of all channels using non overlapping 8x8 windows.
+fn ssim(a: &[u8], b: &[u8], width: usize, height: usize) -> f64 {
+    const WINDOW_SIZE: usize = 8;
+    const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+    const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+
+    let mut sum = 0.0;
+    let mut window_count = 0;
+    for window_y in (0..height).step_by(WINDOW_SIZE) {
+        for window_x in (0..width).step_by(WINDOW_SIZE) {
+            for c in 0..4 {
+                // Windows at the edges may be smaller than the window size.
+                let values = (window_y..(window_y + WINDOW_SIZE).min(height)).flat_map(|y| {
+                    (window_x..(window_x + WINDOW_SIZE).min(width)).map(move |x| {
+                        let i = (y * width + x) * 4 + c;
+                        (a[i] as f64, b[i] as f64)
+                    })
+                });
+
+                let n = values.clone().count() as f64;
+                let mean_a = values.clone().map(|(a, _)| a).sum::<f64>() / n;
+                let mean_b = values.clone().map(|(_, b)| b).sum::<f64>() / n;
+                let (mut variance_a, mut variance_b, mut covariance) = (0.0, 0.0, 0.0);
+                for (a, b) in values {
+                    variance_a += (a - mean_a).powi(2) / n;
+                    variance_b += (b - mean_b).powi(2) / n;
+                    covariance += (a - mean_a) * (b - mean_b) / n;
+                }
+
+                sum += ((2.0 * mean_a * mean_b + C1) * (2.0 * covariance + C2))
+                    / ((mean_a.powi(2) + mean_b.powi(2) + C1) * (variance_a + variance_b + C2));
+                window_count += 1;
+            }
+        }
+    }
+
+    sum / window_count as f64
+}
+
+/// The peak signal to noise ratio in decibels for 8-bit data.
+pub(crate) fn psnr(mean_squared_error: f32) -> f32 {
+    if mean_squared_error == 0.0 {
+        f32::INFINITY
+    } else {
+        10.0 * (255.0f32.powi(2) / mean_squared_error).log10()
+    }
+}
+
+/// Quality metrics from comparing two surfaces with [compare_surfaces].
+#[derive(Debug, PartialEq, Clone)]
+pub struct CompareReport {
+    /// The peak signal to noise ratio in decibels for all channels.
+    ///
+    /// This is [f32::INFINITY] if the decoded data matches exactly.
+    pub psnr: f32,
+    /// The mean structural similarity index for all channels from `-1.0` to `1.0`.
+    ///
+    /// This is `1.0` if the decoded data matches exactly.
+    pub ssim: f32,
+    /// The largest absolute difference for the R, G, B, and A channels.
+    pub max_difference: [u8; 4],
+}
+
+/// Decoded RGBA8 data with the color space of the source format.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DecodedRgba8 {
+    /// The decoded surface.
+    pub surface: SurfaceRgba8<Vec<u8>>,
+    /// `true` if the data is sRGB encoded and `false` if the data is linear.
+    pub is_srgb: bool,
+}
+
+/// Decoded RGBA8 data with any blocks that may indicate corrupted data.
+#[derive(Debug, PartialEq, Clone)]
+pub struct StrictDecodedRgba8 {
+    /// The decoded surface.
+    ///
+    /// Invalid blocks are still decoded with the same output as [Surface::decode_rgba8].
+    pub surface: SurfaceRgba8<Vec<u8>>,
+    /// The blocks with invalid mode bits in the order returned by [Surface::iter_blocks].
+    pub invalid_blocks: Vec<BlockLocation>,
+}
+
+/// The error between a decoded surface and its original RGBA8 data.
+#[derive(Debug, PartialEq, Clone)]
+pub struct RoundtripReport {
+    /// The largest absolute error for the R, G, B, and A channels.
+    pub max_error: [u8; 4],
+    /// The mean absolute error for the R, G, B, and A channels.
+    pub mean_error: [f32; 4],
+    /// The block with the largest total squared error.
+    ///
+    /// This is the first block if the decoded data matches exactly.
+    pub worst_block: BlockLocation,
+    /// `true` if the maximum error for every channel is within the tolerance.
+    pub passed: bool,
+}
+
+/// Decoded RGBA8 data with padded rows for uploading directly to the GPU.
+#[derive(Debug, PartialEq, Clone)]
+pub struct RowAlignedRgba8 {
+    /// The width of the surface in pixels.
+    pub width: u32,
+    /// The height of the surface in pixels.
+    pub height: u32,
+    /// The depth of the surface in pixels.
+    pub depth: u32,
+    /// The number of array layers in the surface.
+    pub layers: u32,
+    /// The number of mipmaps in the surface.
+    pub mipmaps: u32,
+    /// The number of bytes from the start of one row to the start of the next row for each mipmap.
+    pub row_pitches: Vec<usize>,
+    /// The combined image data in the same order as [SurfaceRgba8::data].
+    /// Each row is padded with zeros to the row pitch for its mipmap.
+    pub data: Vec<u8>,
+}
+
+fn row_pitch(width: u32, row_alignment: usize) -> usize {
+    (width as usize * 4).next_multiple_of(row_alignment)
+}
+
+// Formats without alpha decode to an alpha of 255 for every pixel.
+fn has_alpha_channel(format: ImageFormat) -> bool {
+    !matches!(
+        format,
+        ImageFormat::R8Unorm
+            | ImageFormat::R8Snorm
+            | ImageFormat::Rg8Unorm
+            | ImageFormat::Rg8Snorm
+            | ImageFormat::Bgr8Unorm
+            | ImageFormat::Rgb8Unorm
+            | ImageFormat::BC1RgbUnorm
+            | ImageFormat::BC1RgbUnormSrgb
+            | ImageFormat::BC4RUnorm
+            | ImageFormat::BC4RSnorm
+            | ImageFormat::BC5RgUnorm
+            | ImageFormat::BC5RgSnorm
+            | ImageFormat::BC6hRgbUfloat
+            | ImageFormat::BC6hRgbSfloat
+            | ImageFormat::R16Unorm
+            | ImageFormat::R16Snorm
+            | ImageFormat::Rg16Unorm
+            | ImageFormat::Rg16Snorm
+            | ImageFormat::R16Float
+            | ImageFormat::Rg16Float
+            | ImageFormat::R32Float
+            | ImageFormat::Rg32Float
+            | ImageFormat::Rgb32Float
+            | ImageFormat::Rgb9e5Ufloat
+            | ImageFormat::Rg11b10Float
+            | ImageFormat::EacR11Unorm
+            | ImageFormat::EacRg11Unorm
+    )
+}
+
+fn check_format_data_length<T: AsRef<[u8]>>(surface: &Surface<T>) -> Result<(), SurfaceError> {
+    // Check the mipmaps first to avoid calculating sizes for corrupted mipmap counts.
+    // Zero sized surfaces are reported later by validation.
+    let max_mipmaps = max_mipmap_count(surface.width.max(surface.height).max(surface.depth));
+    if surface.mipmaps > max_mipmaps.max(1) {
+        return Err(SurfaceError::UnexpectedMipmapCount {
+            mipmaps: surface.mipmaps,
+            max_mipmaps,
+        });
+    }
+
+    // Compressed data labeled with a format with larger blocks would be misread.
+    // The total size does not depend on the order of the mipmaps.
+    let block_dimensions = surface.image_format.block_dimensions();
+    if block_dimensions == (1, 1, 1) {
+        return Ok(());
+    }
+
+    let expected = calculate_offset(
+        surface.layers,
+        0,
+        0,
+        (surface.width, surface.height, surface.depth),
+        block_dimensions,
+        surface.image_format.block_size_in_bytes(),
+        surface.mipmaps,
+        surface.layout,
+        MipOrder::LargestFirst,
+    )
+    .ok_or(SurfaceError::PixelCountWouldOverflow {
+        width: surface.width,
+        height: surface.height,
+        depth: surface.depth,
+    })?;
+
+    let actual = surface.data.as_ref().len();
+    if actual < expected {
+        Err(SurfaceError::FormatDataMismatch {
+            format: surface.image_format,
+            expected,
+            actual,
+        })
+    } else {
+        Ok(())
+    }
 }
 
 fn decode_surface<T, P>(
     surface: &Surface<T>,
     layers: Range<u32>,
     mipmaps: Range<u32>,
+    row_alignment: usize,
 ) -> Result<Vec<P>, SurfaceError>
 where
     T: AsRef<[u8]>,
-    P: Decode + Copy,
+    P: Decode + Copy + Default,
 {
     let mut combined_surface_data = Vec::new();
+    decode_mipmaps(surface, layers, mipmaps, |width, data: Vec<P>| {
+        if row_alignment > 1 {
+            // Pad each row with zeros to the aligned row pitch.
+            let pitch = row_pitch(width, row_alignment);
+            for row in data.chunks_exact(width as usize * 4) {
+                combined_surface_data.extend_from_slice(row);
+                combined_surface_data.resize(
+                    combined_surface_data.len() + pitch - row.len(),
+                    P::default(),
+                );
+            }
+        } else {
+            combined_surface_data.extend_from_slice(&data);
+        }
+    })?;
+
+    Ok(combined_surface_data)
+}
+
+// Decode each 2D image in order and pass its width and decoded data to f.
+fn decode_mipmaps<T, P>(
+    surface: &Surface<T>,
+    layers: Range<u32>,
+    mipmaps: Range<u32>,
+    mut f: impl FnMut(u32, Vec<P>),
+) -> Result<(), SurfaceError>
+where
+    T: AsRef<[u8]>,
+    P: Decode,
+{
+    let image_format = surface.image_format;
+    for_each_mipmap(surface, layers, mipmaps, |width, height, data| {
+        // TODO: Avoid additional copies?
+        let data = P::decode(width, height, image_format, data)?;
+        f(width, data);
+        Ok(())
+    })
+}
+
+// Pass the width, height, and encoded data of each 2D image in order to f.
+fn for_each_mipmap<T>(
+    surface: &Surface<T>,
+    layers: Range<u32>,
+    mipmaps: Range<u32>,
+    mut f: impl FnMut(u32, u32, &[u8]) -> Result<(), SurfaceError>,
+) -> Result<(), SurfaceError>
+where
+    T: AsRef<[u8]>,
+{
+    // Decoding assumes each 2D image has no padding between rows.
+    let tight = match surface.layout {
+        DataLayout::Tight => None,
+        _ => Some(surface.with_layout(DataLayout::Tight)?),
+    };
+    let surface = match &tight {
+        Some(tight) => tight.as_ref(),
+        None => Surface {
+            width: surface.width,
+            height: surface.height,
+            depth: surface.depth,
+            layers: surface.layers,
+            mipmaps: surface.mipmaps,
+            image_format: surface.image_format,
+            layout: surface.layout,
+            mip_order: surface.mip_order,
+            data: surface.data.as_ref(),
+        },
+    };
+
     for layer in layers {
         for level in 0..surface.depth {
             for mipmap in mipmaps.clone() {
@@ -90,15 +850,12 @@ where
                 let width = mip_dimension(surface.width, mipmap);
                 let height = mip_dimension(surface.height, mipmap);
 
-                // TODO: Avoid additional copies?
-                let data = P::decode(width, height, surface.image_format, data)?;
-
-                combined_surface_data.extend_from_slice(&data);
+                f(width, height, data)?;
             }
         }
     }
 
-    Ok(combined_surface_data)
+    Ok(())
 }
 
 // Decoding only works on 2D surfaces.
@@ -118,41 +875,112 @@ impl Decode for u8 {
         image_format: ImageFormat,
         data: &[u8],
     ) -> Result<Vec<Self>, SurfaceError> {
-        use ImageFormat as F;
-        match image_format {
-            F::BC1RgbaUnorm | F::BC1RgbaUnormSrgb => decode_bcn::<Bc1, u8>(width, height, data),
-            F::BC2RgbaUnorm | F::BC2RgbaUnormSrgb => decode_bcn::<Bc2, u8>(width, height, data),
-            F::BC3RgbaUnorm | F::BC3RgbaUnormSrgb => decode_bcn::<Bc3, u8>(width, height, data),
-            F::BC4RUnorm => decode_bcn::<Bc4, u8>(width, height, data),
-            F::BC4RSnorm => decode_bcn::<Bc4S, u8>(width, height, data),
-            F::BC5RgUnorm => decode_bcn::<Bc5, u8>(width, height, data),
-            F::BC5RgSnorm => decode_bcn::<Bc5S, u8>(width, height, data),
-            F::BC6hRgbUfloat | F::BC6hRgbSfloat => decode_bcn::<Bc6, u8>(width, height, data),
-            F::BC7RgbaUnorm | F::BC7RgbaUnormSrgb => decode_bcn::<Bc7, u8>(width, height, data),
-            F::R8Unorm => decode_rgba::<R8, u8>(width, height, data),
-            F::R8Snorm => decode_rgba::<R8Snorm, u8>(width, height, data),
-            F::Rg8Unorm => decode_rgba::<Rg8, u8>(width, height, data),
-            F::Rg8Snorm => decode_rgba::<Rg8Snorm, u8>(width, height, data),
-            F::Rgba8Unorm | F::Rgba8UnormSrgb => decode_rgba::<Rgba8, u8>(width, height, data),
-            F::Rgba16Float => decode_rgba::<Rgbaf16, u8>(width, height, data),
-            F::Rgba32Float => decode_rgba::<Rgbaf32, u8>(width, height, data),
-            F::Bgra8Unorm | F::Bgra8UnormSrgb => decode_rgba::<Bgra8, u8>(width, height, data),
-            F::Rgba8Snorm => decode_rgba::<Rgba8Snorm, u8>(width, height, data),
-            F::Bgra4Unorm => decode_rgba::<Bgra4, u8>(width, height, data),
-            F::Bgr8Unorm => decode_rgba::<Bgr8, u8>(width, height, data),
-            F::R16Unorm => decode_rgba::<R16, u8>(width, height, data),
-            F::R16Snorm => decode_rgba::<R16Snorm, u8>(width, height, data),
-            F::Rg16Unorm => decode_rgba::<Rg16, u8>(width, height, data),
-            F::Rg16Snorm => decode_rgba::<Rg16Snorm, u8>(width, height, data),
-            F::Rgba16Unorm => decode_rgba::<Rgba16, u8>(width, height, data),
-            F::Rgba16Snorm => decode_rgba::<Rgba16Snorm, u8>(width, height, data),
-            F::Rg16Float => decode_rgba::<Rgf16, u8>(width, height, data),
-            F::Rg32Float => decode_rgba::<Rgf32, u8>(width, height, data),
-            F::R16Float => decode_rgba::<Rf16, u8>(width, height, data),
-            F::R32Float => decode_rgba::<Rf32, u8>(width, height, data),
-            F::Rgb32Float => decode_rgba::<Rgbf32, u8>(width, height, data),
-            F::Bgr5A1Unorm => decode_rgba::<Bgr5A1, u8>(width, height, data),
+        let len = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|n| n.checked_mul(4))
+            .ok_or(SurfaceError::PixelCountWouldOverflow {
+                width,
+                height,
+                depth: 1,
+            })?;
+        let mut rgba = vec![0u8; len];
+        decode_rgba8_2d_into(width, height, image_format, data, &mut rgba)?;
+        Ok(rgba)
+    }
+}
+
+// Decode a single 2D image to `rgba` with a length of `width * height * 4`.
+fn decode_rgba8_2d_into(
+    width: u32,
+    height: u32,
+    image_format: ImageFormat,
+    data: &[u8],
+    rgba: &mut [u8],
+) -> Result<(), SurfaceError> {
+    use ImageFormat as F;
+    match image_format {
+        F::BC1RgbaUnorm | F::BC1RgbaUnormSrgb => {
+            decode_bcn_into::<Bc1, u8>(width, height, data, rgba)
+        }
+        F::BC1RgbUnorm | F::BC1RgbUnormSrgb => {
+            // Ignore the 1-bit alpha used for transparent black pixels.
+            decode_bcn_into::<Bc1, u8>(width, height, data, rgba)?;
+            rgba.chunks_exact_mut(4).for_each(|p| p[3] = 255u8);
+            Ok(())
         }
+        F::BC2RgbaUnorm | F::BC2RgbaUnormSrgb => {
+            decode_bcn_into::<Bc2, u8>(width, height, data, rgba)
+        }
+        F::BC3RgbaUnorm | F::BC3RgbaUnormSrgb => {
+            decode_bcn_into::<Bc3, u8>(width, height, data, rgba)
+        }
+        F::BC4RUnorm => decode_bcn_into::<Bc4, u8>(width, height, data, rgba),
+        F::BC4RSnorm => decode_bcn_into::<Bc4S, u8>(width, height, data, rgba),
+        F::BC5RgUnorm => decode_bcn_into::<Bc5, u8>(width, height, data, rgba),
+        F::BC5RgSnorm => decode_bcn_into::<Bc5S, u8>(width, height, data, rgba),
+        F::BC6hRgbUfloat | F::BC6hRgbSfloat => {
+            decode_bcn_into::<Bc6, u8>(width, height, data, rgba)
+        }
+        F::BC7RgbaUnorm | F::BC7RgbaUnormSrgb => {
+            decode_bcn_into::<Bc7, u8>(width, height, data, rgba)
+        }
+        F::R8Unorm => decode_rgba_into::<R8, u8>(width, height, data, rgba),
+        F::R8Snorm => decode_rgba_into::<R8Snorm, u8>(width, height, data, rgba),
+        F::Rg8Unorm => decode_rgba_into::<Rg8, u8>(width, height, data, rgba),
+        F::Rg8Snorm => decode_rgba_into::<Rg8Snorm, u8>(width, height, data, rgba),
+        F::Rgba8Unorm | F::Rgba8UnormSrgb => {
+            decode_rgba_into::<Rgba8, u8>(width, height, data, rgba)
+        }
+        F::Rgba16Float => decode_rgba_into::<Rgbaf16, u8>(width, height, data, rgba),
+        F::Rgba32Float => decode_rgba_into::<Rgbaf32, u8>(width, height, data, rgba),
+        F::Bgra8Unorm | F::Bgra8UnormSrgb => {
+            decode_rgba_into::<Bgra8, u8>(width, height, data, rgba)
+        }
+        F::Rgba8Snorm => decode_rgba_into::<Rgba8Snorm, u8>(width, height, data, rgba),
+        F::Bgra4Unorm => decode_rgba_into::<Bgra4, u8>(width, height, data, rgba),
+        F::Bgr8Unorm => decode_rgba_into::<Bgr8, u8>(width, height, data, rgba),
+        F::Rgb8Unorm => decode_rgba_into::<Rgb8, u8>(width, height, data, rgba),
+        F::R16Unorm => decode_rgba_into::<R16, u8>(width, height, data, rgba),
+        F::R16Snorm => decode_rgba_into::<R16Snorm, u8>(width, height, data, rgba),
+        F::Rg16Unorm => decode_rgba_into::<Rg16, u8>(width, height, data, rgba),
+        F::Rg16Snorm => decode_rgba_into::<Rg16Snorm, u8>(width, height, data, rgba),
+        F::Rgba16Unorm => decode_rgba_into::<Rgba16, u8>(width, height, data, rgba),
+        F::Rgba16Snorm => decode_rgba_into::<Rgba16Snorm, u8>(width, height, data, rgba),
+        F::Rg16Float => decode_rgba_into::<Rgf16, u8>(width, height, data, rgba),
+        F::Rg32Float => decode_rgba_into::<Rgf32, u8>(width, height, data, rgba),
+        F::R16Float => decode_rgba_into::<Rf16, u8>(width, height, data, rgba),
+        F::R32Float => decode_rgba_into::<Rf32, u8>(width, height, data, rgba),
+        F::Rgb32Float => decode_rgba_into::<Rgbf32, u8>(width, height, data, rgba),
+        F::Bgr5A1Unorm => decode_rgba_into::<Bgr5A1, u8>(width, height, data, rgba),
+        F::Rgb9e5Ufloat => decode_rgba_into::<Rgb9e5, u8>(width, height, data, rgba),
+        F::Rg11b10Float => decode_rgba_into::<Rg11b10, u8>(width, height, data, rgba),
+        F::EacR11Unorm => decode_bcn_into::<EacR11, u8>(width, height, data, rgba),
+        F::EacRg11Unorm => decode_bcn_into::<EacRg11, u8>(width, height, data, rgba),
+    }
+}
+
+// A single alpha value for each pixel.
+#[derive(Debug, Clone, Copy, Default)]
+struct Alpha8(u8);
+
+impl Decode for Alpha8 {
+    fn decode(
+        width: u32,
+        height: u32,
+        image_format: ImageFormat,
+        data: &[u8],
+    ) -> Result<Vec<Self>, SurfaceError> {
+        use ImageFormat as F;
+        let rgba8 = match image_format {
+            F::BC2RgbaUnorm | F::BC2RgbaUnormSrgb => {
+                decode_bcn::<Bc2Alpha, u8>(width, height, data)?
+            }
+            F::BC3RgbaUnorm | F::BC3RgbaUnormSrgb => {
+                decode_bcn::<Bc3Alpha, u8>(width, height, data)?
+            }
+            _ => u8::decode(width, height, image_format, data)?,
+        };
+        Ok(rgba8.chunks_exact(4).map(|p| Alpha8(p[3])).collect())
     }
 }
 
@@ -171,6 +999,8 @@ impl Decode for f32 {
             F::BC4RSnorm => decode_bcn::<Bc4S, f32>(width, height, data),
             F::BC5RgSnorm => decode_bcn::<Bc5S, f32>(width, height, data),
             F::BC6hRgbUfloat | F::BC6hRgbSfloat => decode_bcn::<Bc6, f32>(width, height, data),
+            F::EacR11Unorm => decode_bcn::<EacR11, f32>(width, height, data),
+            F::EacRg11Unorm => decode_bcn::<EacRg11, f32>(width, height, data),
             F::R16Float => decode_rgba::<Rf16, f32>(width, height, data),
             F::Rg16Float => decode_rgba::<Rgf16, f32>(width, height, data),
             F::Rgba16Float => decode_rgba::<Rgbaf16, f32>(width, height, data),
@@ -178,6 +1008,8 @@ impl Decode for f32 {
             F::Rg32Float => decode_rgba::<Rgf32, f32>(width, height, data),
             F::Rgb32Float => decode_rgba::<Rgbf32, f32>(width, height, data),
             F::Rgba32Float => decode_rgba::<Rgbaf32, f32>(width, height, data),
+            F::Rgb9e5Ufloat => decode_rgba::<Rgb9e5, f32>(width, height, data),
+            F::Rg11b10Float => decode_rgba::<Rg11b10, f32>(width, height, data),
             F::R16Unorm => decode_rgba::<R16, f32>(width, height, data),
             F::Rg16Unorm => decode_rgba::<Rg16, f32>(width, height, data),
             F::Rgba16Unorm => decode_rgba::<Rgba16, f32>(width, height, data),
@@ -199,6 +1031,164 @@ mod tests {
 
     use strum::IntoEnumIterator;
 
+    #[test]
+    fn decode_surface_format_data_mismatch() {
+        // BC1 data for 2 mipmaps labeled as BC7.
+        let result = Surface {
+            width: 8,
+            height: 8,
+            depth: 1,
+            layers: 1,
+            mipmaps: 2,
+            image_format: ImageFormat::BC7RgbaUnorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: &[0u8; 8 * 4 + 8],
+        }
+        .decode_rgba8();
+
+        assert_eq!(
+            Err(SurfaceError::FormatDataMismatch {
+                format: ImageFormat::BC7RgbaUnorm,
+                expected: 16 * 4 + 16,
+                actual: 40
+            }),
+            result
+        );
+    }
+
+    #[test]
+    fn decode_surface_format_data_mismatch_row_aligned() {
+        // Tightly packed BC7 data is too short once rows are padded.
+        let result = Surface {
+            width: 8,
+            height: 8,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format: ImageFormat::BC7RgbaUnorm,
+            layout: DataLayout::RowAligned { row_alignment: 64 },
+            mip_order: MipOrder::LargestFirst,
+            data: &[0u8; 16 * 4],
+        }
+        .decode_rgba8();
+
+        assert_eq!(
+            Err(SurfaceError::FormatDataMismatch {
+                format: ImageFormat::BC7RgbaUnorm,
+                expected: 64 * 2,
+                actual: 64
+            }),
+            result
+        );
+    }
+
+    #[test]
+    fn decode_alpha_r8() {
+        let surface = |image_format, data: &[u8]| Surface {
+            width: 8,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 2,
+            image_format,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: data.to_vec(),
+        };
+
+        // Alpha only decoding should match the alpha of the full RGBA8 decode.
+        let mut data: Vec<u8> = (0..160u8)
+            .map(|i| i.wrapping_mul(73).wrapping_add(11))
+            .collect();
+        // Cover both BC3 interpolation modes with endpoints in either order.
+        data[16] = 200;
+        data[17] = 10;
+        for format in [
+            ImageFormat::BC2RgbaUnorm,
+            ImageFormat::BC3RgbaUnormSrgb,
+            ImageFormat::BC7RgbaUnorm,
+            ImageFormat::Rgba8Unorm,
+        ] {
+            let surface = surface(format, &data);
+            let alpha = surface.decode_alpha_r8().unwrap();
+            let rgba = surface.decode_rgba8().unwrap();
+
+            assert_eq!(ImageFormat::R8Unorm, alpha.image_format);
+            assert_eq!(
+                (8, 4, 1, 2),
+                (alpha.width, alpha.height, alpha.depth, alpha.mipmaps)
+            );
+            assert_eq!(
+                rgba.data.chunks_exact(4).map(|p| p[3]).collect::<Vec<_>>(),
+                alpha.data,
+                "{format:?}"
+            );
+        }
+
+        assert_eq!(
+            Err(SurfaceError::MissingAlphaChannel {
+                format: ImageFormat::BC1RgbUnorm
+            }),
+            surface(ImageFormat::BC1RgbUnorm, &[0u8; 24]).decode_alpha_r8()
+        );
+    }
+
+    #[test]
+    fn is_opaque() {
+        let surface = |image_format, data: &[u8]| Surface {
+            width: 4,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: data.to_vec(),
+        };
+
+        let mut rgba = [255u8; 4 * 4 * 4];
+        assert_eq!(
+            Ok(true),
+            surface(ImageFormat::Rgba8Unorm, &rgba).is_opaque()
+        );
+        rgba[4 * 5 + 3] = 254;
+        assert_eq!(
+            Ok(false),
+            surface(ImageFormat::Rgba8Unorm, &rgba).is_opaque()
+        );
+        assert_eq!(
+            Ok(false),
+            surface(ImageFormat::Bgra8UnormSrgb, &rgba).is_opaque()
+        );
+
+        // Formats without alpha are always opaque.
+        assert_eq!(
+            Ok(true),
+            surface(ImageFormat::R8Unorm, &[0u8; 16]).is_opaque()
+        );
+        assert_eq!(
+            Ok(true),
+            surface(ImageFormat::BC1RgbUnorm, &[0u8; 8]).is_opaque()
+        );
+
+        // BC1 with alpha can use transparent black.
+        let bc1_transparent = [0u8, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF];
+        assert_eq!(
+            Ok(false),
+            surface(ImageFormat::BC1RgbaUnorm, &bc1_transparent).is_opaque()
+        );
+        assert_eq!(
+            Ok(true),
+            surface(ImageFormat::BC1RgbaUnorm, &[0u8; 8]).is_opaque()
+        );
+
+        assert!(surface(ImageFormat::BC7RgbaUnorm, &[0u8; 8])
+            .is_opaque()
+            .is_err());
+    }
+
     #[test]
     fn decode_surface_zero_size() {
         let result = Surface {
@@ -208,6 +1198,8 @@ mod tests {
             layers: 1,
             mipmaps: 1,
             image_format: ImageFormat::Rgba8UnormSrgb,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
             data: &[0u8; 0],
         }
         .decode_rgba8();
@@ -231,6 +1223,8 @@ mod tests {
             layers: 1,
             mipmaps: 1,
             image_format: ImageFormat::Rgba8UnormSrgb,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
             data: &[0u8; 0],
         }
         .decode_rgba8();
@@ -254,6 +1248,8 @@ mod tests {
             layers: 1,
             mipmaps: 10,
             image_format: ImageFormat::Rgba8UnormSrgb,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
             data: &[0u8; 4 * 4 * 4],
         }
         .decode_rgba8();
@@ -267,6 +1263,30 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn decode_surface_corrupted_mipmaps() {
+        let surface = Surface {
+            width: 4,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: u32::MAX,
+            image_format: ImageFormat::BC7RgbaUnorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: &[0u8; 16],
+        };
+
+        assert_eq!(None, surface.get(0, 0, 39));
+        assert_eq!(
+            Err(SurfaceError::UnexpectedMipmapCount {
+                mipmaps: u32::MAX,
+                max_mipmaps: 3
+            }),
+            surface.decode_rgba8()
+        );
+    }
+
     #[test]
     fn decode_layers_mipmaps_rgba8_single_mipmap() {
         let rgba8 = Surface {
@@ -276,6 +1296,8 @@ mod tests {
             layers: 1,
             mipmaps: 3,
             image_format: ImageFormat::Rgba8UnormSrgb,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
             data: &[0u8; 512],
         }
         .decode_layers_mipmaps_rgba8(0..1, 1..2)
@@ -303,6 +1325,8 @@ mod tests {
             layers: 1,
             mipmaps: 1,
             image_format: ImageFormat::Rgba8UnormSrgb,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
             data: &[0u8; 4 * 4 * 4],
         }
         .decode_layers_mipmaps_rgba8(0..1, 0..0)
@@ -321,6 +1345,100 @@ mod tests {
         );
     }
 
+    #[test]
+    fn decode_rgba8_with_color_space_srgb() {
+        let decoded = Surface {
+            width: 1,
+            height: 1,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format: ImageFormat::Rgba8UnormSrgb,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: &[1u8, 2, 3, 4],
+        }
+        .decode_rgba8_with_color_space()
+        .unwrap();
+        assert!(decoded.is_srgb);
+        assert_eq!(vec![1, 2, 3, 4], decoded.surface.data);
+    }
+
+    #[test]
+    fn decode_rgba8_with_color_space_linear() {
+        let decoded = Surface {
+            width: 1,
+            height: 1,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format: ImageFormat::Rgba8Unorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: &[1u8, 2, 3, 4],
+        }
+        .decode_rgba8_with_color_space()
+        .unwrap();
+        assert!(!decoded.is_srgb);
+    }
+
+    #[test]
+    fn decode_rgba8_row_aligned() {
+        let rgba8 = Surface {
+            width: 3,
+            height: 2,
+            depth: 1,
+            layers: 1,
+            mipmaps: 2,
+            image_format: ImageFormat::Rgba8Unorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: &[1u8; (3 * 2 + 1) * 4],
+        }
+        .decode_rgba8_row_aligned(16)
+        .unwrap();
+
+        assert_eq!(
+            RowAlignedRgba8 {
+                width: 3,
+                height: 2,
+                depth: 1,
+                layers: 1,
+                mipmaps: 2,
+                row_pitches: vec![16, 16],
+                data: [
+                    [1u8; 12].as_slice(),
+                    &[0u8; 4],
+                    &[1u8; 12],
+                    &[0u8; 4],
+                    &[1u8; 4],
+                    &[0u8; 12]
+                ]
+                .concat()
+            },
+            rgba8
+        );
+    }
+
+    #[test]
+    fn decode_rgba8_row_aligned_tight() {
+        let surface = Surface {
+            width: 3,
+            height: 2,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format: ImageFormat::Rgba8Unorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: &[1u8; 3 * 2 * 4],
+        };
+        let rgba8 = surface.decode_rgba8_row_aligned(1).unwrap();
+
+        assert_eq!(vec![12], rgba8.row_pitches);
+        assert_eq!(surface.decode_rgba8().unwrap().data, rgba8.data);
+    }
+
     #[test]
     fn decode_layers_mipmaps_rgbaf32_single_mipmap() {
         let rgbaf32 = Surface {
@@ -330,6 +1448,8 @@ mod tests {
             layers: 1,
             mipmaps: 3,
             image_format: ImageFormat::Rgba8UnormSrgb,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
             data: &[0u8; 512],
         }
         .decode_layers_mipmaps_rgbaf32(0..1, 1..2)
@@ -357,6 +1477,8 @@ mod tests {
             layers: 1,
             mipmaps: 1,
             image_format: ImageFormat::Rgba8UnormSrgb,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
             data: &[0u8; 4 * 4 * 4],
         }
         .decode_layers_mipmaps_rgbaf32(0..1, 0..0)
@@ -375,6 +1497,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn decode_bc1_punch_through_alpha() {
+        // Color 0 <= color 1 uses index 3 for transparent black.
+        let block = [0u8, 0, 255, 255, 255, 255, 255, 255];
+        let decode = |image_format| {
+            Surface {
+                width: 4,
+                height: 4,
+                depth: 1,
+                layers: 1,
+                mipmaps: 1,
+                image_format,
+                layout: DataLayout::Tight,
+                mip_order: MipOrder::LargestFirst,
+                data: &block,
+            }
+            .decode_rgba8()
+            .unwrap()
+            .data
+        };
+
+        assert_eq!(vec![0u8; 4 * 4 * 4], decode(ImageFormat::BC1RgbaUnorm));
+        assert_eq!(
+            [0u8, 0, 0, 255].repeat(4 * 4),
+            decode(ImageFormat::BC1RgbUnorm)
+        );
+    }
+
     #[test]
     fn decode_all_u8() {
         for image_format in ImageFormat::iter() {
@@ -386,6 +1536,8 @@ mod tests {
                 layers: 1,
                 mipmaps: 1,
                 image_format,
+                layout: DataLayout::Tight,
+                mip_order: MipOrder::LargestFirst,
                 data: data.as_slice(),
             };
             surface.decode_rgba8().unwrap();
@@ -403,9 +1555,574 @@ mod tests {
                 layers: 1,
                 mipmaps: 1,
                 image_format,
+                layout: DataLayout::Tight,
+                mip_order: MipOrder::LargestFirst,
                 data: data.as_slice(),
             };
             surface.decode_rgbaf32().unwrap();
         }
     }
+
+    #[test]
+    fn decode_row_aligned_layout() {
+        let surface = Surface {
+            width: 1,
+            height: 2,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format: ImageFormat::R8Unorm,
+            layout: DataLayout::RowAligned { row_alignment: 4 },
+            mip_order: MipOrder::LargestFirst,
+            data: &[1u8, 0, 0, 0, 2, 0, 0, 0][..],
+        };
+        assert_eq!(
+            vec![1, 1, 1, 255, 2, 2, 2, 255],
+            surface.decode_rgba8().unwrap().data
+        );
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn decode_faces_cube() {
+        let data: Vec<_> = (0..6u8).flat_map(|i| [i; 4 * 4 * 4]).collect();
+        let surface = Surface {
+            width: 4,
+            height: 4,
+            depth: 1,
+            layers: 6,
+            mipmaps: 1,
+            image_format: ImageFormat::Rgba8Unorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: data.as_slice(),
+        };
+
+        let faces = surface.decode_faces().unwrap();
+        assert_eq!(6, faces.len());
+        for (i, face) in faces.iter().enumerate() {
+            assert_eq!((4, 4), face.dimensions());
+            assert_eq!(&[i as u8; 4 * 4 * 4][..], face.as_raw().as_slice());
+        }
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn decode_faces_invalid_layers() {
+        let surface = Surface {
+            width: 4,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format: ImageFormat::Rgba8Unorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: &[0u8; 4 * 4 * 4][..],
+        };
+
+        assert_eq!(
+            Err(crate::error::CreateImageError::UnexpectedLayerCount {
+                layers: 1,
+                expected: 6
+            }),
+            surface.decode_faces()
+        );
+    }
+
+    #[test]
+    fn visually_equals_ignores_padding() {
+        let tight = Surface {
+            width: 2,
+            height: 2,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format: ImageFormat::Rgba8Unorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: &[1u8, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16][..],
+        };
+        let mut data = [0u8; 32];
+        data[..8].copy_from_slice(&tight.data[..8]);
+        data[16..24].copy_from_slice(&tight.data[8..]);
+        data[8..16].fill(255);
+        let aligned = Surface {
+            layout: DataLayout::RowAligned { row_alignment: 16 },
+            data: &data[..],
+            ..tight
+        };
+        assert_ne!(tight.data, aligned.data);
+        assert!(tight.visually_equals(&aligned, 0.0));
+
+        // The same values in a different format.
+        let bgra = Surface {
+            image_format: ImageFormat::Bgra8Unorm,
+            data: &[3u8, 2, 1, 4, 7, 6, 5, 8, 11, 10, 9, 12, 15, 14, 13, 16][..],
+            ..tight
+        };
+        assert!(tight.visually_equals(&bgra, 0.0));
+    }
+
+    #[test]
+    fn visually_equals_tolerance() {
+        let a = Surface {
+            width: 1,
+            height: 1,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format: ImageFormat::Rgba8Unorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: &[0u8, 0, 0, 255][..],
+        };
+        let b = Surface {
+            data: &[1u8, 0, 0, 255][..],
+            ..a
+        };
+        assert!(!a.visually_equals(&b, 0.0));
+        assert!(a.visually_equals(&b, 1.0 / 255.0));
+
+        let c = Surface { width: 2, ..a };
+        assert!(!a.visually_equals(&c, 1.0));
+    }
+
+    #[test]
+    fn thumbnail_existing_mipmap() {
+        // 4x4, 2x2, and 1x1 mipmaps with a different value for each mipmap.
+        let data: Vec<_> = [vec![1u8; 4 * 4 * 4], vec![2u8; 2 * 2 * 4], vec![3u8; 4]].concat();
+        let surface = Surface {
+            width: 4,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 3,
+            image_format: ImageFormat::Rgba8Unorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: &data[..],
+        };
+
+        let thumbnail = surface.thumbnail(3).unwrap();
+        assert_eq!((2, 2), (thumbnail.width, thumbnail.height));
+        assert_eq!(vec![2u8; 2 * 2 * 4], thumbnail.data);
+
+        let thumbnail = surface.thumbnail(8).unwrap();
+        assert_eq!((4, 4), (thumbnail.width, thumbnail.height));
+        assert_eq!(vec![1u8; 4 * 4 * 4], thumbnail.data);
+    }
+
+    #[test]
+    fn thumbnail_generated_mipmap() {
+        let surface = Surface {
+            width: 8,
+            height: 4,
+            depth: 1,
+            layers: 2,
+            mipmaps: 1,
+            image_format: ImageFormat::Rgba8Unorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: &[4u8; 8 * 4 * 4 * 2][..],
+        };
+
+        let thumbnail = surface.thumbnail(2).unwrap();
+        assert_eq!(
+            SurfaceRgba8 {
+                width: 2,
+                height: 1,
+                depth: 1,
+                layers: 1,
+                mipmaps: 1,
+                data: vec![4u8; 2 * 4]
+            },
+            thumbnail
+        );
+    }
+
+    #[test]
+    fn decode_rgba8_into_reused_buffer() {
+        let surface = Surface {
+            width: 2,
+            height: 2,
+            depth: 1,
+            layers: 2,
+            mipmaps: 2,
+            image_format: ImageFormat::Bgra8Unorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: &[1u8, 2, 3, 4].repeat((4 + 1) * 2)[..],
+        };
+
+        let mut output = vec![7u8; 48];
+        surface.decode_rgba8_into(&mut output).unwrap();
+        assert_eq!(surface.decode_rgba8().unwrap().data, &output[..40]);
+        assert_eq!(&[7u8; 8], &output[40..]);
+
+        assert_eq!(
+            Err(SurfaceError::NotEnoughData {
+                expected: 40,
+                actual: 39
+            }),
+            surface.decode_rgba8_into(&mut output[..39])
+        );
+    }
+
+    #[test]
+    fn decode_rgba8_into_bc1_mipmaps() {
+        // Each mipmap is decoded in place, including the opaque alpha for BC1 RGB.
+        let surface = Surface {
+            width: 8,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 3,
+            image_format: ImageFormat::BC1RgbUnorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: &(0..32u8).collect::<Vec<_>>()[..],
+        };
+
+        let mut output = vec![0u8; (32 + 8 + 2) * 4];
+        surface.decode_rgba8_into(&mut output).unwrap();
+        assert_eq!(surface.decode_rgba8().unwrap().data, output);
+    }
+
+    #[test]
+    fn decode_rgba8_into_large_dimensions() {
+        let surface = Surface {
+            width: u32::MAX,
+            height: u32::MAX,
+            depth: 1,
+            layers: u32::MAX,
+            mipmaps: 1,
+            image_format: ImageFormat::R8Unorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: &[0u8; 4][..],
+        };
+        // Computing the output size should not overflow.
+        assert!(matches!(
+            surface.decode_rgba8_into(&mut [0u8; 4]),
+            Err(SurfaceError::NotEnoughData { .. } | SurfaceError::PixelCountWouldOverflow { .. })
+        ));
+    }
+
+    #[test]
+    fn decode_rgbaf32_rgba32float_hdr() {
+        // Float data should be read directly without clamping through RGBA8.
+        let values = [
+            -2.5f32, 0.25, 16.0, 1.0, 1000.0, 3.5, 0.0, 0.5, 7.0, -1.0, 2.0, 8.0,
+        ];
+        let data: Vec<_> = values.iter().flat_map(|f| f.to_le_bytes()).collect();
+        let surface = Surface {
+            width: 2,
+            height: 1,
+            depth: 1,
+            layers: 1,
+            mipmaps: 2,
+            image_format: ImageFormat::Rgba32Float,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: &data[..],
+        };
+
+        let decoded = surface.decode_rgbaf32().unwrap();
+        assert_eq!(2, decoded.mipmaps);
+        assert_eq!(values.to_vec(), decoded.data);
+    }
+
+    #[test]
+    fn decode_rgbaf32_normalized_clamp() {
+        let data: Vec<_> = [-1.0f32, 0.5, 2.0, 1.0]
+            .iter()
+            .flat_map(|f| f.to_le_bytes())
+            .collect();
+        let surface = Surface {
+            width: 1,
+            height: 1,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format: ImageFormat::Rgba32Float,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: &data[..],
+        };
+
+        let decoded = surface.decode_rgbaf32_normalized(false).unwrap();
+        assert_eq!(vec![-1.0, 0.5, 2.0, 1.0], decoded.data);
+
+        let decoded = surface.decode_rgbaf32_normalized(true).unwrap();
+        assert_eq!(vec![0.0, 0.5, 1.0, 1.0], decoded.data);
+    }
+
+    #[test]
+    fn decode_rgbaf32_normalized_unorm() {
+        let surface = Surface {
+            width: 1,
+            height: 1,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format: ImageFormat::Rgba8Unorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: &[0u8, 51, 255, 255][..],
+        };
+        let decoded = surface.decode_rgbaf32_normalized(true).unwrap();
+        assert_eq!(vec![0.0, 0.2, 1.0, 1.0], decoded.data);
+    }
+
+    #[test]
+    fn decode_rgba8_strict_bc7() {
+        // A valid mode 6 block followed by a block without a mode.
+        let mut data = [0u8; 16 * 3];
+        data[0] = 0b0100_0000;
+        data[32] = 0b0000_0001;
+        let surface = Surface {
+            width: 4,
+            height: 12,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format: ImageFormat::BC7RgbaUnorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: &data[..],
+        };
+
+        let decoded = surface.decode_rgba8_strict().unwrap();
+        assert_eq!(surface.decode_rgba8().unwrap(), decoded.surface);
+        assert_eq!(
+            vec![BlockLocation {
+                layer: 0,
+                depth_level: 0,
+                mipmap: 0,
+                x: 0,
+                y: 1
+            }],
+            decoded.invalid_blocks
+        );
+    }
+
+    #[test]
+    fn invalid_blocks_bc6h_reserved_modes() {
+        let mut data = [0u8; 16 * 6];
+        for (i, mode) in [0b00000, 0b10011, 0b00011, 0b10111, 0b11011, 0b11111]
+            .into_iter()
+            .enumerate()
+        {
+            data[i * 16] = mode;
+        }
+        let surface = Surface {
+            width: 12,
+            height: 8,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format: ImageFormat::BC6hRgbUfloat,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: &data[..],
+        };
+
+        let invalid: Vec<_> = surface
+            .invalid_blocks()
+            .unwrap()
+            .iter()
+            .map(|b| (b.x, b.y))
+            .collect();
+        assert_eq!(vec![(1, 0), (0, 1), (1, 1), (2, 1)], invalid);
+    }
+
+    #[test]
+    fn invalid_blocks_uncompressed() {
+        let surface = Surface {
+            width: 1,
+            height: 1,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format: ImageFormat::Rgba8Unorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: &[0u8; 4][..],
+        };
+        assert_eq!(Ok(Vec::new()), surface.invalid_blocks());
+    }
+
+    #[test]
+    fn compare_surfaces_metrics() {
+        let surface = |image_format, data: Vec<u8>| Surface {
+            width: 4,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data,
+        };
+        let rgba: Vec<u8> = (0..4 * 4)
+            .flat_map(|i| [i * 16, 255 - i * 16, 128, 255])
+            .collect();
+        let a = surface(ImageFormat::Rgba8Unorm, rgba.clone());
+
+        // BGRA has the same decoded data even with a different format.
+        let bgra = rgba
+            .chunks_exact(4)
+            .flat_map(|p| [p[2], p[1], p[0], p[3]])
+            .collect();
+        assert_eq!(
+            CompareReport {
+                psnr: f32::INFINITY,
+                ssim: 1.0,
+                max_difference: [0; 4]
+            },
+            compare_surfaces(&a, &surface(ImageFormat::Bgra8Unorm, bgra)).unwrap()
+        );
+
+        let mut noisy = rgba.clone();
+        noisy[0] = 10;
+        noisy[6] = 128 + 20;
+        let report = compare_surfaces(&a, &surface(ImageFormat::Rgba8Unorm, noisy)).unwrap();
+        assert_eq!([10, 0, 20, 0], report.max_difference);
+        assert!(report.psnr.is_finite() && report.psnr > 30.0);
+        assert!(report.ssim < 1.0 && report.ssim > 0.5);
+    }
+
+    #[test]
+    fn compare_surfaces_mismatched_dimensions() {
+        let surface = |width| Surface {
+            width,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format: ImageFormat::Rgba8Unorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: vec![0u8; 8 * 4 * 4],
+        };
+        assert!(matches!(
+            compare_surfaces(&surface(4), &surface(8)),
+            Err(SurfaceError::MismatchedDimensions {
+                width: 8,
+                expected_width: 4,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn verify_roundtrip_worst_block() {
+        let original = SurfaceRgba8 {
+            width: 8,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            data: vec![0u8; 8 * 4 * 4],
+        };
+
+        // Uncompressed formats use a block for each pixel.
+        let mut data = original.data.clone();
+        data[(2 * 8 + 5) * 4] = 10;
+        data[(2 * 8 + 5) * 4 + 3] = 2;
+        let surface = Surface {
+            width: 8,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format: ImageFormat::Rgba8Unorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data,
+        };
+
+        let report = surface.verify_roundtrip(&original, 5).unwrap();
+        assert_eq!([10, 0, 0, 2], report.max_error);
+        assert_eq!([10.0 / 32.0, 0.0, 0.0, 2.0 / 32.0], report.mean_error);
+        assert_eq!(
+            BlockLocation {
+                layer: 0,
+                depth_level: 0,
+                mipmap: 0,
+                x: 5,
+                y: 2
+            },
+            report.worst_block
+        );
+        assert!(!report.passed);
+        assert!(surface.verify_roundtrip(&original, 10).unwrap().passed);
+    }
+
+    #[test]
+    fn verify_roundtrip_mismatched_dimensions() {
+        let original = SurfaceRgba8 {
+            width: 2,
+            height: 2,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            data: &[0u8; 2 * 2 * 4][..],
+        };
+        let surface = Surface {
+            width: 4,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format: ImageFormat::BC1RgbaUnorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: &[0u8; 8][..],
+        };
+        assert_eq!(
+            Err(SurfaceError::MismatchedDimensions {
+                width: 4,
+                height: 4,
+                depth: 1,
+                layers: 1,
+                expected_width: 2,
+                expected_height: 2,
+                expected_depth: 1,
+                expected_layers: 1
+            }),
+            surface.verify_roundtrip(&original, 0)
+        );
+    }
+
+    #[test]
+    fn decode_rgba8_alpha_cutoff() {
+        let surface = Surface {
+            width: 3,
+            height: 1,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format: ImageFormat::Rgba8Unorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: &[1u8, 2, 3, 127, 4, 5, 6, 128, 7, 8, 9, 200][..],
+        };
+
+        let decoded = surface
+            .decode_rgba8_with_options(&DecodeOptions::default())
+            .unwrap();
+        assert_eq!(surface.data, decoded.data);
+
+        let options = DecodeOptions {
+            alpha_cutoff: Some(128),
+        };
+        let decoded = surface.decode_rgba8_with_options(&options).unwrap();
+        assert_eq!(
+            &[1u8, 2, 3, 0, 4, 5, 6, 255, 7, 8, 9, 255][..],
+            decoded.data
+        );
+    }
 }
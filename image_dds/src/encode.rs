@@ -1,18 +1,27 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, ops::Range};
 
-use crate::bcn::{encode_bcn, Bc1, Bc2, Bc3, Bc4, Bc5, Bc6, Bc7};
+use half::f16;
+
+#[cfg(feature = "intel-tex")]
+use crate::bcn::{encode_bcn, BcnEncode};
+use crate::bcn::{Bc1, Bc2, Bc3, Bc4, Bc5, Bc6, Bc7};
 use crate::rgba::{
-    encode_rgba, Bgr5A1, Bgr8, Bgra4, Bgra8, R16Snorm, R8Snorm, Rf16, Rf32, Rg16, Rg16Snorm, Rg8,
-    Rg8Snorm, Rgba16, Rgba16Snorm, Rgba8, Rgba8Snorm, Rgbaf16, Rgbaf32, Rgbf32, Rgf16, Rgf32, R16,
-    R8,
+    encode_rgba, Bgr5A1, Bgr8, Bgra4, Bgra8, R16Snorm, R8Snorm, Rf16, Rf32, Rg11b10, Rg16,
+    Rg16Snorm, Rg8, Rg8Snorm, Rgb8, Rgb9e5, Rgba16, Rgba16Snorm, Rgba8, Rgba8Snorm, Rgbaf16,
+    Rgbaf32, Rgbf32, Rgf16, Rgf32, R16, R8,
 };
 use crate::{
-    downsample_rgba, error::SurfaceError, max_mipmap_count, mip_dimension, ImageFormat, Mipmaps,
-    Quality, Surface, SurfaceRgba8,
+    block_align_dimensions, calculate_offset, downsample_rgba, downsample_rgba_alpha_weighted,
+    downsample_rgba_triangle, downsample_rgba_triangle_alpha_weighted, error::SurfaceError,
+    layout_mip_size, max_mipmap_count, mip_dimension, AlphaBlockMode, ContentHint, DataLayout,
+    EncodeOptions, ImageFormat, MipOrder, MipmapFilter, Mipmaps, Quality, SingleChannelSource,
+    Surface, SurfaceInfo, SurfaceRgba8,
 };
 use crate::{
+    decode::psnr,
     rgba::convert::{float_to_snorm8, Channel},
-    SurfaceRgba32Float,
+    surface::{get_rgba_mipmap, input_block_dimensions, stored_rgba_mipmap_count},
+    SurfaceRgba16Float, SurfaceRgba32Float, SurfaceRgba8Planar,
 };
 
 impl<T: AsRef<[u8]>> SurfaceRgba8<T> {
@@ -25,8 +34,260 @@ impl<T: AsRef<[u8]>> SurfaceRgba8<T> {
         quality: Quality,
         mipmaps: Mipmaps,
     ) -> Result<Surface<Vec<u8>>, SurfaceError> {
+        self.encode_with_options(format, quality, mipmaps, &EncodeOptions::default())
+    }
+
+    /// Encode an RGBA8 surface to the given `format` with additional encoding `options`.
+    ///
+    /// See [SurfaceRgba8::encode] for details.
+    pub fn encode_with_options(
+        &self,
+        format: ImageFormat,
+        quality: Quality,
+        mipmaps: Mipmaps,
+        options: &EncodeOptions,
+    ) -> Result<Surface<Vec<u8>>, SurfaceError> {
+        self.validate_encode(format, options)?;
+        encode_surface(self, format, quality, mipmaps, options)
+    }
+
+    /// Encode an RGBA8 surface like [SurfaceRgba8::encode_with_options]
+    /// but return a separate buffer for each mipmap of each layer.
+    ///
+    /// Buffers are ordered by layer and then by mipmap from largest to smallest
+    /// and contain all depth slices for that mipmap.
+    /// The [SurfaceInfo] describes the encoded surface as if the buffers were concatenated.
+    pub fn encode_levels(
+        &self,
+        format: ImageFormat,
+        quality: Quality,
+        mipmaps: Mipmaps,
+        options: &EncodeOptions,
+    ) -> Result<(SurfaceInfo, Vec<Vec<u8>>), SurfaceError> {
+        let surface = self.encode_with_options(format, quality, mipmaps, options)?;
+        let levels = split_levels(&surface)?;
+        Ok((surface.info(), levels))
+    }
+
+    /// Encode the region of the base mip level with the given pixel position and dimensions
+    /// for all layers to the given `format` without generating mipmaps.
+    ///
+    /// Block compressed formats read the rows of the region directly from the surface data
+    /// if the region dimensions are a multiple of the block dimensions.
+    /// This avoids copying regions of large texture atlases.
+    /// Other regions and formats are copied with [SurfaceRgba8::crop] before encoding.
+    #[allow(clippy::too_many_arguments)]
+    pub fn encode_region(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        format: ImageFormat,
+        quality: Quality,
+        options: &EncodeOptions,
+    ) -> Result<Surface<Vec<u8>>, SurfaceError> {
+        let (block_width, block_height, _) = format.block_dimensions();
+        let whole_blocks = (width % block_width, height % block_height) == (0, 0);
+        if format.block_dimensions() == (1, 1, 1) || self.depth != 1 || !whole_blocks {
+            return self.crop(x, y, width, height)?.encode_with_options(
+                format,
+                quality,
+                Mipmaps::Disabled,
+                options,
+            );
+        }
+
         self.validate()?;
-        encode_surface(self, format, quality, mipmaps)
+        if width == 0 || height == 0 {
+            return Err(SurfaceError::ZeroSizedSurface {
+                width,
+                height,
+                depth: self.depth,
+            });
+        }
+        let in_bounds =
+            |start: u32, size: u32, max: u32| start.checked_add(size).is_some_and(|end| end <= max);
+        if !in_bounds(x, width, self.width) || !in_bounds(y, height, self.height) {
+            return Err(SurfaceError::CropOutOfBounds {
+                x,
+                y,
+                width,
+                height,
+            });
+        }
+        if quality == Quality::Lossless {
+            return Err(SurfaceError::LossyFormat { format });
+        }
+
+        // The region is in bounds, so the offsets fit within the data for each layer.
+        let stride = self.width as usize * 4;
+        let start = y as usize * stride + x as usize * 4;
+        let end = start + stride * (height as usize - 1) + width as usize * 4;
+
+        let mut data = Vec::new();
+        for layer in 0..self.layers {
+            let base = self
+                .get(layer, 0, 0)
+                .ok_or(SurfaceError::MipmapDataOutOfBounds { layer, mipmap: 0 })?;
+            data.extend_from_slice(&u8::encode(
+                width,
+                height,
+                &base[start..end],
+                stride,
+                format,
+                quality,
+                options,
+            )?);
+        }
+
+        Ok(Surface {
+            width,
+            height,
+            depth: 1,
+            layers: self.layers,
+            mipmaps: 1,
+            image_format: format,
+            layout: DataLayout::Tight,
+            mip_order: options.mip_order,
+            data,
+        })
+    }
+
+    /// Encode the base mip level of an RGBA8 surface to the given `format`
+    /// and calculate the PSNR in decibels for each block compared to the original data.
+    ///
+    /// Blocks are ordered by layer, depth level, row, and then column.
+    /// Blocks with no error have a PSNR of [f32::INFINITY].
+    /// This decodes the encoded data and is intended for debugging rather than exporting.
+    pub fn encode_with_error_map(
+        &self,
+        format: ImageFormat,
+        quality: Quality,
+    ) -> Result<(Surface<Vec<u8>>, Vec<f32>), SurfaceError> {
+        let encoded = self.encode(format, quality, Mipmaps::Disabled)?;
+        let decoded = encoded.decode_rgba8()?;
+
+        let (block_width, block_height, _) = format.block_dimensions();
+        let (width, height) = (self.width as usize, self.height as usize);
+
+        let mut error_map = Vec::new();
+        for layer in 0..self.layers {
+            for level in 0..self.depth {
+                let original = self
+                    .get(layer, level, 0)
+                    .ok_or(SurfaceError::MipmapDataOutOfBounds { layer, mipmap: 0 })?;
+                let decoded = decoded
+                    .get(layer, level, 0)
+                    .ok_or(SurfaceError::MipmapDataOutOfBounds { layer, mipmap: 0 })?;
+
+                for y in (0..height).step_by(block_height as usize) {
+                    for x in (0..width).step_by(block_width as usize) {
+                        // Blocks on the edges may be partially outside the image.
+                        let mut squared_error = 0.0;
+                        let mut count = 0;
+                        for y in y..(y + block_height as usize).min(height) {
+                            for x in x..(x + block_width as usize).min(width) {
+                                let i = (y * width + x) * 4;
+                                for c in i..i + 4 {
+                                    squared_error +=
+                                        (original[c] as f32 - decoded[c] as f32).powi(2);
+                                    count += 1;
+                                }
+                            }
+                        }
+
+                        error_map.push(psnr(squared_error / count as f32));
+                    }
+                }
+            }
+        }
+
+        Ok((encoded, error_map))
+    }
+}
+
+impl<T: AsRef<[u8]>> Surface<T> {
+    /// Decode and encode all layers and mipmaps to the given `format`.
+    ///
+    /// Surfaces already in `format` are copied without decoding,
+    /// so the `quality` and `mipmaps` are only used when the format differs.
+    /// Formats with more than 8 bits per channel are converted using RGBAF32
+    /// to preserve precision and HDR values.
+    pub fn reencode_to(
+        &self,
+        format: ImageFormat,
+        quality: Quality,
+        mipmaps: Mipmaps,
+    ) -> Result<Surface<Vec<u8>>, SurfaceError> {
+        if self.format_matches(format) {
+            return Ok(Surface {
+                width: self.width,
+                height: self.height,
+                depth: self.depth,
+                layers: self.layers,
+                mipmaps: self.mipmaps,
+                image_format: self.image_format,
+                layout: self.layout,
+                mip_order: self.mip_order,
+                data: self.data.as_ref().to_vec(),
+            });
+        }
+
+        if self.image_format.is_high_precision() || format.is_high_precision() {
+            self.decode_rgbaf32()?.encode(format, quality, mipmaps)
+        } else {
+            self.decode_rgba8()?.encode(format, quality, mipmaps)
+        }
+    }
+}
+
+impl Surface<Vec<u8>> {
+    /// Create a 2D surface with a single mipmap filled with the color `rgba` encoded to `format`.
+    ///
+    /// Only a single block is encoded, so this is much faster than encoding a full image.
+    pub fn solid_color(
+        format: ImageFormat,
+        width: u32,
+        height: u32,
+        rgba: [u8; 4],
+        quality: Quality,
+    ) -> Result<Self, SurfaceError> {
+        if width == 0 || height == 0 {
+            return Err(SurfaceError::ZeroSizedSurface {
+                width,
+                height,
+                depth: 1,
+            });
+        }
+
+        // Every block has the same encoded data.
+        let (block_width, block_height, _) = format.block_dimensions();
+        let block = SurfaceRgba8 {
+            width: block_width,
+            height: block_height,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            data: rgba.repeat((block_width * block_height) as usize),
+        }
+        .encode(format, quality, Mipmaps::Disabled)?;
+
+        let (physical_width, physical_height, _) = format.block_align(width, height, 1);
+        let block_count =
+            (physical_width / block_width) as usize * (physical_height / block_height) as usize;
+
+        Ok(Surface {
+            width,
+            height,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format: format,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: block.data.repeat(block_count),
+        })
     }
 }
 
@@ -40,8 +301,136 @@ impl<T: AsRef<[f32]>> SurfaceRgba32Float<T> {
         quality: Quality,
         mipmaps: Mipmaps,
     ) -> Result<Surface<Vec<u8>>, SurfaceError> {
-        self.validate()?;
-        encode_surface(self, format, quality, mipmaps)
+        self.encode_with_options(format, quality, mipmaps, &EncodeOptions::default())
+    }
+
+    /// Encode an RGBAF32 surface to the given `format` with additional encoding `options`.
+    ///
+    /// See [SurfaceRgba32Float::encode] for details.
+    pub fn encode_with_options(
+        &self,
+        format: ImageFormat,
+        quality: Quality,
+        mipmaps: Mipmaps,
+        options: &EncodeOptions,
+    ) -> Result<Surface<Vec<u8>>, SurfaceError> {
+        self.validate_encode(format, options)?;
+        encode_surface(self, format, quality, mipmaps, options)
+    }
+
+    /// Encode an RGBAF32 surface like [SurfaceRgba32Float::encode_with_options]
+    /// and report the RGB values outside the range supported by `format`.
+    ///
+    /// Only BC6H formats are checked, and the values are scaled by [EncodeOptions::bc6_scale] first.
+    /// [ImageFormat::BC6hRgbUfloat] supports `0.0` to `65504.0`,
+    /// and [ImageFormat::BC6hRgbSfloat] supports `-65504.0` to `65504.0`.
+    /// Other formats always report no clamped pixels.
+    pub fn encode_with_clamp_report(
+        &self,
+        format: ImageFormat,
+        quality: Quality,
+        mipmaps: Mipmaps,
+        options: &EncodeOptions,
+    ) -> Result<(Surface<Vec<u8>>, ClampReport), SurfaceError> {
+        let encoded = self.encode_with_options(format, quality, mipmaps, options)?;
+        let report = clamp_report(self.data.as_ref(), format, options.bc6_scale());
+        Ok((encoded, report))
+    }
+}
+
+fn clamp_report(data: &[f32], format: ImageFormat, scale: f32) -> ClampReport {
+    let max = f16::MAX.to_f32();
+    let min = match format {
+        ImageFormat::BC6hRgbUfloat => 0.0,
+        ImageFormat::BC6hRgbSfloat => -max,
+        _ => return ClampReport::default(),
+    };
+
+    let mut report = ClampReport::default();
+    for pixel in data.chunks_exact(4) {
+        let rgb = pixel[..3].iter().map(|v| v * scale);
+        // NaN has no distance from the range but still can't be represented.
+        let has_nan = rgb.clone().any(f32::is_nan);
+        let overflow = rgb
+            .map(|v| (min - v).max(v - max).max(0.0))
+            .fold(0.0, f32::max);
+        if has_nan || overflow > 0.0 {
+            report.clamped_pixels += 1;
+            report.max_overflow = report.max_overflow.max(overflow);
+        }
+    }
+    report
+}
+
+/// Input values that were outside the range of the output format when encoding.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ClampReport {
+    /// The number of pixels with at least one clamped channel.
+    ///
+    /// Infinite and NaN values are always clamped.
+    pub clamped_pixels: usize,
+    /// The largest distance of any clamped value from the supported range.
+    ///
+    /// NaN values do not affect this distance.
+    pub max_overflow: f32,
+}
+
+impl<T: AsRef<[f16]>> SurfaceRgba16Float<T> {
+    /// Encode an RGBAF16 surface to the given `format`.
+    ///
+    /// BC6H formats use the half precision data directly.
+    /// Other formats convert the data to `f32` first.
+    /// The number of mipmaps generated depends on the `mipmaps` parameter.
+    pub fn encode(
+        &self,
+        format: ImageFormat,
+        quality: Quality,
+        mipmaps: Mipmaps,
+    ) -> Result<Surface<Vec<u8>>, SurfaceError> {
+        self.encode_with_options(format, quality, mipmaps, &EncodeOptions::default())
+    }
+
+    /// Encode an RGBAF16 surface to the given `format` with additional encoding `options`.
+    ///
+    /// See [SurfaceRgba16Float::encode] for details.
+    pub fn encode_with_options(
+        &self,
+        format: ImageFormat,
+        quality: Quality,
+        mipmaps: Mipmaps,
+        options: &EncodeOptions,
+    ) -> Result<Surface<Vec<u8>>, SurfaceError> {
+        self.validate_encode(format, options)?;
+        encode_surface(self, format, quality, mipmaps, options)
+    }
+}
+
+impl<T: AsRef<[u8]>> SurfaceRgba8Planar<T> {
+    /// Encode a planar RGBA8 surface to the given `format`.
+    ///
+    /// The planes are interleaved with [SurfaceRgba8Planar::to_rgba8] before encoding.
+    /// The number of mipmaps generated depends on the `mipmaps` parameter.
+    pub fn encode(
+        &self,
+        format: ImageFormat,
+        quality: Quality,
+        mipmaps: Mipmaps,
+    ) -> Result<Surface<Vec<u8>>, SurfaceError> {
+        self.encode_with_options(format, quality, mipmaps, &EncodeOptions::default())
+    }
+
+    /// Encode a planar RGBA8 surface to the given `format` with additional encoding `options`.
+    ///
+    /// See [SurfaceRgba8Planar::encode] for details.
+    pub fn encode_with_options(
+        &self,
+        format: ImageFormat,
+        quality: Quality,
+        mipmaps: Mipmaps,
+        options: &EncodeOptions,
+    ) -> Result<Surface<Vec<u8>>, SurfaceError> {
+        self.to_rgba8()?
+            .encode_with_options(format, quality, mipmaps, options)
     }
 }
 
@@ -50,92 +439,289 @@ fn encode_surface<S, P>(
     format: ImageFormat,
     quality: Quality,
     mipmaps: Mipmaps,
+    options: &EncodeOptions,
 ) -> Result<Surface<Vec<u8>>, SurfaceError>
 where
     S: GetMipmap<P>,
     P: Encode + Channel + Default,
 {
+    if quality == Quality::Lossless && !P::is_lossless(format) {
+        return Err(SurfaceError::LossyFormat { format });
+    }
+
+    // Block compressed formats require an encoder backend.
+    #[cfg(not(feature = "intel-tex"))]
+    if format.block_dimensions() != (1, 1, 1) {
+        return Err(SurfaceError::UnsupportedEncodeFormat { format });
+    }
+
     // TODO: Encode the correct number of array layers.
-    let num_mipmaps = match mipmaps {
-        Mipmaps::Disabled => 1,
-        Mipmaps::FromSurface => surface.mipmaps(),
-        Mipmaps::GeneratedExact(count) => count,
-        Mipmaps::GeneratedAutomatic => {
-            max_mipmap_count(surface.width().max(surface.height()).max(surface.depth()))
+    let max_mipmaps = max_mipmap_count(surface.width().max(surface.height()).max(surface.depth()));
+    let automatic_mipmaps = if options.skip_sub_block_mipmaps {
+        let (block_width, block_height, _) = format.block_dimensions();
+        let count = (0..max_mipmaps)
+            .take_while(|m| {
+                mip_dimension(surface.width(), *m) >= block_width
+                    && mip_dimension(surface.height(), *m) >= block_height
+            })
+            .count() as u32;
+        count.max(1)
+    } else {
+        max_mipmaps
+    };
+    let mip_range = match mipmaps {
+        Mipmaps::Disabled => 0..1,
+        Mipmaps::FromSurface => 0..surface.mipmaps(),
+        Mipmaps::GeneratedExact(count) => {
+            if count > max_mipmaps {
+                return Err(SurfaceError::UnexpectedMipmapCount {
+                    mipmaps: count,
+                    max_mipmaps,
+                });
+            }
+            0..count.max(1)
+        }
+        Mipmaps::GeneratedAutomatic => 0..automatic_mipmaps,
+        Mipmaps::GeneratedAutomaticCapped(count) => 0..automatic_mipmaps.min(count).max(1),
+        Mipmaps::GeneratedRange { start, end } => {
+            if start >= end {
+                return Err(SurfaceError::EmptyMipmapRange { start, end });
+            }
+            if end > max_mipmaps {
+                return Err(SurfaceError::UnexpectedMipmapCount {
+                    mipmaps: end,
+                    max_mipmaps,
+                });
+            }
+            start..end
         }
     };
 
     let use_surface = mipmaps == Mipmaps::FromSurface;
 
+    // Check the total size up front to avoid overflow or aborting on allocation failure.
+    let size = encoded_size(
+        (surface.width(), surface.height(), surface.depth()),
+        surface.layers(),
+        mip_range.clone(),
+        format,
+    )
+    .ok_or(SurfaceError::PixelCountWouldOverflow {
+        width: surface.width(),
+        height: surface.height(),
+        depth: surface.depth(),
+    })?;
+
     // TODO: Does this work if the base mip level is smaller than 4x4?
     let mut surface_data = Vec::new();
+    surface_data
+        .try_reserve_exact(size)
+        .map_err(|_| SurfaceError::OutputTooLarge { size })?;
 
     for layer in 0..surface.layers() {
         // Encode 2D or 3D data for this layer.
-        encode_mipmaps_rgba(
-            &mut surface_data,
+        let mut layer_mipmaps = encode_mipmaps_rgba(
             surface,
             format,
             quality,
-            num_mipmaps,
+            options,
+            mip_range.clone(),
             use_surface,
             layer,
         )?;
+        if options.mip_order == MipOrder::SmallestFirst {
+            layer_mipmaps.reverse();
+        }
+        for mip_data in layer_mipmaps {
+            surface_data.extend_from_slice(&mip_data);
+        }
     }
 
     Ok(Surface {
-        width: surface.width(),
-        height: surface.height(),
-        depth: surface.depth(),
+        width: mip_dimension(surface.width(), mip_range.start),
+        height: mip_dimension(surface.height(), mip_range.start),
+        depth: mip_dimension(surface.depth(), mip_range.start),
         layers: surface.layers(),
-        mipmaps: num_mipmaps,
+        mipmaps: mip_range.len() as u32,
         image_format: format,
+        layout: DataLayout::Tight,
+        mip_order: options.mip_order,
         data: surface_data,
     })
 }
 
+// Copy each mipmap of each layer in order from largest to smallest.
+fn split_levels(surface: &Surface<Vec<u8>>) -> Result<Vec<Vec<u8>>, SurfaceError> {
+    let dimensions = (surface.width, surface.height, surface.depth);
+    let block_dimensions = surface.image_format.block_dimensions();
+    let block_size = surface.image_format.block_size_in_bytes();
+
+    let mut levels = Vec::with_capacity(surface.layers as usize * surface.mipmaps as usize);
+    for layer in 0..surface.layers {
+        for mipmap in 0..surface.mipmaps {
+            let offset = calculate_offset(
+                layer,
+                0,
+                mipmap,
+                dimensions,
+                block_dimensions,
+                block_size,
+                surface.mipmaps,
+                surface.layout,
+                surface.mip_order,
+            );
+            let size = layout_mip_size(
+                surface.layout,
+                (
+                    mip_dimension(surface.width, mipmap) as usize,
+                    mip_dimension(surface.height, mipmap) as usize,
+                    mip_dimension(surface.depth, mipmap) as usize,
+                ),
+                block_dimensions,
+                block_size,
+            );
+            let data = offset
+                .zip(size)
+                .and_then(|(offset, size)| surface.data.get(offset..offset.checked_add(size)?))
+                .ok_or(SurfaceError::MipmapDataOutOfBounds { layer, mipmap })?;
+            levels.push(data.to_vec());
+        }
+    }
+    Ok(levels)
+}
+
+/// The size in bytes of the encoded `mipmaps` for all layers
+/// or `None` if the size would overflow.
+fn encoded_size(
+    dimensions: (u32, u32, u32),
+    layers: u32,
+    mipmaps: Range<u32>,
+    format: ImageFormat,
+) -> Option<usize> {
+    let (width, height, depth) = dimensions;
+    let mip_size = |mipmap| {
+        layout_mip_size(
+            DataLayout::Tight,
+            (
+                mip_dimension(width, mipmap) as usize,
+                mip_dimension(height, mipmap) as usize,
+                mip_dimension(depth, mipmap) as usize,
+            ),
+            format.block_dimensions(),
+            format.block_size_in_bytes(),
+        )
+    };
+
+    // Mipmaps past the last 1x1x1 mipmap all have the same size.
+    let max_mipmaps = max_mipmap_count(width.max(height).max(depth));
+    let extra_mipmaps = mipmaps.end.saturating_sub(mipmaps.start.max(max_mipmaps));
+    let layer_size = (mipmaps.start..mipmaps.end.min(max_mipmaps))
+        .try_fold(0usize, |size, mipmap| size.checked_add(mip_size(mipmap)?))?
+        .checked_add(mip_size(max_mipmaps)?.checked_mul(extra_mipmaps as usize)?)?;
+    layer_size.checked_mul(layers as usize)
+}
+
 // TODO: Find a way to simplify this.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        skip_all,
+        fields(
+            ?format,
+            width = surface.width(),
+            height = surface.height(),
+            depth = surface.depth(),
+            layer,
+            ?mipmaps
+        )
+    )
+)]
 fn encode_mipmaps_rgba<S, P>(
-    surface_data: &mut Vec<u8>,
     surface: &S,
     format: ImageFormat,
     quality: Quality,
-    num_mipmaps: u32,
+    options: &EncodeOptions,
+    mipmaps: Range<u32>,
     use_surface: bool,
     layer: u32,
-) -> Result<(), SurfaceError>
+) -> Result<Vec<Vec<u8>>, SurfaceError>
 where
     S: GetMipmap<P>,
     P: Default + Encode + Channel,
 {
-    let block_dimensions = format.block_dimensions();
-
     // Track the previous image data and dimensions.
     // This enables generating mipmaps from a single base layer.
-    let mut mip_data = get_mipmap_data(surface, layer, 0, block_dimensions)?;
-
-    let encoded = mip_data.encode(format, quality)?;
-    surface_data.extend_from_slice(&encoded);
+    let mut mip_data = get_mipmap_data(surface, layer, 0, format, options)?;
+
+    // Each level may depend on the previous level, so find the data for all levels first.
+    // Levels before the start of the range are still needed for downsampling.
+    let mut levels = Vec::new();
+    for mipmap in 1..mipmaps.end {
+        let surface_data = if use_surface {
+            match get_mipmap_data(surface, layer, mipmap, format, options) {
+                Err(SurfaceError::MipmapDataOutOfBounds { .. })
+                    if options.generate_missing_mipmaps =>
+                {
+                    None
+                }
+                result => Some(result?),
+            }
+        } else {
+            None
+        };
 
-    for mipmap in 1..num_mipmaps {
-        mip_data = if use_surface {
-            // TODO: Error if surface does not have the appropriate number of mipmaps?
-            get_mipmap_data(surface, layer, mipmap, block_dimensions)?
+        let next_data = if let Some(data) = surface_data {
+            data
         } else {
             mip_data.downsample(
                 surface.width(),
                 surface.height(),
                 surface.depth(),
-                block_dimensions,
+                format,
                 mipmap,
+                options,
             )
         };
 
-        let encoded = mip_data.encode(format, quality)?;
-        surface_data.extend_from_slice(&encoded);
+        let previous_data = std::mem::replace(&mut mip_data, next_data);
+        if mipmaps.contains(&(mipmap - 1)) {
+            levels.push(previous_data);
+        }
     }
+    if mipmaps.contains(&mipmaps.end.saturating_sub(1)) {
+        levels.push(mip_data);
+    }
+
+    encode_mip_levels(&levels, format, quality, options)
+}
+
+#[cfg(feature = "rayon")]
+fn encode_mip_levels<P: Encode>(
+    levels: &[MipData<P>],
+    format: ImageFormat,
+    quality: Quality,
+    options: &EncodeOptions,
+) -> Result<Vec<Vec<u8>>, SurfaceError> {
+    use rayon::prelude::*;
+
+    // The levels no longer depend on each other once downsampled.
+    levels
+        .par_iter()
+        .map(|level| level.encode(format, quality, options))
+        .collect()
+}
 
-    Ok(())
+#[cfg(not(feature = "rayon"))]
+fn encode_mip_levels<P: Encode>(
+    levels: &[MipData<P>],
+    format: ImageFormat,
+    quality: Quality,
+    options: &EncodeOptions,
+) -> Result<Vec<Vec<u8>>, SurfaceError> {
+    levels
+        .iter()
+        .map(|level| level.encode(format, quality, options))
+        .collect()
 }
 
 struct MipData<T> {
@@ -151,8 +737,9 @@ impl<T: Channel> MipData<T> {
         base_width: u32,
         base_height: u32,
         base_depth: u32,
-        block_dimensions: (u32, u32, u32),
+        format: ImageFormat,
         mipmap: u32,
+        options: &EncodeOptions,
     ) -> MipData<T> {
         // Mip dimensions are the padded virtual size of the mipmap.
         // Padding the physical size of the previous mip produces incorrect results.
@@ -160,20 +747,43 @@ impl<T: Channel> MipData<T> {
             mip_dimension(base_width, mipmap),
             mip_dimension(base_height, mipmap),
             mip_dimension(base_depth, mipmap),
-            block_dimensions,
+            format.block_dimensions(),
         );
 
-        // Assume the data is already padded.
-        let data = downsample_rgba(
-            width,
-            height,
-            depth,
+        // Sample only the previous mip and not its padding.
+        // Padding is zero and would darken the edges, especially with wrapping.
+        let previous = (
+            mip_dimension(base_width, mipmap - 1) as usize,
+            mip_dimension(base_height, mipmap - 1) as usize,
+            mip_dimension(base_depth, mipmap - 1) as usize,
+        );
+        let previous_data = unpad_mipmap_rgba(
             self.width,
             self.height,
             self.depth,
+            previous.0,
+            previous.1,
+            previous.2,
             &self.data,
         );
 
+        let downsample = match (options.mipmap_filter, options.alpha_weighted_mipmaps) {
+            (MipmapFilter::Box, false) => downsample_rgba,
+            (MipmapFilter::Box, true) => downsample_rgba_alpha_weighted,
+            (MipmapFilter::Triangle, false) => downsample_rgba_triangle,
+            (MipmapFilter::Triangle, true) => downsample_rgba_triangle_alpha_weighted,
+        };
+        let data = downsample(
+            width,
+            height,
+            depth,
+            previous.0,
+            previous.1,
+            previous.2,
+            &previous_data,
+            options.mipmap_address_mode,
+        );
+
         MipData {
             width,
             height,
@@ -187,13 +797,41 @@ impl<T> MipData<T>
 where
     T: Encode,
 {
-    fn encode(&self, format: ImageFormat, quality: Quality) -> Result<Vec<u8>, SurfaceError> {
+    fn encode(
+        &self,
+        format: ImageFormat,
+        quality: Quality,
+        options: &EncodeOptions,
+    ) -> Result<Vec<u8>, SurfaceError> {
+        // Padding should make every mipmap at least one whole block.
+        // Check anyway since empty or partial blocks would otherwise fail deep in the encoder.
+        let (width, height, depth) = (self.width as u32, self.height as u32, self.depth as u32);
+        if width == 0 || height == 0 || depth == 0 {
+            return Err(SurfaceError::ZeroSizedSurface {
+                width,
+                height,
+                depth,
+            });
+        }
+        let (block_width, block_height, block_depth) = format.block_dimensions();
+        if width % block_width != 0 || height % block_height != 0 || depth % block_depth != 0 {
+            return Err(SurfaceError::NonIntegralDimensionsInBlocks {
+                width,
+                height,
+                depth,
+                block_width,
+                block_height,
+            });
+        }
+
         T::encode(
-            self.width as u32,
-            self.height as u32 * self.depth as u32,
+            width,
+            height * depth,
             &self.data,
+            width as usize * 4,
             format,
             quality,
+            options,
         )
     }
 }
@@ -204,7 +842,7 @@ trait GetMipmap<P> {
     fn depth(&self) -> u32;
     fn layers(&self) -> u32;
     fn mipmaps(&self) -> u32;
-    fn get(&self, layer: u32, depth_level: u32, mipmap: u32) -> Option<&[P]>;
+    fn data(&self) -> &[P];
 }
 
 impl<T> GetMipmap<u8> for SurfaceRgba8<T>
@@ -231,8 +869,8 @@ where
         self.mipmaps
     }
 
-    fn get(&self, layer: u32, depth_level: u32, mipmap: u32) -> Option<&[u8]> {
-        self.get(layer, depth_level, mipmap)
+    fn data(&self) -> &[u8] {
+        self.data.as_ref()
     }
 }
 
@@ -260,8 +898,37 @@ where
         self.mipmaps
     }
 
-    fn get(&self, layer: u32, depth_level: u32, mipmap: u32) -> Option<&[f32]> {
-        self.get(layer, depth_level, mipmap)
+    fn data(&self) -> &[f32] {
+        self.data.as_ref()
+    }
+}
+
+impl<T> GetMipmap<f16> for SurfaceRgba16Float<T>
+where
+    T: AsRef<[f16]>,
+{
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    fn layers(&self) -> u32 {
+        self.layers
+    }
+
+    fn mipmaps(&self) -> u32 {
+        self.mipmaps
+    }
+
+    fn data(&self) -> &[f16] {
+        self.data.as_ref()
     }
 }
 
@@ -269,26 +936,56 @@ fn get_mipmap_data<S, P>(
     surface: &S,
     layer: u32,
     mipmap: u32,
-    block_dimensions: (u32, u32, u32),
+    format: ImageFormat,
+    options: &EncodeOptions,
 ) -> Result<MipData<P>, SurfaceError>
 where
     S: GetMipmap<P>,
     P: Default + Copy,
 {
+    let block_dimensions = input_block_dimensions(format, options);
+
     let mip_width = mip_dimension(surface.width(), mipmap);
     let mip_height = mip_dimension(surface.height(), mipmap);
     let mip_depth = mip_dimension(surface.depth(), mipmap);
 
+    let dimensions = (surface.width(), surface.height(), surface.depth());
+
+    // Missing mipmaps are omitted from every layer, so offsets use the shorter mipmap chain.
+    let mipmaps = if options.generate_missing_mipmaps {
+        stored_rgba_mipmap_count(
+            dimensions,
+            block_dimensions,
+            surface.layers(),
+            surface.mipmaps(),
+            surface.data().len(),
+        )
+    } else {
+        surface.mipmaps()
+    };
+    if mipmap >= mipmaps {
+        return Err(SurfaceError::MipmapDataOutOfBounds { layer, mipmap });
+    }
+
     // TODO: This should be for all depth levels.
     // TODO: This can be optimized to avoid copies?
     let mut data = Vec::new();
     for level in 0..surface.depth() {
-        let new_data = surface.get(layer, level, mipmap).unwrap();
-        data.extend_from_slice(new_data);
+        let new_data = get_rgba_mipmap(
+            surface.data(),
+            dimensions,
+            block_dimensions,
+            mipmaps,
+            layer,
+            level,
+            mipmap,
+        )
+        .ok_or(SurfaceError::MipmapDataOutOfBounds { layer, mipmap })?;
+        data.extend_from_slice(new_data);
     }
 
     let (width, height, depth) =
-        physical_dimensions(mip_width, mip_height, mip_depth, block_dimensions);
+        physical_dimensions(mip_width, mip_height, mip_depth, format.block_dimensions());
 
     let data = pad_mipmap_rgba(
         mip_width as usize,
@@ -320,11 +1017,15 @@ fn physical_dimensions(
     // For example, a 1x1 BCN block still requires 4x4 pixels of data.
     // https://learn.microsoft.com/en-us/windows/win32/direct3d10/d3d10-graphics-programming-guide-resources-block-compression
     let (block_width, block_height, block_depth) = block_dimensions;
-    (
-        width.next_multiple_of(block_width) as usize,
-        height.next_multiple_of(block_height) as usize,
-        depth.next_multiple_of(block_depth) as usize,
+    block_align_dimensions(
+        (width as usize, height as usize, depth as usize),
+        (
+            block_width as usize,
+            block_height as usize,
+            block_depth as usize,
+        ),
     )
+    .expect("padded dimensions should not overflow")
 }
 
 fn pad_mipmap_rgba<T>(
@@ -362,15 +1063,171 @@ where
     }
 }
 
+// Remove the padding added by pad_mipmap_rgba.
+fn unpad_mipmap_rgba<T>(
+    width: usize,
+    height: usize,
+    depth: usize,
+    new_width: usize,
+    new_height: usize,
+    new_depth: usize,
+    data: &[T],
+) -> Cow<'_, [T]>
+where
+    T: Copy,
+{
+    let channels = 4;
+    if (width, height, depth) == (new_width, new_height, new_depth) {
+        return Cow::Borrowed(data);
+    }
+
+    let mut unpadded_data = Vec::with_capacity(new_width * new_height * new_depth * channels);
+    for z in 0..new_depth {
+        for y in 0..new_height {
+            let in_base = ((z * width * height) + y * width) * channels;
+            unpadded_data.extend_from_slice(&data[in_base..in_base + new_width * channels]);
+        }
+    }
+    Cow::Owned(unpadded_data)
+}
+
+fn single_channel_rgba<'a, T: Copy>(
+    data: &'a [T],
+    format: ImageFormat,
+    options: &EncodeOptions,
+    luminance: impl Fn(T, T, T) -> T,
+    invert: impl Fn(T) -> T,
+) -> Cow<'a, [T]> {
+    // Single channel formats only read the red channel.
+    let source = options.single_channel_source;
+    if !format.is_single_channel()
+        || (source == SingleChannelSource::Red && !options.invert_single_channel)
+    {
+        return Cow::Borrowed(data);
+    }
+
+    Cow::Owned(
+        data.chunks_exact(4)
+            .flat_map(|p| {
+                let value = match source {
+                    SingleChannelSource::Red => p[0],
+                    SingleChannelSource::Green => p[1],
+                    SingleChannelSource::Blue => p[2],
+                    SingleChannelSource::Luminance => luminance(p[0], p[1], p[2]),
+                    SingleChannelSource::Alpha => p[3],
+                };
+                let value = if options.invert_single_channel {
+                    invert(value)
+                } else {
+                    value
+                };
+                [value, p[1], p[2], p[3]]
+            })
+            .collect(),
+    )
+}
+
+// Only block compressed formats read padded rows directly.
+fn tight_rows<T: Copy>(
+    data: &[T],
+    width: u32,
+    height: u32,
+    stride: usize,
+    format: ImageFormat,
+) -> (Cow<'_, [T]>, usize) {
+    let row_size = width as usize * 4;
+    if stride == row_size || format.block_dimensions() != (1, 1, 1) {
+        (Cow::Borrowed(data), stride)
+    } else {
+        let rows = data.chunks(stride).take(height as usize);
+        let data = rows.flat_map(|row| &row[..row_size.min(row.len())]);
+        (Cow::Owned(data.copied().collect()), row_size)
+    }
+}
+
+// Vectors shorter than this are treated as zero vectors with rounding error.
+const MIN_NORMAL_LENGTH: f32 = 1.0 / 32.0;
+
+fn normal_map_rgba<T: Copy>(
+    data: &[T],
+    format: ImageFormat,
+    hint: ContentHint,
+    to_f32: impl Fn(T) -> f32,
+    from_f32: impl Fn(f32) -> T,
+) -> Cow<'_, [T]> {
+    // Only block compression can denormalize vectors.
+    if hint == ContentHint::Color || format.block_dimensions() == (1, 1, 1) {
+        return Cow::Borrowed(data);
+    }
+
+    Cow::Owned(
+        data.chunks_exact(4)
+            .flat_map(|p| {
+                // Map from 0.0 to 1.0 to -1.0 to 1.0 and back after normalizing.
+                let xyz = [p[0], p[1], p[2]].map(|c| to_f32(c) * 2.0 - 1.0);
+                let length = xyz.iter().map(|c| c * c).sum::<f32>().sqrt();
+                if length >= MIN_NORMAL_LENGTH {
+                    let [x, y, z] = xyz.map(|c| from_f32((c / length + 1.0) / 2.0));
+                    [x, y, z, p[3]]
+                } else {
+                    [p[0], p[1], p[2], p[3]]
+                }
+            })
+            .collect(),
+    )
+}
+
+fn luminance(r: f32, g: f32, b: f32) -> f32 {
+    0.299 * r + 0.587 * g + 0.114 * b
+}
+
+// The error uses the requested format since each encoder handles several formats.
+#[cfg(feature = "intel-tex")]
+fn encode_blocks<F, T>(
+    width: u32,
+    height: u32,
+    data: &[T],
+    stride: usize,
+    _format: ImageFormat,
+    quality: Quality,
+    options: &EncodeOptions,
+) -> Result<Vec<u8>, SurfaceError>
+where
+    F: BcnEncode<T>,
+    T: Sync,
+{
+    encode_bcn::<F, T>(width, height, data, stride, quality, options)
+}
+
+#[cfg(not(feature = "intel-tex"))]
+#[allow(clippy::extra_unused_type_parameters)]
+fn encode_blocks<F, T>(
+    _width: u32,
+    _height: u32,
+    _data: &[T],
+    _stride: usize,
+    format: ImageFormat,
+    _quality: Quality,
+    _options: &EncodeOptions,
+) -> Result<Vec<u8>, SurfaceError> {
+    Err(SurfaceError::UnsupportedEncodeFormat { format })
+}
+
 // Encoding only works on 2D surfaces.
-trait Encode: Sized {
+trait Encode: Sized + Sync {
+    /// Encode `data` with `stride` elements between the start of each row.
     fn encode(
         width: u32,
         height: u32,
         data: &[Self],
+        stride: usize,
         format: ImageFormat,
         quality: Quality,
+        options: &EncodeOptions,
     ) -> Result<Vec<u8>, SurfaceError>;
+
+    /// Returns `true` if `format` stores all channels of `Self` without any loss of precision.
+    fn is_lossless(format: ImageFormat) -> bool;
 }
 
 impl Encode for u8 {
@@ -378,29 +1235,60 @@ impl Encode for u8 {
         width: u32,
         height: u32,
         data: &[Self],
+        stride: usize,
         format: ImageFormat,
         quality: Quality,
+        options: &EncodeOptions,
     ) -> Result<Vec<u8>, SurfaceError> {
+        let data = &normal_map_rgba(
+            data,
+            format,
+            options.content_hint,
+            |c| c as f32 / 255.0,
+            |c| (c * 255.0).round() as u8,
+        );
+        let data = &single_channel_rgba(
+            data,
+            format,
+            options,
+            |r, g, b| luminance(r as f32, g as f32, b as f32).round() as u8,
+            |v| 255 - v,
+        );
+        let (data, stride) = tight_rows(data, width, height, stride, format);
+        let data = &data;
+
         // Unorm and srgb only affect how the data is read.
         // Use the same conversion code for both.
         use ImageFormat as F;
         match format {
-            F::BC1RgbaUnorm | F::BC1RgbaUnormSrgb => {
-                encode_bcn::<Bc1, u8>(width, height, data, quality)
+            F::BC1RgbaUnorm | F::BC1RgbaUnormSrgb | F::BC1RgbUnorm | F::BC1RgbUnormSrgb => {
+                encode_blocks::<Bc1, u8>(width, height, data, stride, format, quality, options)
             }
             F::BC2RgbaUnorm | F::BC2RgbaUnormSrgb => {
-                encode_bcn::<Bc2, u8>(width, height, data, quality)
+                encode_blocks::<Bc2, u8>(width, height, data, stride, format, quality, options)
             }
             F::BC3RgbaUnorm | F::BC3RgbaUnormSrgb => {
-                encode_bcn::<Bc3, u8>(width, height, data, quality)
+                encode_blocks::<Bc3, u8>(width, height, data, stride, format, quality, options)
+            }
+            F::BC4RUnorm => {
+                encode_blocks::<Bc4, u8>(width, height, data, stride, format, quality, options)
+            }
+            F::BC4RSnorm => {
+                // Endpoints are compared as signed values, so keep the encoder's choice.
+                let options = &EncodeOptions {
+                    alpha_block_mode: AlphaBlockMode::Automatic,
+                    ..options.clone()
+                };
+                encode_blocks::<Bc4, u8>(width, height, data, stride, format, quality, options)
+            }
+            F::BC5RgUnorm | F::BC5RgSnorm => {
+                encode_blocks::<Bc5, u8>(width, height, data, stride, format, quality, options)
             }
-            F::BC4RUnorm | F::BC4RSnorm => encode_bcn::<Bc4, u8>(width, height, data, quality),
-            F::BC5RgUnorm | F::BC5RgSnorm => encode_bcn::<Bc5, u8>(width, height, data, quality),
             F::BC6hRgbUfloat | F::BC6hRgbSfloat => {
-                encode_bcn::<Bc6, u8>(width, height, data, quality)
+                encode_blocks::<Bc6, u8>(width, height, data, stride, format, quality, options)
             }
             F::BC7RgbaUnorm | F::BC7RgbaUnormSrgb => {
-                encode_bcn::<Bc7, u8>(width, height, data, quality)
+                encode_blocks::<Bc7, u8>(width, height, data, stride, format, quality, options)
             }
             F::R8Unorm => encode_rgba::<R8, u8>(width, height, data),
             F::R8Snorm => encode_rgba::<R8Snorm, u8>(width, height, data),
@@ -411,6 +1299,7 @@ impl Encode for u8 {
             F::Bgra8Unorm | F::Bgra8UnormSrgb => encode_rgba::<Bgra8, u8>(width, height, data),
             F::Bgra4Unorm => encode_rgba::<Bgra4, u8>(width, height, data),
             F::Bgr8Unorm => encode_rgba::<Bgr8, u8>(width, height, data),
+            F::Rgb8Unorm => encode_rgba::<Rgb8, u8>(width, height, data),
             F::R16Unorm => encode_rgba::<R16, u8>(width, height, data),
             F::R16Snorm => encode_rgba::<R16Snorm, u8>(width, height, data),
             F::Rg16Unorm => encode_rgba::<Rg16, u8>(width, height, data),
@@ -425,8 +1314,17 @@ impl Encode for u8 {
             F::Rgb32Float => encode_rgba::<Rgbf32, u8>(width, height, data),
             F::Rgba32Float => encode_rgba::<Rgbaf32, u8>(width, height, data),
             F::Bgr5A1Unorm => encode_rgba::<Bgr5A1, u8>(width, height, data),
+            F::Rgb9e5Ufloat => encode_rgba::<Rgb9e5, u8>(width, height, data),
+            F::Rg11b10Float => encode_rgba::<Rg11b10, u8>(width, height, data),
+            F::EacR11Unorm | F::EacRg11Unorm => {
+                Err(SurfaceError::UnsupportedEncodeFormat { format })
+            }
         }
     }
+
+    fn is_lossless(format: ImageFormat) -> bool {
+        format.is_lossless_rgba8()
+    }
 }
 
 impl Encode for f32 {
@@ -434,9 +1332,23 @@ impl Encode for f32 {
         width: u32,
         height: u32,
         data: &[Self],
+        stride: usize,
         format: ImageFormat,
         quality: Quality,
+        options: &EncodeOptions,
     ) -> Result<Vec<u8>, SurfaceError> {
+        let data = &normal_map_rgba(data, format, options.content_hint, |c| c, |c| c);
+        let data = &single_channel_rgba(data, format, options, luminance, |v| 1.0 - v);
+        let (data, stride) = tight_rows(data, width, height, stride, format);
+        let data = &data;
+
+        // The channel is already selected, so the u8 encoder can use red.
+        let options = &EncodeOptions {
+            single_channel_source: SingleChannelSource::Red,
+            invert_single_channel: false,
+            ..options.clone()
+        };
+
         // Unorm and srgb only affect how the data is read.
         // Use the same conversion code for both.
         use ImageFormat as F;
@@ -447,10 +1359,10 @@ impl Encode for f32 {
             F::BC4RSnorm | F::BC5RgSnorm => {
                 // intel_tex doesn't have a dedicated encoder for snorm formats.
                 let rgba8: Vec<_> = data.iter().map(|f| float_to_snorm8(*f) as u8).collect();
-                u8::encode(width, height, &rgba8, format, quality)
+                u8::encode(width, height, &rgba8, stride, format, quality, options)
             }
             F::BC6hRgbUfloat | F::BC6hRgbSfloat => {
-                encode_bcn::<Bc6, f32>(width, height, data, quality)
+                encode_blocks::<Bc6, f32>(width, height, data, stride, format, quality, options)
             }
             F::R16Float => encode_rgba::<Rf16, f32>(width, height, data),
             F::Rg16Float => encode_rgba::<Rgf16, f32>(width, height, data),
@@ -458,6 +1370,8 @@ impl Encode for f32 {
             F::R32Float => encode_rgba::<Rf32, f32>(width, height, data),
             F::Rg32Float => encode_rgba::<Rgf32, f32>(width, height, data),
             F::Rgba32Float => encode_rgba::<Rgbaf32, f32>(width, height, data),
+            F::Rgb9e5Ufloat => encode_rgba::<Rgb9e5, f32>(width, height, data),
+            F::Rg11b10Float => encode_rgba::<Rg11b10, f32>(width, height, data),
             F::R16Unorm => encode_rgba::<R16, f32>(width, height, data),
             F::Rg16Unorm => encode_rgba::<Rg16, f32>(width, height, data),
             F::Rgba16Unorm => encode_rgba::<Rgba16, f32>(width, height, data),
@@ -466,10 +1380,40 @@ impl Encode for f32 {
             F::Rgba16Snorm => encode_rgba::<Rgba16Snorm, f32>(width, height, data),
             _ => {
                 let rgba8: Vec<_> = data.iter().map(|f| (f * 255.0) as u8).collect();
-                u8::encode(width, height, &rgba8, format, quality)
+                u8::encode(width, height, &rgba8, stride, format, quality, options)
+            }
+        }
+    }
+
+    fn is_lossless(format: ImageFormat) -> bool {
+        format == ImageFormat::Rgba32Float
+    }
+}
+
+impl Encode for f16 {
+    fn encode(
+        width: u32,
+        height: u32,
+        data: &[Self],
+        stride: usize,
+        format: ImageFormat,
+        quality: Quality,
+        options: &EncodeOptions,
+    ) -> Result<Vec<u8>, SurfaceError> {
+        match format {
+            ImageFormat::BC6hRgbUfloat | ImageFormat::BC6hRgbSfloat => {
+                encode_blocks::<Bc6, f16>(width, height, data, stride, format, quality, options)
+            }
+            _ => {
+                let rgbaf32: Vec<_> = data.iter().map(|h| h.to_f32()).collect();
+                f32::encode(width, height, &rgbaf32, stride, format, quality, options)
             }
         }
     }
+
+    fn is_lossless(format: ImageFormat) -> bool {
+        matches!(format, ImageFormat::Rgba16Float | ImageFormat::Rgba32Float)
+    }
 }
 
 #[cfg(test)]
@@ -479,6 +1423,202 @@ mod tests {
     use strum::IntoEnumIterator;
 
     #[test]
+    fn encode_surface_lossless() {
+        let surface = SurfaceRgba8 {
+            width: 4,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            data: &[1u8; 4 * 4 * 4][..],
+        };
+        let encoded = surface
+            .encode(
+                ImageFormat::Rgba8Unorm,
+                Quality::Lossless,
+                Mipmaps::Disabled,
+            )
+            .unwrap();
+        assert_eq!(surface.data, encoded.data);
+
+        assert_eq!(
+            Err(SurfaceError::LossyFormat {
+                format: ImageFormat::BC7RgbaUnorm
+            }),
+            surface.encode(
+                ImageFormat::BC7RgbaUnorm,
+                Quality::Lossless,
+                Mipmaps::Disabled
+            )
+        );
+    }
+
+    #[test]
+    fn encode_surface_recommended_quality_rgbaf32() {
+        let surface = SurfaceRgba32Float {
+            width: 4,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            data: vec![0.5f32; 4 * 4 * 4],
+        };
+        for format in ImageFormat::iter() {
+            let result = surface.encode(
+                format,
+                format.recommended_quality_rgbaf32(),
+                Mipmaps::Disabled,
+            );
+            // Some formats can't be encoded with any quality.
+            assert!(
+                matches!(
+                    result,
+                    Ok(_) | Err(SurfaceError::UnsupportedEncodeFormat { .. })
+                ),
+                "{format:?}: {result:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn encode_surface_lossless_round_trip() {
+        let surface = SurfaceRgba8 {
+            width: 8,
+            height: 8,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            data: (0..=255u8).collect::<Vec<_>>(),
+        };
+        for format in ImageFormat::iter().filter(|f| f.is_lossless_rgba8()) {
+            let encoded = surface
+                .encode(format, Quality::Lossless, Mipmaps::Disabled)
+                .unwrap();
+            assert_eq!(
+                surface.data,
+                encoded.decode_rgba8().unwrap().data,
+                "{format:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn encode_surface_lossless_lossy_uncompressed() {
+        let surface = SurfaceRgba8 {
+            width: 1,
+            height: 1,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            data: &[1u8, 2, 3, 4][..],
+        };
+        for format in [
+            ImageFormat::R8Unorm,
+            ImageFormat::Bgra4Unorm,
+            ImageFormat::Bgr5A1Unorm,
+            ImageFormat::Rgb9e5Ufloat,
+            ImageFormat::Rg11b10Float,
+        ] {
+            assert_eq!(
+                Err(SurfaceError::LossyFormat { format }),
+                surface.encode(format, Quality::Lossless, Mipmaps::Disabled)
+            );
+        }
+
+        // Float data requires at least the same precision.
+        let surface = SurfaceRgba32Float {
+            width: 1,
+            height: 1,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            data: &[0.1f32, 0.2, 0.3, 0.4][..],
+        };
+        assert_eq!(
+            Err(SurfaceError::LossyFormat {
+                format: ImageFormat::Rgba16Float
+            }),
+            surface.encode(
+                ImageFormat::Rgba16Float,
+                Quality::Lossless,
+                Mipmaps::Disabled
+            )
+        );
+        assert!(surface
+            .encode(
+                ImageFormat::Rgba32Float,
+                Quality::Lossless,
+                Mipmaps::Disabled
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn encode_surface_rgb8() {
+        let surface = SurfaceRgba8 {
+            width: 2,
+            height: 1,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            data: &[1u8, 2, 3, 4, 5, 6, 7, 8][..],
+        };
+        let encoded = surface
+            .encode(ImageFormat::Rgb8Unorm, Quality::Fast, Mipmaps::Disabled)
+            .unwrap();
+        assert_eq!(vec![1, 2, 3, 5, 6, 7], encoded.data);
+        assert_eq!(
+            vec![1, 2, 3, 255, 5, 6, 7, 255],
+            encoded.decode_rgba8().unwrap().data
+        );
+    }
+
+    #[test]
+    fn encode_surface_volume_single_slice() {
+        // The data must contain all depth slices.
+        let surface = SurfaceRgba8 {
+            width: 4,
+            height: 4,
+            depth: 4,
+            layers: 1,
+            mipmaps: 1,
+            data: &[0u8; 4 * 4 * 4][..],
+        };
+        assert_eq!(
+            Err(SurfaceError::NotEnoughData {
+                expected: 4 * 4 * 4 * 4,
+                actual: 4 * 4 * 4
+            }),
+            surface.encode(ImageFormat::Rgba8Unorm, Quality::Fast, Mipmaps::Disabled)
+        );
+    }
+
+    #[test]
+    fn encode_surface_missing_layers_and_mipmaps() {
+        // 2 layers with 4x4 and 2x2 mipmaps but data for only the first layer.
+        let surface = SurfaceRgba32Float {
+            width: 4,
+            height: 4,
+            depth: 1,
+            layers: 2,
+            mipmaps: 2,
+            data: &[0.0; (16 + 4) * 4][..],
+        };
+        assert_eq!(
+            Err(SurfaceError::NotEnoughData {
+                expected: (16 + 4) * 4 * 2,
+                actual: (16 + 4) * 4
+            }),
+            surface.encode(
+                ImageFormat::Rgba32Float,
+                Quality::Fast,
+                Mipmaps::FromSurface
+            )
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "intel-tex")]
     fn encode_surface_integral_dimensions() {
         // It's ok for mipmaps to not be divisible by the block width.
         let surface = SurfaceRgba8 {
@@ -507,6 +1647,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "intel-tex")]
     fn encode_surface_cube_mipmaps() {
         // It's ok for mipmaps to not be divisible by the block width.
         let surface = SurfaceRgba8 {
@@ -535,6 +1676,191 @@ mod tests {
     }
 
     #[test]
+    fn encode_surface_cube_mipmaps_face_offsets() {
+        // Use a distinct color for each face to check the layer layout.
+        let colors = [
+            [255, 0, 0, 255],
+            [0, 255, 0, 255],
+            [0, 0, 255, 255],
+            [255, 255, 0, 255],
+            [255, 0, 255, 255],
+            [0, 255, 255, 255],
+        ];
+        let data: Vec<u8> = colors.iter().flat_map(|c| c.repeat(4 * 4)).collect();
+
+        let surface = SurfaceRgba8 {
+            width: 4,
+            height: 4,
+            depth: 1,
+            layers: 6,
+            mipmaps: 1,
+            data: &data[..],
+        }
+        .encode(
+            ImageFormat::Bgra8Unorm,
+            Quality::Fast,
+            Mipmaps::GeneratedAutomatic,
+        )
+        .unwrap();
+        assert_eq!(3, surface.mipmaps);
+
+        for (face, [r, g, b, a]) in colors.into_iter().enumerate() {
+            for mipmap in 0..3 {
+                let pixels = (4 >> mipmap) * (4 >> mipmap);
+                assert_eq!(
+                    Some([b, g, r, a].repeat(pixels).as_slice()),
+                    surface.get_cube_face(0, face as u32, mipmap),
+                    "face {face}, mipmap {mipmap}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "intel-tex")]
+    fn encode_surface_skip_sub_block_mipmaps() {
+        let surface = SurfaceRgba8 {
+            width: 16,
+            height: 8,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            data: &[0u8; 16 * 8 * 4][..],
+        };
+        let options = EncodeOptions {
+            skip_sub_block_mipmaps: true,
+            ..Default::default()
+        };
+
+        // 16x8 and 8x4 but not 4x2, 2x1, or 1x1.
+        let encoded = surface
+            .encode_with_options(
+                ImageFormat::BC7RgbaUnorm,
+                Quality::Fast,
+                Mipmaps::GeneratedAutomatic,
+                &options,
+            )
+            .unwrap();
+        assert_eq!(2, encoded.mipmaps);
+        assert_eq!((8 + 2) * 16, encoded.data.len());
+
+        // Uncompressed formats use all mipmaps.
+        let encoded = surface
+            .encode_with_options(
+                ImageFormat::Rgba8Unorm,
+                Quality::Fast,
+                Mipmaps::GeneratedAutomatic,
+                &options,
+            )
+            .unwrap();
+        assert_eq!(5, encoded.mipmaps);
+    }
+
+    #[test]
+    fn encode_surface_alpha_weighted_mipmaps() {
+        // A white pixel on a transparent black background.
+        let mut data = vec![0u8; 4 * 4 * 4];
+        data[..4].copy_from_slice(&[255; 4]);
+        let surface = SurfaceRgba8 {
+            width: 4,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            data,
+        };
+
+        let encoded = surface
+            .encode_with_options(
+                ImageFormat::Rgba8Unorm,
+                Quality::Fast,
+                Mipmaps::GeneratedAutomatic,
+                &EncodeOptions {
+                    alpha_weighted_mipmaps: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(3, encoded.mipmaps);
+        assert_eq!(&[255, 255, 255, 63], &encoded.get(0, 0, 1).unwrap()[..4]);
+        assert_eq!(&[255, 255, 255, 15], encoded.get(0, 0, 2).unwrap());
+    }
+
+    #[test]
+    fn reencode_to_format() {
+        let surface = Surface {
+            width: 4,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format: ImageFormat::Bgra8Unorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: [3u8, 2, 1, 4].repeat(4 * 4),
+        };
+        assert!(surface.format_matches(ImageFormat::Bgra8Unorm));
+        assert!(!surface.format_matches(ImageFormat::Rgba8Unorm));
+
+        // The same format is copied without generating mipmaps.
+        let same = surface
+            .reencode_to(
+                ImageFormat::Bgra8Unorm,
+                Quality::Fast,
+                Mipmaps::GeneratedAutomatic,
+            )
+            .unwrap();
+        assert_eq!(surface, same);
+
+        let rgba = surface
+            .reencode_to(ImageFormat::Rgba8Unorm, Quality::Fast, Mipmaps::Disabled)
+            .unwrap();
+        assert_eq!(ImageFormat::Rgba8Unorm, rgba.image_format);
+        assert_eq!([1u8, 2, 3, 4].repeat(4 * 4), rgba.data);
+
+        let float = surface
+            .reencode_to(ImageFormat::Rgba32Float, Quality::Fast, Mipmaps::Disabled)
+            .unwrap();
+        assert_eq!(
+            &[1.0 / 255.0, 2.0 / 255.0, 3.0 / 255.0, 4.0 / 255.0],
+            &bytemuck::cast_slice::<u8, f32>(&float.data)[..4]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "intel-tex")]
+    fn encode_surface_strip_mipmaps() {
+        // Each mipmap is padded to at least 1 block in both dimensions.
+        // 256, 128, 64, 32, 16, 8, 4, 2, 1 pixels use 64, 32, 16, 8, 4, 2, 1, 1, 1 blocks.
+        let blocks = 64 + 32 + 16 + 8 + 4 + 2 + 1 + 1 + 1;
+        for (width, height) in [(256, 1), (1, 256)] {
+            let surface = SurfaceRgba8 {
+                width,
+                height,
+                depth: 1,
+                layers: 1,
+                mipmaps: 1,
+                data: &[0u8; 256 * 4][..],
+            };
+            for (format, block_size) in [
+                (ImageFormat::BC1RgbaUnorm, 8),
+                (ImageFormat::BC7RgbaUnorm, 16),
+            ] {
+                let encoded = surface
+                    .encode(format, Quality::Fast, Mipmaps::GeneratedAutomatic)
+                    .unwrap();
+                assert_eq!(9, encoded.mipmaps);
+                assert_eq!(blocks * block_size, encoded.data.len());
+
+                let decoded = encoded.decode_rgba8().unwrap();
+                assert_eq!((width, height), (decoded.width, decoded.height));
+                assert_eq!(511 * 4, decoded.data.len());
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "intel-tex")]
     fn encode_surface_disabled_mipmaps() {
         let surface = SurfaceRgba8 {
             width: 4,
@@ -561,6 +1887,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "intel-tex")]
     fn encode_surface_mipmaps_from_surface() {
         let surface = SurfaceRgba8 {
             width: 4,
@@ -587,14 +1914,535 @@ mod tests {
     }
 
     #[test]
-    fn encode_surface_non_integral_dimensions() {
-        // This should succeed with appropriate padding.
-        let surface = SurfaceRgba8 {
-            width: 3,
-            height: 5,
+    fn encode_surface_mipmaps_from_surface_partial_mipmap() {
+        // The second mipmap is missing channels for its last pixel.
+        let result = SurfaceRgba8 {
+            width: 4,
+            height: 4,
             depth: 1,
             layers: 1,
-            mipmaps: 1,
+            mipmaps: 2,
+            data: &[0u8; 64 + 13],
+        }
+        .encode(
+            ImageFormat::BC7RgbaUnormSrgb,
+            Quality::Fast,
+            Mipmaps::FromSurface,
+        );
+
+        assert_eq!(
+            Err(SurfaceError::NotEnoughData {
+                expected: 64 + 16,
+                actual: 64 + 13
+            }),
+            result
+        );
+    }
+
+    #[test]
+    fn encode_surface_strict_data_length() {
+        let encode = |data: &[u8], format, strict_data_length| {
+            SurfaceRgba8 {
+                width: 4,
+                height: 4,
+                depth: 1,
+                layers: 1,
+                mipmaps: 1,
+                data,
+            }
+            .encode_with_options(
+                format,
+                Quality::Fast,
+                Mipmaps::Disabled,
+                &EncodeOptions {
+                    strict_data_length,
+                    ..Default::default()
+                },
+            )
+        };
+
+        assert!(encode(&[0u8; 64 + 4], ImageFormat::Rgba8Unorm, false).is_ok());
+        assert!(encode(&[0u8; 64], ImageFormat::Rgba8Unorm, true).is_ok());
+        for format in [ImageFormat::Rgba8Unorm, ImageFormat::BC7RgbaUnorm] {
+            assert_eq!(
+                Err(SurfaceError::UnexpectedDataLength {
+                    expected: 64,
+                    actual: 64 + 4
+                }),
+                encode(&[0u8; 64 + 4], format, true)
+            );
+        }
+    }
+
+    #[test]
+    fn encode_surface_strict_data_length_missing_mipmaps() {
+        // Missing mipmaps are allowed, but data past the last mipmap is not.
+        let encode = |data: &[u8]| {
+            SurfaceRgba8 {
+                width: 4,
+                height: 4,
+                depth: 1,
+                layers: 1,
+                mipmaps: 3,
+                data,
+            }
+            .encode_with_options(
+                ImageFormat::Rgba8Unorm,
+                Quality::Fast,
+                Mipmaps::FromSurface,
+                &EncodeOptions {
+                    strict_data_length: true,
+                    generate_missing_mipmaps: true,
+                    ..Default::default()
+                },
+            )
+        };
+
+        assert!(encode(&[0u8; 64]).is_ok());
+        assert!(encode(&[0u8; 64 + 16 + 4]).is_ok());
+        assert_eq!(
+            Err(SurfaceError::UnexpectedDataLength {
+                expected: 64 + 16 + 4,
+                actual: 64 + 16 + 8
+            }),
+            encode(&[0u8; 64 + 16 + 8])
+        );
+    }
+
+    #[test]
+    fn encode_surface_validate() {
+        let surface = SurfaceRgba8 {
+            width: 4,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            data: &[0u8; 4 * 4 * 4],
+        };
+        let encode = |mipmaps| {
+            surface
+                .encode(ImageFormat::Rgba8Unorm, Quality::Fast, mipmaps)
+                .and_then(|encoded| encoded.validate())
+        };
+        assert_eq!(Ok(()), encode(Mipmaps::GeneratedAutomatic));
+        assert_eq!(Ok(()), encode(Mipmaps::GeneratedExact(3)));
+        assert_eq!(
+            Err(SurfaceError::UnexpectedMipmapCount {
+                mipmaps: 5,
+                max_mipmaps: 3
+            }),
+            encode(Mipmaps::GeneratedExact(5))
+        );
+    }
+
+    #[test]
+    fn downsample_ignores_block_padding() {
+        // Unaligned dimensions and small mipmaps are padded to whole 4x4 blocks with zeros.
+        let color = [255u8, 0, 255, 255];
+        let format = ImageFormat::BC7RgbaUnorm;
+        for (base_width, base_height) in [(8, 8), (8, 2), (6, 6), (2, 2)] {
+            for address_mode in [crate::AddressMode::Clamp, crate::AddressMode::Wrap] {
+                for mipmap_filter in [MipmapFilter::Box, MipmapFilter::Triangle] {
+                    let options = EncodeOptions {
+                        mipmap_address_mode: address_mode,
+                        mipmap_filter,
+                        ..Default::default()
+                    };
+
+                    let (width, height, depth) =
+                        physical_dimensions(base_width, base_height, 1, format.block_dimensions());
+                    let data = color.repeat((base_width * base_height) as usize);
+                    let mut mip_data = MipData {
+                        width,
+                        height,
+                        depth,
+                        data: pad_mipmap_rgba(
+                            base_width as usize,
+                            base_height as usize,
+                            1,
+                            width,
+                            height,
+                            depth,
+                            &data,
+                        )
+                        .to_vec(),
+                    };
+
+                    let mipmaps = max_mipmap_count(base_width.max(base_height));
+                    for mipmap in 1..mipmaps {
+                        mip_data = mip_data.downsample(
+                            base_width,
+                            base_height,
+                            1,
+                            format,
+                            mipmap,
+                            &options,
+                        );
+                        let mip_width = mip_dimension(base_width, mipmap) as usize;
+                        let mip_height = mip_dimension(base_height, mipmap) as usize;
+                        for y in 0..mip_height {
+                            let row = y * mip_data.width * 4;
+                            assert_eq!(
+                                color.repeat(mip_width),
+                                mip_data.data[row..row + mip_width * 4],
+                                "{base_width}x{base_height} {address_mode:?} {mipmap_filter:?} mip {mipmap}"
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "intel-tex")]
+    fn encode_surface_mipmaps_wrap_bc7() {
+        // The padding for the 6x6 base level should not affect the 3x3 and 1x1 mipmaps.
+        let color = [255u8, 0, 255, 255];
+        let encoded = SurfaceRgba8 {
+            width: 6,
+            height: 6,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            data: color.repeat(6 * 6),
+        }
+        .encode_with_options(
+            ImageFormat::BC7RgbaUnorm,
+            Quality::Fast,
+            Mipmaps::GeneratedAutomatic,
+            &EncodeOptions {
+                mipmap_address_mode: crate::AddressMode::Wrap,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(3, encoded.mipmaps);
+
+        // The base level blocks also contain padding, so only check the generated mipmaps.
+        let decoded = encoded.decode_rgba8().unwrap();
+        for mipmap in 1..3 {
+            for pixel in decoded.get(0, 0, mipmap).unwrap().chunks_exact(4) {
+                assert!(pixel.iter().zip(color).all(|(a, b)| a.abs_diff(b) <= 2));
+            }
+        }
+    }
+
+    fn atlas() -> SurfaceRgba8<Vec<u8>> {
+        // Each 4x4 tile of the 8x8 atlas has a different color.
+        SurfaceRgba8 {
+            width: 8,
+            height: 8,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            data: (0..8 * 8)
+                .flat_map(|i| {
+                    let tile = (i / 32 * 2 + i % 8 / 4) as u8;
+                    [tile * 64, 255 - tile * 64, tile, 255]
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "intel-tex")]
+    fn encode_region_in_place() {
+        let atlas = atlas();
+        for (x, y) in [(0, 0), (4, 0), (0, 4), (4, 4)] {
+            let cropped = atlas
+                .crop(x, y, 4, 4)
+                .unwrap()
+                .encode(ImageFormat::BC7RgbaUnorm, Quality::Fast, Mipmaps::Disabled)
+                .unwrap();
+            let region = atlas
+                .encode_region(
+                    x,
+                    y,
+                    4,
+                    4,
+                    ImageFormat::BC7RgbaUnorm,
+                    Quality::Fast,
+                    &EncodeOptions::default(),
+                )
+                .unwrap();
+            assert_eq!(cropped, region);
+        }
+    }
+
+    #[test]
+    fn encode_region_copy() {
+        // Uncompressed formats and unaligned regions are copied first.
+        let region = atlas()
+            .encode_region(
+                3,
+                4,
+                2,
+                1,
+                ImageFormat::Rgba8Unorm,
+                Quality::Fast,
+                &EncodeOptions::default(),
+            )
+            .unwrap();
+        assert_eq!(
+            (2, 1, 1, 1),
+            (region.width, region.height, region.layers, region.mipmaps)
+        );
+        assert_eq!(vec![128, 127, 2, 255, 192, 63, 3, 255], region.data);
+    }
+
+    #[test]
+    fn encode_region_invalid() {
+        let encode = |x, width, quality| {
+            atlas().encode_region(
+                x,
+                4,
+                width,
+                4,
+                ImageFormat::BC7RgbaUnorm,
+                quality,
+                &EncodeOptions::default(),
+            )
+        };
+        assert_eq!(
+            Err(SurfaceError::CropOutOfBounds {
+                x: 8,
+                y: 4,
+                width: 4,
+                height: 4
+            }),
+            encode(8, 4, Quality::Fast)
+        );
+        assert_eq!(
+            Err(SurfaceError::ZeroSizedSurface {
+                width: 0,
+                height: 4,
+                depth: 1
+            }),
+            encode(0, 0, Quality::Fast)
+        );
+        assert_eq!(
+            Err(SurfaceError::LossyFormat {
+                format: ImageFormat::BC7RgbaUnorm
+            }),
+            encode(4, 4, Quality::Lossless)
+        );
+    }
+
+    #[test]
+    fn encode_padded_stride_uncompressed() {
+        // Padded rows are removed for uncompressed formats.
+        let data = [
+            1u8, 2, 3, 4, 5, 6, 7, 8, 0, 0, 0, 0, 9, 10, 11, 12, 13, 14, 15, 16,
+        ];
+        assert_eq!(
+            Ok((1..=16).collect()),
+            u8::encode(
+                2,
+                2,
+                &data,
+                12,
+                ImageFormat::Rgba8Unorm,
+                Quality::Fast,
+                &EncodeOptions::default()
+            )
+        );
+    }
+
+    #[test]
+    fn encode_surface_mipmaps_smallest_first() {
+        let surface = SurfaceRgba8 {
+            width: 4,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            data: &[255u8; 4 * 4 * 4],
+        };
+        let encode = |mip_order| {
+            surface
+                .encode_with_options(
+                    ImageFormat::R8Unorm,
+                    Quality::Fast,
+                    Mipmaps::GeneratedAutomatic,
+                    &EncodeOptions {
+                        mip_order,
+                        ..Default::default()
+                    },
+                )
+                .unwrap()
+        };
+        let largest_first = encode(MipOrder::LargestFirst);
+        let smallest_first = encode(MipOrder::SmallestFirst);
+
+        assert_eq!(MipOrder::SmallestFirst, smallest_first.mip_order);
+        assert_eq!(3, smallest_first.mipmaps);
+        assert_eq!(&[255u8], &smallest_first.data[..1]);
+        for mipmap in 0..3 {
+            assert_eq!(
+                largest_first.get(0, 0, mipmap),
+                smallest_first.get(0, 0, mipmap)
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "intel-tex")]
+    fn encode_surface_rgba16_float() {
+        let data: Vec<_> = (0..4 * 4 * 4)
+            .map(|i| f16::from_f32(i as f32 / 8.0))
+            .collect();
+        let surface = SurfaceRgba16Float {
+            width: 4,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            data: &data,
+        };
+
+        let bc6 = surface
+            .encode(ImageFormat::BC6hRgbUfloat, Quality::Fast, Mipmaps::Disabled)
+            .unwrap();
+        assert_eq!(16, bc6.data.len());
+
+        let rgba16 = surface
+            .encode(ImageFormat::Rgba16Float, Quality::Fast, Mipmaps::Disabled)
+            .unwrap();
+        assert_eq!(bytemuck::cast_slice::<_, u8>(&data), rgba16.data);
+    }
+
+    #[test]
+    fn encode_surface_mipmaps_range() {
+        let data: Vec<_> = (0..16 * 16 * 4).map(|i| (i % 251) as u8).collect();
+        let surface = SurfaceRgba8 {
+            width: 16,
+            height: 16,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            data: &data,
+        };
+        let all = surface
+            .encode(
+                ImageFormat::Rgba8Unorm,
+                Quality::Fast,
+                Mipmaps::GeneratedAutomatic,
+            )
+            .unwrap();
+        let range = surface
+            .encode(
+                ImageFormat::Rgba8Unorm,
+                Quality::Fast,
+                Mipmaps::GeneratedRange { start: 2, end: 4 },
+            )
+            .unwrap();
+
+        assert_eq!((4, 4, 1), (range.width, range.height, range.depth));
+        assert_eq!(2, range.mipmaps);
+        assert_eq!(all.get(0, 0, 2), range.get(0, 0, 0));
+        assert_eq!(all.get(0, 0, 3), range.get(0, 0, 1));
+        assert_eq!((4 * 4 + 2 * 2) * 4, range.data.len());
+    }
+
+    #[test]
+    fn encode_surface_mipmaps_invalid_range() {
+        let surface = SurfaceRgba8 {
+            width: 4,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            data: &[0u8; 4 * 4 * 4],
+        };
+        let encode = |start, end| {
+            surface.encode(
+                ImageFormat::Rgba8Unorm,
+                Quality::Fast,
+                Mipmaps::GeneratedRange { start, end },
+            )
+        };
+        assert_eq!(
+            Err(SurfaceError::EmptyMipmapRange { start: 1, end: 1 }),
+            encode(1, 1)
+        );
+        assert_eq!(
+            Err(SurfaceError::UnexpectedMipmapCount {
+                mipmaps: 4,
+                max_mipmaps: 3
+            }),
+            encode(1, 4)
+        );
+    }
+
+    #[test]
+    fn encode_with_error_map_blocks() {
+        // The right half loses its green and blue channels when converted to R8.
+        let data: Vec<_> = (0..8 * 4)
+            .flat_map(|i| {
+                if i % 8 < 4 {
+                    [64, 64, 64, 255]
+                } else {
+                    [64, 0, 0, 255]
+                }
+            })
+            .collect();
+        let surface = SurfaceRgba8 {
+            width: 8,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            data: data.as_slice(),
+        };
+
+        let (encoded, error_map) = surface
+            .encode_with_error_map(ImageFormat::Rgba8Unorm, Quality::Fast)
+            .unwrap();
+        assert_eq!(data, encoded.data);
+        assert_eq!(vec![f32::INFINITY; 8 * 4], error_map);
+
+        let (_, error_map) = surface
+            .encode_with_error_map(ImageFormat::R8Unorm, Quality::Fast)
+            .unwrap();
+        assert_eq!(8 * 4, error_map.len());
+        assert_eq!(f32::INFINITY, error_map[0]);
+        assert!(error_map[4].is_finite());
+    }
+
+    #[test]
+    #[cfg(feature = "intel-tex")]
+    fn solid_color_bc7() {
+        let surface =
+            Surface::solid_color(ImageFormat::BC7RgbaUnorm, 6, 5, [1, 2, 3, 4], Quality::Fast)
+                .unwrap();
+        assert_eq!(6, surface.width);
+        assert_eq!(5, surface.height);
+        assert_eq!(2 * 2 * 16, surface.data.len());
+        assert_eq!(
+            [1, 2, 3, 4].repeat(6 * 5),
+            surface.decode_rgba8().unwrap().data
+        );
+    }
+
+    #[test]
+    fn solid_color_uncompressed() {
+        let surface =
+            Surface::solid_color(ImageFormat::Bgra8Unorm, 3, 2, [1, 2, 3, 4], Quality::Fast)
+                .unwrap();
+        assert_eq!([3, 2, 1, 4].repeat(3 * 2), surface.data);
+    }
+
+    #[test]
+    #[cfg(feature = "intel-tex")]
+    fn encode_surface_non_integral_dimensions() {
+        // This should succeed with appropriate padding.
+        let surface = SurfaceRgba8 {
+            width: 3,
+            height: 5,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
             data: &[0u8; 256],
         }
         .encode(
@@ -640,6 +2488,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "intel-tex")]
     fn encode_surface_float32_integral_dimensions() {
         // It's ok for mipmaps to not be divisible by the block width.
         let surface = SurfaceRgba32Float {
@@ -668,6 +2517,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "intel-tex")]
     fn encode_surface_float32_cube_mipmaps_length() {
         // It's ok for mipmaps to not be divisible by the block width.
         let surface = SurfaceRgba32Float {
@@ -696,6 +2546,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "intel-tex")]
     fn encode_surface_float32_disabled_mipmaps() {
         let surface = SurfaceRgba32Float {
             width: 4,
@@ -722,6 +2573,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "intel-tex")]
     fn encode_surface_float32_mipmaps_from_surface() {
         let surface = SurfaceRgba32Float {
             width: 4,
@@ -748,6 +2600,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "intel-tex")]
     fn encode_surface_float32_non_integral_dimensions() {
         // This should succeed with appropriate padding.
         let surface = SurfaceRgba32Float {
@@ -825,6 +2678,8 @@ mod tests {
                 layers: 1,
                 mipmaps: 2,
                 image_format: ImageFormat::Rgba32Float,
+                layout: DataLayout::Tight,
+                mip_order: MipOrder::LargestFirst,
                 data: bytemuck::cast_slice::<[f32; 4], u8>(&[
                     [0.0, 1.0, 2.0, 3.0],
                     [4.0, 5.0, 6.0, 7.0],
@@ -870,6 +2725,8 @@ mod tests {
                 layers: 1,
                 mipmaps: 2,
                 image_format: ImageFormat::Rgba32Float,
+                layout: DataLayout::Tight,
+                mip_order: MipOrder::LargestFirst,
                 data: bytemuck::cast_slice::<[f32; 4], u8>(&[
                     [0.0, 1.0, 2.0, 3.0],
                     [4.0, 5.0, 6.0, 7.0],
@@ -931,6 +2788,8 @@ mod tests {
                 layers: 6,
                 mipmaps: 2,
                 image_format: ImageFormat::Rgba32Float,
+                layout: DataLayout::Tight,
+                mip_order: MipOrder::LargestFirst,
                 data: bytemuck::cast_slice::<[f32; 4], u8>(&[
                     [0.0, 1.0, 2.0, 3.0],
                     [4.0, 5.0, 6.0, 7.0],
@@ -999,6 +2858,44 @@ mod tests {
         );
     }
 
+    #[test]
+    fn encode_surface_float32_hdr_mipmaps() {
+        // Mipmaps for float surfaces should average values outside the range 0.0 to 1.0.
+        let data: Vec<_> = (0..16)
+            .flat_map(|i| {
+                let x = (i % 4) as f32 * 100.0;
+                [x, -x, 1000.0, 1.0]
+            })
+            .collect();
+        let surface = SurfaceRgba32Float {
+            width: 4,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            data: &data,
+        }
+        .encode(
+            ImageFormat::Rgba32Float,
+            Quality::Fast,
+            Mipmaps::GeneratedAutomatic,
+        )
+        .unwrap();
+
+        assert_eq!(3, surface.mipmaps);
+        let mips: &[[f32; 4]] = bytemuck::cast_slice(&surface.data);
+        assert_eq!(
+            &[
+                [50.0, -50.0, 1000.0, 1.0],
+                [250.0, -250.0, 1000.0, 1.0],
+                [50.0, -50.0, 1000.0, 1.0],
+                [250.0, -250.0, 1000.0, 1.0],
+                [150.0, -150.0, 1000.0, 1.0],
+            ],
+            &mips[16..]
+        );
+    }
+
     #[test]
     fn pad_1x1_to_1x1() {
         assert_eq!(
@@ -1034,6 +2931,515 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(feature = "intel-tex")]
+    fn encode_surface_1x1_mipmaps() {
+        // Mipmaps down to 1x1 should still be padded to at least one whole block.
+        for (format, mipmap_sizes) in [
+            (ImageFormat::BC7RgbaUnorm, 16 * 3),
+            (ImageFormat::Rgba8Unorm, (4 + 2 + 1) * 4),
+        ] {
+            let encoded = SurfaceRgba8 {
+                width: 4,
+                height: 1,
+                depth: 1,
+                layers: 1,
+                mipmaps: 1,
+                data: &[255u8; 4 * 4][..],
+            }
+            .encode(format, Quality::Fast, Mipmaps::GeneratedExact(3))
+            .unwrap();
+            assert_eq!(3, encoded.mipmaps);
+            assert_eq!(mipmap_sizes, encoded.data.len());
+        }
+    }
+
+    #[test]
+    fn get_mipmap_data_already_block_aligned() {
+        // Each 3x3 and 1x1 mipmap is already padded to 4x4 pixels.
+        let data: Vec<u8> = (0..4 * 4 * 2).flat_map(|i| [i as u8; 4]).collect();
+        let surface = SurfaceRgba8 {
+            width: 3,
+            height: 3,
+            depth: 1,
+            layers: 1,
+            mipmaps: 2,
+            data: &data[..],
+        };
+        let options = EncodeOptions {
+            input_alignment: crate::InputAlignment::AlreadyBlockAligned,
+            ..Default::default()
+        };
+
+        for mipmap in 0..2 {
+            let mip_data =
+                get_mipmap_data(&surface, 0, mipmap, ImageFormat::BC7RgbaUnorm, &options).unwrap();
+            assert_eq!((4, 4, 1), (mip_data.width, mip_data.height, mip_data.depth));
+            let start = mipmap as usize * 64;
+            assert_eq!(&data[start..start + 64], &mip_data.data[..]);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "intel-tex")]
+    fn encode_surface_already_block_aligned_length() {
+        let data = [0u8; 4 * 4 * 4 * 2];
+        let surface = |len| SurfaceRgba8 {
+            width: 3,
+            height: 3,
+            depth: 1,
+            layers: 1,
+            mipmaps: 2,
+            data: &data[..len],
+        };
+        let encode = |len| {
+            surface(len).encode_with_options(
+                ImageFormat::BC7RgbaUnorm,
+                Quality::Fast,
+                Mipmaps::FromSurface,
+                &EncodeOptions {
+                    input_alignment: crate::InputAlignment::AlreadyBlockAligned,
+                    ..Default::default()
+                },
+            )
+        };
+
+        assert_eq!(16 * 2, encode(data.len()).unwrap().data.len());
+        assert_eq!(
+            Err(SurfaceError::NotEnoughData {
+                expected: 128,
+                actual: 40
+            }),
+            encode(40)
+        );
+        // The tight layout only needs 3x3 and 1x1 pixels.
+        assert!(surface(40)
+            .encode(
+                ImageFormat::BC7RgbaUnorm,
+                Quality::Fast,
+                Mipmaps::FromSurface
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn clamp_report_non_finite() {
+        let data = [
+            [f32::NAN, 1.0, 1.0, 1.0],
+            [1.0, f32::INFINITY, 1.0, 1.0],
+            [1.0, 1.0, f32::NEG_INFINITY, 1.0],
+            [1.0, 1.0, 1.0, f32::NAN],
+        ]
+        .concat();
+
+        assert_eq!(
+            ClampReport {
+                clamped_pixels: 3,
+                max_overflow: f32::INFINITY
+            },
+            clamp_report(&data, ImageFormat::BC6hRgbSfloat, 1.0)
+        );
+        assert_eq!(
+            ClampReport {
+                clamped_pixels: 1,
+                max_overflow: 0.0
+            },
+            clamp_report(&data[..4], ImageFormat::BC6hRgbUfloat, 1.0)
+        );
+        assert_eq!(
+            ClampReport::default(),
+            clamp_report(&data, ImageFormat::Rgba32Float, 1.0)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "intel-tex")]
+    fn encode_with_clamp_report_bc6h() {
+        let surface = SurfaceRgba32Float {
+            width: 4,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            data: [
+                [0.5, 1.0, 2.0, 1.0],
+                [70000.0, 1.0, 1.0, 1.0],
+                [-2.0, 0.0, 0.0, -100.0],
+            ]
+            .into_iter()
+            .chain(std::iter::repeat_n([1.0; 4], 13))
+            .flatten()
+            .collect::<Vec<f32>>(),
+        };
+
+        let encode = |format, bc6_exposure| {
+            surface
+                .encode_with_clamp_report(
+                    format,
+                    Quality::Fast,
+                    Mipmaps::Disabled,
+                    &EncodeOptions {
+                        bc6_exposure,
+                        ..Default::default()
+                    },
+                )
+                .unwrap()
+                .1
+        };
+
+        assert_eq!(
+            ClampReport {
+                clamped_pixels: 2,
+                max_overflow: 70000.0 - 65504.0
+            },
+            encode(ImageFormat::BC6hRgbUfloat, 0)
+        );
+        assert_eq!(
+            ClampReport {
+                clamped_pixels: 1,
+                max_overflow: 70000.0 - 65504.0
+            },
+            encode(ImageFormat::BC6hRgbSfloat, 0)
+        );
+        // The exposure scales values into range.
+        assert_eq!(
+            ClampReport {
+                clamped_pixels: 0,
+                max_overflow: 0.0
+            },
+            encode(ImageFormat::BC6hRgbSfloat, -1)
+        );
+        assert_eq!(ClampReport::default(), encode(ImageFormat::Rgba32Float, 0));
+    }
+
+    #[test]
+    fn encode_surface_mipmaps_automatic_capped() {
+        let surface = SurfaceRgba8 {
+            width: 16,
+            height: 16,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            data: &[0u8; 16 * 16 * 4][..],
+        };
+        let encode = |count| {
+            surface
+                .encode(
+                    ImageFormat::Rgba8Unorm,
+                    Quality::Fast,
+                    Mipmaps::GeneratedAutomaticCapped(count),
+                )
+                .unwrap()
+                .mipmaps
+        };
+        assert_eq!(1, encode(0));
+        assert_eq!(3, encode(3));
+        assert_eq!(5, encode(5));
+        assert_eq!(5, encode(10));
+    }
+
+    #[test]
+    fn encode_levels() {
+        let data: Vec<u8> = (0..8 * 8 * 2 * 4).map(|i| i as u8).collect();
+        let surface = SurfaceRgba8 {
+            width: 8,
+            height: 8,
+            depth: 1,
+            layers: 2,
+            mipmaps: 1,
+            data: &data[..],
+        };
+
+        for mip_order in [MipOrder::LargestFirst, MipOrder::SmallestFirst] {
+            let options = EncodeOptions {
+                mip_order,
+                ..Default::default()
+            };
+            let encoded = surface
+                .encode_with_options(
+                    ImageFormat::Rgba8Unorm,
+                    Quality::Fast,
+                    Mipmaps::GeneratedAutomatic,
+                    &options,
+                )
+                .unwrap();
+            let (info, levels) = surface
+                .encode_levels(
+                    ImageFormat::Rgba8Unorm,
+                    Quality::Fast,
+                    Mipmaps::GeneratedAutomatic,
+                    &options,
+                )
+                .unwrap();
+
+            assert_eq!(encoded.info(), info);
+            assert_eq!(2 * 4, levels.len());
+            for layer in 0..2 {
+                for mipmap in 0..4 {
+                    assert_eq!(
+                        encoded.get(layer, 0, mipmap),
+                        Some(levels[(layer * 4 + mipmap) as usize].as_slice())
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "intel-tex")]
+    fn encoded_size_matches_output() {
+        let encoded = SurfaceRgba8 {
+            width: 5,
+            height: 3,
+            depth: 1,
+            layers: 2,
+            mipmaps: 1,
+            data: &[0u8; 5 * 3 * 2 * 4][..],
+        }
+        .encode(
+            ImageFormat::BC7RgbaUnorm,
+            Quality::Fast,
+            Mipmaps::GeneratedExact(3),
+        )
+        .unwrap();
+        assert_eq!(
+            Some(encoded.data.len()),
+            encoded_size((5, 3, 1), 2, 0..3, ImageFormat::BC7RgbaUnorm)
+        );
+        assert_eq!(
+            Some(16 * 3),
+            encoded_size((5, 3, 1), 1, 1..4, ImageFormat::BC7RgbaUnorm)
+        );
+    }
+
+    #[test]
+    fn encoded_size_overflow() {
+        assert_eq!(
+            None,
+            encoded_size(
+                (u32::MAX, u32::MAX, u32::MAX),
+                u32::MAX,
+                0..1,
+                ImageFormat::Rgba8Unorm
+            )
+        );
+        assert_eq!(
+            None,
+            encoded_size((1, 1, 1), u32::MAX, 0..u32::MAX, ImageFormat::Rgba32Float)
+        );
+    }
+
+    #[test]
+    fn encode_surface_planar() {
+        let plane = [128u8; 4 * 4];
+        let encoded = SurfaceRgba8Planar {
+            width: 4,
+            height: 4,
+            r: &plane[..],
+            g: &plane[..],
+            b: &plane[..],
+            a: &plane[..],
+        }
+        .encode(ImageFormat::Rgba8Unorm, Quality::Fast, Mipmaps::Disabled)
+        .unwrap();
+        assert_eq!(vec![128u8; 4 * 4 * 4], encoded.data);
+    }
+
+    #[test]
+    fn encode_surface_generate_missing_mipmaps() {
+        // The 1x1 mipmap is missing from the data.
+        let data = [[255u8; 4 * 4 * 4].as_slice(), &[100u8; 2 * 2 * 4]].concat();
+        let surface = SurfaceRgba8 {
+            width: 4,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 3,
+            data: &data[..],
+        };
+
+        assert_eq!(
+            Err(SurfaceError::NotEnoughData {
+                expected: 84,
+                actual: 80
+            }),
+            surface.encode(ImageFormat::Rgba8Unorm, Quality::Fast, Mipmaps::FromSurface)
+        );
+
+        let encoded = surface
+            .encode_with_options(
+                ImageFormat::Rgba8Unorm,
+                Quality::Fast,
+                Mipmaps::FromSurface,
+                &EncodeOptions {
+                    generate_missing_mipmaps: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(3, encoded.mipmaps);
+        assert_eq!(&data[..], &encoded.data[..80]);
+        assert_eq!(&[100u8; 4], &encoded.data[80..]);
+    }
+
+    #[test]
+    fn encode_surface_generate_missing_mipmaps_layers() {
+        // Each layer only stores the 2x2 base level.
+        let data = [[10u8; 2 * 2 * 4], [200u8; 2 * 2 * 4]].concat();
+        let surface = SurfaceRgba8 {
+            width: 2,
+            height: 2,
+            depth: 1,
+            layers: 2,
+            mipmaps: 2,
+            data: &data[..],
+        };
+
+        let encoded = surface
+            .encode_with_options(
+                ImageFormat::Rgba8Unorm,
+                Quality::Fast,
+                Mipmaps::FromSurface,
+                &EncodeOptions {
+                    generate_missing_mipmaps: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(2, encoded.layers);
+        assert_eq!(2, encoded.mipmaps);
+        assert_eq!([[10u8; 5 * 4], [200u8; 5 * 4]].concat(), encoded.data);
+    }
+
+    #[test]
+    fn mip_data_encode_invalid_size() {
+        let encode = |width, height| {
+            MipData {
+                width,
+                height,
+                depth: 1,
+                data: vec![0u8; width * height * 4],
+            }
+            .encode(
+                ImageFormat::BC7RgbaUnorm,
+                Quality::Fast,
+                &EncodeOptions::default(),
+            )
+        };
+        assert_eq!(
+            Err(SurfaceError::ZeroSizedSurface {
+                width: 0,
+                height: 4,
+                depth: 1
+            }),
+            encode(0, 4)
+        );
+        assert_eq!(
+            Err(SurfaceError::NonIntegralDimensionsInBlocks {
+                width: 2,
+                height: 4,
+                depth: 1,
+                block_width: 4,
+                block_height: 4
+            }),
+            encode(2, 4)
+        );
+    }
+
+    #[test]
+    fn normal_map_rgba_normalize() {
+        let data = [255u8, 255, 128, 7, 128, 128, 255, 255];
+        let to_f32 = |c| c as f32 / 255.0;
+        let from_f32 = |c: f32| (c * 255.0).round() as u8;
+
+        assert_eq!(
+            vec![218, 218, 128, 7, 128, 128, 255, 255],
+            normal_map_rgba(
+                &data,
+                ImageFormat::BC7RgbaUnorm,
+                ContentHint::NormalMap,
+                to_f32,
+                from_f32
+            )
+            .into_owned()
+        );
+
+        // Color data and uncompressed formats are not modified.
+        assert_eq!(
+            &data[..],
+            &*normal_map_rgba(
+                &data,
+                ImageFormat::BC7RgbaUnorm,
+                ContentHint::Color,
+                to_f32,
+                from_f32
+            )
+        );
+        assert_eq!(
+            &data[..],
+            &*normal_map_rgba(
+                &data,
+                ImageFormat::Rgba8Unorm,
+                ContentHint::NormalMap,
+                to_f32,
+                from_f32
+            )
+        );
+    }
+
+    #[test]
+    fn normal_map_rgba_degenerate() {
+        // Zero vectors and vectors close to zero should not be scaled up.
+        let data = [128u8, 128, 128, 255, 127, 128, 129, 255];
+        assert_eq!(
+            &data[..],
+            &*normal_map_rgba(
+                &data,
+                ImageFormat::BC3RgbaUnorm,
+                ContentHint::NormalMap,
+                |c| c as f32 / 255.0,
+                |c| (c * 255.0).round() as u8
+            )
+        );
+
+        let data = [0.5f32, 0.5, 0.5, 1.0];
+        assert_eq!(
+            &data[..],
+            &*normal_map_rgba(
+                &data,
+                ImageFormat::BC6hRgbUfloat,
+                ContentHint::NormalMap,
+                |c| c,
+                |c| c
+            )
+        );
+    }
+
+    #[test]
+    fn encode_surface_normal_map_uncompressed() {
+        let surface = SurfaceRgba8 {
+            width: 2,
+            height: 1,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            data: &[255u8, 255, 128, 7, 128, 128, 255, 255][..],
+        };
+        let options = EncodeOptions {
+            content_hint: ContentHint::NormalMap,
+            ..Default::default()
+        };
+        let encoded = surface
+            .encode_with_options(
+                ImageFormat::Rgba8Unorm,
+                Quality::Fast,
+                Mipmaps::Disabled,
+                &options,
+            )
+            .unwrap();
+        assert_eq!(surface.data, encoded.data);
+    }
+
     #[test]
     fn physical_dimensions_padding() {
         assert_eq!((4, 5, 6), physical_dimensions(2, 3, 1, (4, 5, 6)));
@@ -1047,6 +3453,139 @@ mod tests {
         assert_eq!((4, 4, 1), physical_dimensions(1, 1, 1, (4, 4, 1)));
     }
 
+    #[test]
+    fn encode_r8_single_channel_source() {
+        let encode = |single_channel_source| {
+            SurfaceRgba8 {
+                width: 2,
+                height: 1,
+                depth: 1,
+                layers: 1,
+                mipmaps: 1,
+                data: &[255u8, 0, 0, 64, 10, 20, 30, 40],
+            }
+            .encode_with_options(
+                ImageFormat::R8Unorm,
+                Quality::Fast,
+                Mipmaps::Disabled,
+                &EncodeOptions {
+                    single_channel_source,
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+            .data
+        };
+
+        assert_eq!(vec![255, 10], encode(SingleChannelSource::Red));
+        assert_eq!(vec![76, 18], encode(SingleChannelSource::Luminance));
+        assert_eq!(vec![64, 40], encode(SingleChannelSource::Alpha));
+        assert_eq!(vec![0, 20], encode(SingleChannelSource::Green));
+        assert_eq!(vec![0, 30], encode(SingleChannelSource::Blue));
+    }
+
+    #[test]
+    fn encode_r8_invert_single_channel() {
+        let encode = |format, single_channel_source| {
+            SurfaceRgba8 {
+                width: 2,
+                height: 1,
+                depth: 1,
+                layers: 1,
+                mipmaps: 1,
+                data: &[255u8, 0, 0, 64, 10, 20, 30, 40],
+            }
+            .encode_with_options(
+                format,
+                Quality::Fast,
+                Mipmaps::Disabled,
+                &EncodeOptions {
+                    single_channel_source,
+                    invert_single_channel: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+            .data
+        };
+
+        assert_eq!(
+            vec![0, 245],
+            encode(ImageFormat::R8Unorm, SingleChannelSource::Red)
+        );
+        assert_eq!(
+            vec![191, 215],
+            encode(ImageFormat::R8Unorm, SingleChannelSource::Alpha)
+        );
+        // Inversion only applies to single channel formats.
+        assert_eq!(
+            vec![255, 0, 0, 64, 10, 20, 30, 40],
+            encode(ImageFormat::Rgba8Unorm, SingleChannelSource::Red)
+        );
+    }
+
+    #[test]
+    fn encode_r32_float_single_channel_source() {
+        let encode = |single_channel_source| {
+            SurfaceRgba32Float {
+                width: 1,
+                height: 1,
+                depth: 1,
+                layers: 1,
+                mipmaps: 1,
+                data: &[1.0, 1.0, 0.0, 0.5],
+            }
+            .encode_with_options(
+                ImageFormat::R32Float,
+                Quality::Fast,
+                Mipmaps::Disabled,
+                &EncodeOptions {
+                    single_channel_source,
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+            .data
+        };
+
+        assert_eq!(
+            1.0f32.to_le_bytes().to_vec(),
+            encode(SingleChannelSource::Red)
+        );
+        assert_eq!(
+            (0.299f32 + 0.587f32).to_le_bytes().to_vec(),
+            encode(SingleChannelSource::Luminance)
+        );
+        assert_eq!(
+            0.5f32.to_le_bytes().to_vec(),
+            encode(SingleChannelSource::Alpha)
+        );
+    }
+
+    #[test]
+    fn encode_r32_float_invert_single_channel() {
+        let encoded = SurfaceRgba32Float {
+            width: 1,
+            height: 1,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            data: &[1.0, 0.25, 0.0, 0.5],
+        }
+        .encode_with_options(
+            ImageFormat::R32Float,
+            Quality::Fast,
+            Mipmaps::Disabled,
+            &EncodeOptions {
+                single_channel_source: SingleChannelSource::Green,
+                invert_single_channel: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(0.75f32.to_le_bytes().to_vec(), encoded.data);
+    }
+
     #[test]
     fn encode_all_u8() {
         for image_format in ImageFormat::iter() {
@@ -1058,9 +3597,24 @@ mod tests {
                 mipmaps: 1,
                 data: vec![0u8; 4 * 4 * 4],
             };
-            surface
-                .encode(image_format, Quality::Normal, Mipmaps::GeneratedAutomatic)
-                .unwrap();
+            let result = surface.encode(image_format, Quality::Normal, Mipmaps::GeneratedAutomatic);
+            match image_format {
+                ImageFormat::EacR11Unorm | ImageFormat::EacRg11Unorm => assert_eq!(
+                    Err(SurfaceError::UnsupportedEncodeFormat {
+                        format: image_format
+                    }),
+                    result
+                ),
+                // Block compressed formats require an encoder backend.
+                #[cfg(not(feature = "intel-tex"))]
+                _ if image_format.block_dimensions() != (1, 1, 1) => assert_eq!(
+                    Err(SurfaceError::UnsupportedEncodeFormat {
+                        format: image_format
+                    }),
+                    result
+                ),
+                _ => assert!(result.is_ok()),
+            }
         }
     }
 
@@ -1075,9 +3629,43 @@ mod tests {
                 mipmaps: 1,
                 data: vec![0.0; 4 * 4 * 4],
             };
-            surface
-                .encode(image_format, Quality::Normal, Mipmaps::GeneratedAutomatic)
-                .unwrap();
+            let result = surface.encode(image_format, Quality::Normal, Mipmaps::GeneratedAutomatic);
+            match image_format {
+                ImageFormat::EacR11Unorm | ImageFormat::EacRg11Unorm => assert_eq!(
+                    Err(SurfaceError::UnsupportedEncodeFormat {
+                        format: image_format
+                    }),
+                    result
+                ),
+                // Block compressed formats require an encoder backend.
+                #[cfg(not(feature = "intel-tex"))]
+                _ if image_format.block_dimensions() != (1, 1, 1) => assert_eq!(
+                    Err(SurfaceError::UnsupportedEncodeFormat {
+                        format: image_format
+                    }),
+                    result
+                ),
+                _ => assert!(result.is_ok()),
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "intel-tex"))]
+    fn encode_blocks_unsupported_format() {
+        for format in ImageFormat::iter().filter(|f| f.block_dimensions() != (1, 1, 1)) {
+            assert_eq!(
+                Err(SurfaceError::UnsupportedEncodeFormat { format }),
+                u8::encode(
+                    4,
+                    4,
+                    &[0u8; 4 * 4 * 4],
+                    4 * 4,
+                    format,
+                    Quality::Normal,
+                    &EncodeOptions::default()
+                )
+            );
         }
     }
 }
@@ -19,6 +19,9 @@ pub enum CreateImageError {
 
     #[error("{mipmaps} mipmaps exceeds the maximum expected mipmap count of {max_mipmaps}")]
     UnexpectedMipmapCount { mipmaps: u32, max_mipmaps: u32 },
+
+    #[error("expected {expected} layers but found {layers}")]
+    UnexpectedLayerCount { layers: u32, expected: u32 },
 }
 
 /// Errors that can occur while encoding or decoding a surface.
@@ -27,10 +30,13 @@ pub enum SurfaceError {
     #[error("surface dimensions {width} x {height} x {depth} contain no pixels")]
     ZeroSizedSurface { width: u32, height: u32, depth: u32 },
 
+    #[error("surface must have at least 1 layer and 1 mipmap but found {layers} layers and {mipmaps} mipmaps")]
+    ZeroLayersOrMipmaps { layers: u32, mipmaps: u32 },
+
     #[error("surface pixel count {width} x {height} x {depth} would overflow")]
     PixelCountWouldOverflow { width: u32, height: u32, depth: u32 },
 
-    #[error("surface dimensions {width} x {height} x {depth} are not divisibly by the block dimensions {block_width} x {block_height}")]
+    #[error("surface dimensions {width} x {height} x {depth} are not divisible by the {block_width} x {block_height} block size")]
     NonIntegralDimensionsInBlocks {
         width: u32,
         height: u32,
@@ -42,6 +48,23 @@ pub enum SurfaceError {
     #[error("expected surface to have at least {expected} bytes but found {actual}")]
     NotEnoughData { expected: usize, actual: usize },
 
+    #[error("expected surface to have exactly {expected} bytes but found {actual}")]
+    UnexpectedDataLength { expected: usize, actual: usize },
+
+    #[error(
+        "data length {actual} is smaller than the expected length {expected} for format {format:?}"
+    )]
+    FormatDataMismatch {
+        format: ImageFormat,
+        expected: usize,
+        actual: usize,
+    },
+
+    #[error(
+        "format {format:?} uses lossy compression and cannot be encoded with lossless quality"
+    )]
+    LossyFormat { format: ImageFormat },
+
     #[error("encoding data to format {format:?} is not supported")]
     UnsupportedEncodeFormat { format: ImageFormat },
 
@@ -59,6 +82,80 @@ pub enum SurfaceError {
     #[error("DDS image format {0:?} is not supported")]
     UnsupportedDdsFormat(DdsFormatInfo),
 
+    #[error("DXGI format {code} is not supported")]
+    UnsupportedDxgiFormat { code: u32 },
+
+    #[error("D3D format {code} is not supported")]
+    UnsupportedD3dFormat { code: u32 },
+
+    #[error("FourCC {code:#010x} is not supported")]
+    UnsupportedFourCC { code: u32 },
+
     #[error("{mipmaps} mipmaps exceeds the maximum expected mipmap count of {max_mipmaps}")]
     UnexpectedMipmapCount { mipmaps: u32, max_mipmaps: u32 },
+
+    #[error("crop region {width} x {height} at ({x}, {y}) is not contained within the surface")]
+    CropOutOfBounds {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+
+    #[error(
+        "surface {index} does not match the format, dimensions, and mipmaps of the first surface"
+    )]
+    MismatchedLayer { index: usize },
+
+    #[error("surface dimensions {width} x {height} x {depth} with {layers} layers do not match the expected dimensions {expected_width} x {expected_height} x {expected_depth} with {expected_layers} layers")]
+    MismatchedDimensions {
+        width: u32,
+        height: u32,
+        depth: u32,
+        layers: u32,
+        expected_width: u32,
+        expected_height: u32,
+        expected_depth: u32,
+        expected_layers: u32,
+    },
+
+    #[error("image format {format:?} does not have an alpha channel")]
+    MissingAlphaChannel { format: ImageFormat },
+
+    #[error("failed to allocate {size} bytes for the encoded surface")]
+    OutputTooLarge { size: usize },
+
+    #[error("mipmap range {start}..{end} does not contain any mipmaps")]
+    EmptyMipmapRange { start: u32, end: u32 },
+
+    #[error("BC7 mode mask {modes:#010b} does not enable any complete group of encoder modes")]
+    UnsupportedBc7Modes { modes: u8 },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn surface_error_display() {
+        assert_eq!(
+            "surface dimensions 3 x 5 x 2 are not divisible by the 4 x 4 block size",
+            SurfaceError::NonIntegralDimensionsInBlocks {
+                width: 3,
+                height: 5,
+                depth: 2,
+                block_width: 4,
+                block_height: 4,
+            }
+            .to_string()
+        );
+        assert_eq!(
+            "error decompressing surface: expected surface to have at least 16 bytes but found 8",
+            CreateImageError::from(SurfaceError::NotEnoughData {
+                expected: 16,
+                actual: 8
+            })
+            .to_string()
+        );
+    }
 }
@@ -50,6 +50,17 @@
 //! The `"ddsfile"` and `"image"` features can then be enabled individually.
 //! The `"encode"` feature is enabled by default but can be disabled
 //! to resolve compilation errors on some targets if not needed.
+//! Encoding block compressed formats uses the `"intel-tex"` feature, which is enabled by default.
+//! Disable `"intel-tex"` while keeping `"encode"` for a pure Rust build
+//! that encodes only uncompressed formats.
+//! The optional `"tracing"` feature emits [tracing](https://crates.io/crates/tracing) spans
+//! for encoding each mipmap and block compressed surface.
+//! The optional `"rayon"` feature encodes and decodes strips of blocks in parallel
+//! using [rayon](https://crates.io/crates/rayon).
+//! The optional `"capi"` feature exports C functions for encoding and decoding.
+//! See the `capi` module for details.
+//! The optional `"perf-unchecked"` feature skips bounds checks when reading blocks for decoding
+//! after the data length for the entire surface has already been validated.
 //!
 //! # Direct Draw Surface (DDS)
 //! DDS can store GPU texture data in a variety of formats.
@@ -83,7 +94,10 @@ mod rgba;
 mod surface;
 
 use rgba::convert::Channel;
-pub use surface::{Surface, SurfaceRgba32Float, SurfaceRgba8};
+pub use surface::{
+    BlockLocation, BlockRef, ChannelStats, DataLayout, MipOrder, Surface, SurfaceInfo,
+    SurfaceRgba16Float, SurfaceRgba32Float, SurfaceRgba8, SurfaceRgba8Planar,
+};
 
 pub mod error;
 use error::*;
@@ -95,15 +109,24 @@ pub use ddsfile;
 pub use image;
 
 mod decode;
+pub use decode::{
+    compare_surfaces, CompareReport, DecodedRgba8, RoundtripReport, RowAlignedRgba8,
+    StrictDecodedRgba8,
+};
 
 #[cfg(feature = "encode")]
 mod encode;
+#[cfg(feature = "encode")]
+pub use encode::ClampReport;
 
 #[cfg(feature = "ddsfile")]
 mod dds;
 #[cfg(feature = "ddsfile")]
 pub use dds::*;
 
+#[cfg(feature = "capi")]
+pub mod capi;
+
 /// The conversion quality when encoding to compressed formats.
 ///
 /// Higher quality settings run significantly slower.
@@ -115,6 +138,7 @@ pub use dds::*;
     feature = "strum",
     derive(strum::EnumString, strum::Display, strum::EnumIter)
 )]
+#[non_exhaustive]
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Quality {
     /// Faster exports with slightly lower quality.
@@ -123,6 +147,12 @@ pub enum Quality {
     Normal,
     /// Slower exports for slightly higher quality.
     Slow,
+    /// Only allow formats that store every channel of the input without any loss of precision.
+    ///
+    /// Encoding to a block compressed format or an uncompressed format with fewer channels
+    /// or less precision like [ImageFormat::R8Unorm] or [ImageFormat::Bgra4Unorm]
+    /// returns [SurfaceError::LossyFormat].
+    Lossless,
 }
 
 /// Options for how many mipmaps to generate.
@@ -139,20 +169,353 @@ pub enum Mipmaps {
     /// No mipmapping. Only the base mip level will be used.
     Disabled,
     /// Use the number of mipmaps specified in the input surface.
+    ///
+    /// Every level is encoded as the color space of the output format
+    /// without converting between sRGB and linear values,
+    /// so all levels in the input should use the same color space.
     FromSurface,
     /// Generate mipmaps to create a surface with a desired number of mipmaps.
     /// A value of `0` or `1` is equivalent to [Mipmaps::Disabled].
+    /// Values larger than the number of mipmaps supported by the dimensions
+    /// return [SurfaceError::UnexpectedMipmapCount].
     GeneratedExact(u32),
     /// Generate mipmaps starting from the base level
     /// until dimensions can be reduced no further.
     GeneratedAutomatic,
+    /// Generate mipmaps like [Mipmaps::GeneratedAutomatic]
+    /// but with at most the given number of mipmaps including the base level.
+    /// A value of `0` or `1` is equivalent to [Mipmaps::Disabled].
+    GeneratedAutomaticCapped(u32),
+    /// Generate mipmaps and only keep the levels in the range `start..end`.
+    /// The base level of the output has the dimensions of mipmap `start`.
+    GeneratedRange { start: u32, end: u32 },
+}
+
+/// Additional settings for encoding that most applications can leave as the default.
+///
+/// Fields may be added in future versions,
+/// so create options by modifying [EncodeOptions::default].
+#[non_exhaustive]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct EncodeOptions {
+    /// A bitmask of the BC7 modes the encoder may use with bit `i` enabling mode `i`.
+    ///
+    /// The encoder only toggles groups of related modes,
+    /// so a group is used only if all of its modes are enabled.
+    /// The groups are mode 0, modes 1, 3, and 7, modes 4 and 5, and mode 6.
+    /// Mode 2 can be disabled on its own but is only used alongside mode 0.
+    /// The default of `0xFF` enables all modes.
+    ///
+    /// The encoder weights errors in RGB and alpha equally.
+    /// For alpha data that needs higher precision like masks,
+    /// enabling only modes 4 and 5 with `0b0011_0000` encodes alpha with separate endpoints.
+    pub bc7_modes: u8,
+    /// The input channel used when encoding to single channel formats like [ImageFormat::BC4RUnorm].
+    pub single_channel_source: SingleChannelSource,
+    /// Invert the value from [EncodeOptions::single_channel_source] when encoding to single channel formats.
+    ///
+    /// Values are replaced with `255 - value` for 8 bit input and `1.0 - value` for floating point input.
+    /// This can convert between maps like smoothness and roughness without an additional pass.
+    pub invert_single_channel: bool,
+    /// Reject input surfaces with more data than required for their dimensions, layers, and mipmaps.
+    ///
+    /// The expected size uses the block padded dimensions for [InputAlignment::AlreadyBlockAligned].
+    /// Extra data is ignored by default.
+    /// Enable this during development to catch incorrectly calculated mipmap or layer ranges.
+    pub strict_data_length: bool,
+    /// The order of the encoded mipmaps for each layer.
+    pub mip_order: MipOrder,
+    /// Snap the RGB endpoints of BC1 blocks to the nearest colors in this palette.
+    ///
+    /// Decoded colors are limited to the palette entries and their interpolated values
+    /// after conversion to 5:6:5 bits. Blocks always use the 4 color mode without transparency.
+    /// The default of `None` does not constrain the endpoints.
+    pub bc1_palette: Option<Vec<[u8; 3]>>,
+    /// Force BC1 blocks to use the 4 color mode with the first endpoint greater than the second.
+    ///
+    /// Some older decoders do not correctly handle blocks using the 3 color mode.
+    /// Blocks with equal endpoints change one endpoint by the smallest 5:6:5 step.
+    /// With [EncodeOptions::bc1_palette], the second endpoint is the nearest other palette color instead.
+    /// A palette with only one distinct 5:6:5 color keeps equal endpoints.
+    /// The 4 color mode has no transparency, so transparent pixels encoded to
+    /// [ImageFormat::BC1RgbaUnorm] or [ImageFormat::BC1RgbaUnormSrgb] become opaque.
+    /// The default of `false` allows the encoder to use either mode.
+    pub bc1_four_color: bool,
+    /// The interpolation mode for the alpha blocks of BC3 and the red blocks of [ImageFormat::BC4RUnorm].
+    pub alpha_block_mode: AlphaBlockMode,
+    /// The number of block rows in each strip encoded in parallel with the `"rayon"` feature.
+    ///
+    /// Smaller strips balance work across threads at the cost of more overhead per strip.
+    /// Values of `0` are treated as `1`. The default is `64`.
+    pub parallel_block_rows: u32,
+    /// Stop [Mipmaps::GeneratedAutomatic] at the last mipmap that is at least one block
+    /// in width and height for compressed formats like [ImageFormat::BC7RgbaUnorm].
+    ///
+    /// Smaller mipmaps are still padded to whole blocks and add little detail.
+    /// The base level is always included. Uncompressed formats are not affected.
+    pub skip_sub_block_mipmaps: bool,
+    /// The exposure in stops applied to the input of BC6H formats before encoding.
+    ///
+    /// Values are multiplied by [EncodeOptions::bc6_scale] to fit bright HDR content
+    /// into the range of BC6H. Multiply the decoded values by [EncodeOptions::bc6_inverse_scale]
+    /// to restore the original range. The default of `0` leaves values unchanged.
+    pub bc6_exposure: i8,
+    /// Weight the RGB channels by alpha when generating mipmaps.
+    ///
+    /// This prevents the color of transparent pixels from darkening the edges
+    /// of cutout textures in smaller mipmaps. Regions that are fully transparent
+    /// use an unweighted average. Alpha is always averaged without weights.
+    pub alpha_weighted_mipmaps: bool,
+    /// The filter used when generating mipmaps.
+    ///
+    /// Pixels are averaged using the stored values, so sRGB data is not converted to linear first.
+    pub mipmap_filter: MipmapFilter,
+    /// How pixels past the edges are sampled when generating mipmaps.
+    pub mipmap_address_mode: AddressMode,
+    /// Generate mipmaps missing from the input data by downsampling the previous level
+    /// when using [Mipmaps::FromSurface].
+    ///
+    /// Each layer may omit the same number of trailing mipmaps.
+    /// The layers are stored one after the other with only the present mipmaps,
+    /// so only the base level of each layer needs to be present.
+    /// If this is `false`, input data without all mipmaps returns an error.
+    pub generate_missing_mipmaps: bool,
+    /// The layout of the input data for each mipmap.
+    pub input_alignment: InputAlignment,
+    /// The type of data stored in the surface.
+    ///
+    /// See [ContentHint] for how this affects encoding.
+    pub content_hint: ContentHint,
+}
+
+impl Default for EncodeOptions {
+    fn default() -> Self {
+        Self {
+            bc7_modes: 0xFF,
+            single_channel_source: SingleChannelSource::Red,
+            invert_single_channel: false,
+            strict_data_length: false,
+            mip_order: MipOrder::LargestFirst,
+            bc1_palette: None,
+            bc1_four_color: false,
+            alpha_block_mode: AlphaBlockMode::Automatic,
+            parallel_block_rows: 64,
+            skip_sub_block_mipmaps: false,
+            bc6_exposure: 0,
+            alpha_weighted_mipmaps: false,
+            mipmap_filter: MipmapFilter::Box,
+            mipmap_address_mode: AddressMode::Clamp,
+            generate_missing_mipmaps: false,
+            input_alignment: InputAlignment::Tight,
+            content_hint: ContentHint::Color,
+        }
+    }
+}
+
+impl EncodeOptions {
+    /// The scale of `2^bc6_exposure` applied to BC6H input values.
+    pub fn bc6_scale(&self) -> f32 {
+        2.0f32.powi(self.bc6_exposure as i32)
+    }
+
+    /// The scale of `2^-bc6_exposure` that restores the range of decoded BC6H values.
+    ///
+    /// Scaling by a power of two is exact, so this does not add any additional error.
+    pub fn bc6_inverse_scale(&self) -> f32 {
+        2.0f32.powi(-(self.bc6_exposure as i32))
+    }
+}
+
+/// Additional settings for decoding that most applications can leave as the default.
+///
+/// Fields may be added in future versions,
+/// so create options by modifying [DecodeOptions::default].
+#[non_exhaustive]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct DecodeOptions {
+    /// Threshold the decoded alpha to preview alpha tested materials.
+    ///
+    /// Alpha values below the cutoff are set to `0` and all other values are set to `255`.
+    /// The default of `None` leaves alpha unchanged.
+    pub alpha_cutoff: Option<u8>,
+}
+
+/// The source of the values when encoding RGBA data to a single channel format.
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "strum",
+    derive(strum::EnumString, strum::Display, strum::EnumIter)
+)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SingleChannelSource {
+    /// Use the red channel and ignore the other channels.
+    Red,
+    /// Use the green channel and ignore the other channels.
+    Green,
+    /// Use the blue channel and ignore the other channels.
+    Blue,
+    /// Use the luminance of the RGB channels calculated as `0.299 * R + 0.587 * G + 0.114 * B`.
+    Luminance,
+    /// Use the alpha channel and ignore the other channels.
+    Alpha,
+}
+
+/// The layout of the input RGBA data for each mipmap when encoding.
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "strum",
+    derive(strum::EnumString, strum::Display, strum::EnumIter)
+)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum InputAlignment {
+    /// Each mipmap has exactly the pixels in the surface dimensions.
+    /// The encoder pads the data to whole blocks as needed.
+    Tight,
+    /// Each mipmap is already padded to a multiple of the block dimensions of the output format.
+    ///
+    /// The data is used without any additional padding.
+    /// A 3x3 mipmap for BC7 would have 4x4 pixels of data.
+    /// See [SurfaceRgba8::pad_block_edges] for padding the base level.
+    AlreadyBlockAligned,
+}
+
+/// How to fill the pixels added when padding a surface to whole blocks.
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "strum",
+    derive(strum::EnumString, strum::Display, strum::EnumIter)
+)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum EdgePadding {
+    /// Fill with transparent black like the padding applied when encoding.
+    Zero,
+    /// Repeat the last row and column of pixels.
+    ///
+    /// This avoids darkening the colors of partially filled blocks.
+    Clamp,
+}
+
+/// The filter for generating each mipmap from the previous mipmap.
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "strum",
+    derive(strum::EnumString, strum::Display, strum::EnumIter)
+)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MipmapFilter {
+    /// Average each 2x2 region of pixels.
+    Box,
+    /// Weight a 4x4 region of pixels with a tent filter.
+    /// Samples past the edges follow [EncodeOptions::mipmap_address_mode].
+    ///
+    /// This is slightly softer than [MipmapFilter::Box] but reduces aliasing for fine detail.
+    Triangle,
+}
+
+/// How pixels outside the edges of a mipmap are sampled when generating the next mipmap.
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "strum",
+    derive(strum::EnumString, strum::Display, strum::EnumIter)
+)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum AddressMode {
+    /// Repeat the pixels at the edges.
+    ///
+    /// Samples outside the previous mipmap use the nearest pixel in the first or last row or column.
+    Clamp,
+    /// Sample pixels from the opposite edge.
+    ///
+    /// This keeps the mipmaps of tiling textures seamless.
+    Wrap,
+}
+
+impl AddressMode {
+    // The pixel sampled at index for an axis with size pixels.
+    fn sample(self, index: isize, size: usize) -> usize {
+        match self {
+            AddressMode::Clamp => index.clamp(0, size as isize - 1) as usize,
+            AddressMode::Wrap => index.rem_euclid(size as isize) as usize,
+        }
+    }
+}
+
+/// The interpolation mode for 8 byte alpha blocks in BC3 and BC4.
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "strum",
+    derive(strum::EnumString, strum::Display, strum::EnumIter)
+)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum AlphaBlockMode {
+    /// Let the encoder choose the mode for each block.
+    Automatic,
+    /// Interpolate 6 values between the endpoints for smoother gradients.
+    EightValues,
+    /// Interpolate 4 values between the endpoints and store exact values for `0` and `255`.
+    SixValues,
+}
+
+/// The type of data stored in a surface for choosing encoding optimizations.
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "strum",
+    derive(strum::EnumString, strum::Display, strum::EnumIter)
+)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ContentHint {
+    /// Color or other data without any special handling.
+    Color,
+    /// Tangent space normals with XYZ stored in RGB using the range `0.0` to `1.0`.
+    ///
+    /// The XYZ vectors of each mipmap are normalized to unit length before encoding
+    /// to block compressed formats. Vectors with a length close to zero are not modified.
+    /// Uncompressed formats store the input data without any changes.
+    ///
+    /// The BC1, BC2, and BC3 refinement for [Quality::Slow] weights errors in X and Y
+    /// more heavily than Z. Other formats like BC7 do not use this weighting
+    /// and are only affected by the normalization.
+    /// BC5 only stores X and Y and ignores the blue channel.
+    NormalMap,
+}
+
+/// A broad group of [ImageFormat] returned by [ImageFormat::category].
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "strum",
+    derive(strum::EnumString, strum::Display, strum::EnumIter)
+)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FormatCategory {
+    /// Uncompressed formats with values in the range `0.0` to `1.0` or `-1.0` to `1.0`.
+    Uncompressed,
+    /// Compressed formats like BC7 that store blocks of pixels.
+    BlockCompressed,
+    /// Floating point formats that can store values above `1.0` like BC6H.
+    Hdr,
 }
 
 /// Supported image formats for encoding and decoding.
 ///
 /// Not all DDS formats are supported,
-/// but all current variants for [ImageFormat] are supported by some version of DDS.
+/// and not all variants for [ImageFormat] are supported by DDS.
+///
+/// The `u32` value of each variant is its index in declaration order.
+/// New variants are only added to the end.
 #[non_exhaustive]
+#[repr(u32)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(
@@ -207,6 +570,16 @@ pub enum ImageFormat {
     Rg32Float,
     Rgb32Float,
     Bgr5A1Unorm,
+    BC1RgbUnorm,
+    BC1RgbUnormSrgb,
+    Rgb9e5Ufloat,
+    /// Packed unsigned floats with 11 bits for red and green and 10 bits for blue.
+    Rg11b10Float,
+    /// EAC R11
+    EacR11Unorm,
+    /// EAC RG11
+    EacRg11Unorm,
+    Rgb8Unorm,
 }
 
 impl ImageFormat {
@@ -215,6 +588,8 @@ impl ImageFormat {
         match self {
             ImageFormat::BC1RgbaUnorm => (4, 4, 1),
             ImageFormat::BC1RgbaUnormSrgb => (4, 4, 1),
+            ImageFormat::BC1RgbUnorm => (4, 4, 1),
+            ImageFormat::BC1RgbUnormSrgb => (4, 4, 1),
             ImageFormat::BC2RgbaUnorm => (4, 4, 1),
             ImageFormat::BC2RgbaUnormSrgb => (4, 4, 1),
             ImageFormat::BC3RgbaUnorm => (4, 4, 1),
@@ -227,10 +602,203 @@ impl ImageFormat {
             ImageFormat::BC6hRgbSfloat => (4, 4, 1),
             ImageFormat::BC7RgbaUnorm => (4, 4, 1),
             ImageFormat::BC7RgbaUnormSrgb => (4, 4, 1),
+            ImageFormat::EacR11Unorm => (4, 4, 1),
+            ImageFormat::EacRg11Unorm => (4, 4, 1),
             _ => (1, 1, 1),
         }
     }
 
+    /// Round the dimensions up to a multiple of the block dimensions.
+    ///
+    /// This is the physical size of the data for a `width` x `height` x `depth` surface.
+    /// Uncompressed formats use a block size of 1 pixel and are not padded.
+    /// ```rust
+    /// use image_dds::ImageFormat;
+    ///
+    /// assert_eq!((4, 8, 1), ImageFormat::BC7RgbaUnorm.block_align(1, 5, 1));
+    /// assert_eq!((1, 5, 1), ImageFormat::Rgba8Unorm.block_align(1, 5, 1));
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if a padded dimension does not fit in a [u32].
+    pub fn block_align(&self, width: u32, height: u32, depth: u32) -> (u32, u32, u32) {
+        let (block_width, block_height, block_depth) = self.block_dimensions();
+        block_align_dimensions(
+            (width as usize, height as usize, depth as usize),
+            (
+                block_width as usize,
+                block_height as usize,
+                block_depth as usize,
+            ),
+        )
+        .and_then(|(width, height, depth)| {
+            Some((
+                u32::try_from(width).ok()?,
+                u32::try_from(height).ok()?,
+                u32::try_from(depth).ok()?,
+            ))
+        })
+        .expect("padded dimensions should fit in u32")
+    }
+
+    /// Returns `true` if the format stores sRGB encoded color data.
+    ///
+    /// Decoding does not convert the color space,
+    /// so the decoded data for these formats is also sRGB encoded.
+    pub fn is_srgb(&self) -> bool {
+        matches!(
+            self,
+            ImageFormat::Rgba8UnormSrgb
+                | ImageFormat::Bgra8UnormSrgb
+                | ImageFormat::BC1RgbaUnormSrgb
+                | ImageFormat::BC1RgbUnormSrgb
+                | ImageFormat::BC2RgbaUnormSrgb
+                | ImageFormat::BC3RgbaUnormSrgb
+                | ImageFormat::BC7RgbaUnormSrgb
+        )
+    }
+
+    /// Returns `true` if the format stores floating point values that can exceed `1.0`.
+    ///
+    /// ```rust
+    /// use image_dds::ImageFormat;
+    ///
+    /// assert!(ImageFormat::BC6hRgbUfloat.is_hdr());
+    /// assert!(ImageFormat::Rg11b10Float.is_hdr());
+    /// assert!(!ImageFormat::Rgba16Unorm.is_hdr());
+    /// ```
+    pub fn is_hdr(&self) -> bool {
+        matches!(
+            self,
+            ImageFormat::R16Float
+                | ImageFormat::Rg16Float
+                | ImageFormat::Rgba16Float
+                | ImageFormat::R32Float
+                | ImageFormat::Rg32Float
+                | ImageFormat::Rgb32Float
+                | ImageFormat::Rgba32Float
+                | ImageFormat::BC6hRgbUfloat
+                | ImageFormat::BC6hRgbSfloat
+                | ImageFormat::Rgb9e5Ufloat
+                | ImageFormat::Rg11b10Float
+        )
+    }
+
+    /// The [FormatCategory] for grouping formats in user interfaces.
+    ///
+    /// HDR formats use [FormatCategory::Hdr] even if they are block compressed.
+    pub fn category(&self) -> FormatCategory {
+        if self.is_hdr() {
+            FormatCategory::Hdr
+        } else if self.block_dimensions() != (1, 1, 1) {
+            FormatCategory::BlockCompressed
+        } else {
+            FormatCategory::Uncompressed
+        }
+    }
+
+    /// A sensible default [Quality] for encoding to this format.
+    ///
+    /// BC6H and BC7 search many block modes and benefit from at least [Quality::Normal].
+    /// Other compressed formats like BC1 barely improve with slower settings and use [Quality::Fast].
+    /// Uncompressed formats that store RGBA8 data exactly use [Quality::Lossless].
+    /// This is only a suggestion, and any quality can still be used for encoding.
+    ///
+    /// The recommendation is for RGBA8 input like [SurfaceRgba8].
+    /// Use [ImageFormat::recommended_quality_rgbaf32] for floating point input.
+    ///
+    /// ```rust
+    /// use image_dds::{ImageFormat, Quality};
+    ///
+    /// assert_eq!(Quality::Normal, ImageFormat::BC7RgbaUnorm.recommended_quality());
+    /// assert_eq!(Quality::Fast, ImageFormat::BC1RgbaUnorm.recommended_quality());
+    /// assert_eq!(Quality::Lossless, ImageFormat::Rgba8Unorm.recommended_quality());
+    /// ```
+    pub fn recommended_quality(&self) -> Quality {
+        self.recommended_quality_lossless(self.is_lossless_rgba8())
+    }
+
+    /// A sensible default [Quality] like [ImageFormat::recommended_quality]
+    /// for floating point input like [SurfaceRgba32Float] or [SurfaceRgba16Float].
+    ///
+    /// Only [ImageFormat::Rgba32Float] stores floating point data exactly and uses [Quality::Lossless].
+    ///
+    /// ```rust
+    /// use image_dds::{ImageFormat, Quality};
+    ///
+    /// assert_eq!(Quality::Normal, ImageFormat::BC6hRgbUfloat.recommended_quality_rgbaf32());
+    /// assert_eq!(Quality::Fast, ImageFormat::Rgba8Unorm.recommended_quality_rgbaf32());
+    /// assert_eq!(Quality::Lossless, ImageFormat::Rgba32Float.recommended_quality_rgbaf32());
+    /// ```
+    pub fn recommended_quality_rgbaf32(&self) -> Quality {
+        self.recommended_quality_lossless(*self == ImageFormat::Rgba32Float)
+    }
+
+    fn recommended_quality_lossless(&self, is_lossless: bool) -> Quality {
+        match self {
+            ImageFormat::BC6hRgbUfloat
+            | ImageFormat::BC6hRgbSfloat
+            | ImageFormat::BC7RgbaUnorm
+            | ImageFormat::BC7RgbaUnormSrgb => Quality::Normal,
+            _ if is_lossless => Quality::Lossless,
+            _ => Quality::Fast,
+        }
+    }
+
+    // Formats that store all channels of RGBA8 data without any loss of precision.
+    pub(crate) fn is_lossless_rgba8(&self) -> bool {
+        matches!(
+            self,
+            ImageFormat::Rgba8Unorm
+                | ImageFormat::Rgba8UnormSrgb
+                | ImageFormat::Bgra8Unorm
+                | ImageFormat::Bgra8UnormSrgb
+                | ImageFormat::Rgba16Unorm
+                | ImageFormat::Rgba32Float
+        )
+    }
+
+    // Formats with more than 8 bits of precision for at least one channel.
+    #[cfg(feature = "encode")]
+    fn is_high_precision(&self) -> bool {
+        matches!(
+            self,
+            ImageFormat::R16Unorm
+                | ImageFormat::R16Snorm
+                | ImageFormat::Rg16Unorm
+                | ImageFormat::Rg16Snorm
+                | ImageFormat::Rgba16Unorm
+                | ImageFormat::Rgba16Snorm
+                | ImageFormat::R16Float
+                | ImageFormat::Rg16Float
+                | ImageFormat::Rgba16Float
+                | ImageFormat::R32Float
+                | ImageFormat::Rg32Float
+                | ImageFormat::Rgb32Float
+                | ImageFormat::Rgba32Float
+                | ImageFormat::BC6hRgbUfloat
+                | ImageFormat::BC6hRgbSfloat
+                | ImageFormat::Rgb9e5Ufloat
+                | ImageFormat::Rg11b10Float
+                | ImageFormat::EacR11Unorm
+                | ImageFormat::EacRg11Unorm
+        )
+    }
+
+    fn is_single_channel(&self) -> bool {
+        matches!(
+            self,
+            ImageFormat::R8Unorm
+                | ImageFormat::R8Snorm
+                | ImageFormat::R16Unorm
+                | ImageFormat::R16Snorm
+                | ImageFormat::R16Float
+                | ImageFormat::R32Float
+                | ImageFormat::BC4RUnorm
+                | ImageFormat::BC4RSnorm
+        )
+    }
+
     fn block_size_in_bytes(&self) -> usize {
         // Size of a block if compressed or pixel if uncompressed.
         match self {
@@ -273,6 +841,13 @@ impl ImageFormat {
             ImageFormat::Rgba8Snorm => 4,
             ImageFormat::Rgb32Float => 12,
             ImageFormat::Bgr5A1Unorm => 2,
+            ImageFormat::BC1RgbUnorm => 8,
+            ImageFormat::BC1RgbUnormSrgb => 8,
+            ImageFormat::Rgb9e5Ufloat => 4,
+            ImageFormat::Rg11b10Float => 4,
+            ImageFormat::EacR11Unorm => 8,
+            ImageFormat::EacRg11Unorm => 16,
+            ImageFormat::Rgb8Unorm => 3,
         }
     }
 }
@@ -282,12 +857,44 @@ fn max_mipmap_count(max_dimension: u32) -> u32 {
     u32::BITS - max_dimension.leading_zeros()
 }
 
+/// The number of mipmaps that keep `width`, `height`, and `depth` at least `min_dimension`.
+///
+/// Dimensions of `1` like the `depth` of 2D surfaces do not limit the count.
+/// The result is always at least `1` for the base level
+/// and can be used with [Mipmaps::GeneratedExact].
+///
+/// ```rust
+/// use image_dds::max_mipmap_count_to;
+///
+/// // 256x64, 128x32, 64x16
+/// assert_eq!(3, max_mipmap_count_to(256, 64, 1, 16));
+/// // The full mip chain down to 1x1.
+/// assert_eq!(9, max_mipmap_count_to(256, 64, 1, 1));
+/// ```
+pub fn max_mipmap_count_to(width: u32, height: u32, depth: u32, min_dimension: u32) -> u32 {
+    let max_mipmaps = max_mipmap_count(width.max(height).max(depth)).max(1);
+    let is_large_enough = |base_dimension: u32, mipmap: u32| {
+        base_dimension <= 1 || mip_dimension(base_dimension, mipmap) >= min_dimension
+    };
+
+    (1..max_mipmaps)
+        .take_while(|mipmap| {
+            is_large_enough(width, *mipmap)
+                && is_large_enough(height, *mipmap)
+                && is_large_enough(depth, *mipmap)
+        })
+        .count() as u32
+        + 1
+}
+
 /// The reduced value for `base_dimension` at level `mipmap`.
 pub fn mip_dimension(base_dimension: u32, mipmap: u32) -> u32 {
     // Halve for each mip level.
-    (base_dimension >> mipmap).max(1)
+    // Large mipmaps from untrusted input should not overflow the shift.
+    base_dimension.checked_shr(mipmap).unwrap_or(0).max(1)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn downsample_rgba<T: Channel>(
     new_width: usize,
     new_height: usize,
@@ -296,10 +903,77 @@ fn downsample_rgba<T: Channel>(
     height: usize,
     depth: usize,
     data: &[T],
+    address_mode: AddressMode,
+) -> Vec<T> {
+    downsample_rgba_by(
+        (new_width, new_height, new_depth),
+        (width, height, depth),
+        data,
+        average_pixels,
+        address_mode,
+    )
+}
+
+/// Downsample like [downsample_rgba] but weight the RGB channels by alpha.
+/// This prevents the color of transparent pixels from bleeding into visible pixels.
+#[cfg(feature = "encode")]
+#[allow(clippy::too_many_arguments)]
+fn downsample_rgba_alpha_weighted<T: Channel>(
+    new_width: usize,
+    new_height: usize,
+    new_depth: usize,
+    width: usize,
+    height: usize,
+    depth: usize,
+    data: &[T],
+    address_mode: AddressMode,
 ) -> Vec<T> {
+    downsample_rgba_by(
+        (new_width, new_height, new_depth),
+        (width, height, depth),
+        data,
+        average_pixels_alpha_weighted,
+        address_mode,
+    )
+}
+
+fn average_pixels(pixels: &[[f32; 4]; 8]) -> [f32; 4] {
+    std::array::from_fn(|c| pixels.iter().map(|p| p[c]).sum::<f32>() / 8.0)
+}
+
+#[cfg(feature = "encode")]
+fn average_pixels_alpha_weighted(pixels: &[[f32; 4]; 8]) -> [f32; 4] {
+    let alpha_sum: f32 = pixels.iter().map(|p| p[3].max(0.0)).sum();
+    if alpha_sum <= 0.0 {
+        // Fully transparent regions have no meaningful weights.
+        return average_pixels(pixels);
+    }
+
+    let mut average = [0.0; 4];
+    for c in 0..3 {
+        average[c] = pixels.iter().map(|p| p[c] * p[3].max(0.0)).sum::<f32>() / alpha_sum;
+    }
+    average[3] = pixels.iter().map(|p| p[3]).sum::<f32>() / 8.0;
+    average
+}
+
+fn downsample_rgba_by<T: Channel>(
+    new_dimensions: (usize, usize, usize),
+    dimensions: (usize, usize, usize),
+    data: &[T],
+    average: fn(&[[f32; 4]; 8]) -> [f32; 4],
+    address_mode: AddressMode,
+) -> Vec<T> {
+    let (new_width, new_height, new_depth) = new_dimensions;
+    let (width, height, depth) = dimensions;
+
     // Halve the width and height by averaging pixels.
     // This is faster than resizing using the image crate.
     let mut new_data = vec![T::ZERO; new_width * new_height * new_depth * 4];
+    if width == 0 || height == 0 || depth == 0 {
+        return new_data;
+    }
+
     for z in 0..new_depth {
         for x in 0..new_width {
             for y in 0..new_height {
@@ -307,30 +981,145 @@ fn downsample_rgba<T: Channel>(
 
                 // Average a 2x2x2 pixel region from data into a 1x1x1 pixel region.
                 // This is equivalent to a 3D convolution or pooling operation over the pixels.
-                for c in 0..4 {
-                    let mut sum = 0.0;
-                    let mut count = 0u64;
-                    for z2 in 0..2 {
-                        let sampled_z = (z * 2) + z2;
-                        if sampled_z < depth {
-                            for y2 in 0..2 {
-                                let sampled_y = (y * 2) + y2;
-                                if sampled_y < height {
-                                    for x2 in 0..2 {
-                                        let sampled_x = (x * 2) + x2;
-                                        if sampled_x < width {
-                                            let index = (sampled_z * width * height)
-                                                + (sampled_y * width)
-                                                + sampled_x;
-                                            sum += data[index * 4 + c].to_f32();
-                                            count += 1;
-                                        }
-                                    }
-                                }
+                // Clamp or wrap at the edge for odd dimensions and padding.
+                // Repeating edge pixels along an axis doesn't change the average.
+                let mut pixels = [[0.0; 4]; 8];
+                for z2 in 0..2 {
+                    let sampled_z = address_mode.sample((z * 2 + z2) as isize, depth);
+                    for y2 in 0..2 {
+                        let sampled_y = address_mode.sample((y * 2 + y2) as isize, height);
+                        for x2 in 0..2 {
+                            let sampled_x = address_mode.sample((x * 2 + x2) as isize, width);
+                            let index =
+                                (sampled_z * width * height) + (sampled_y * width) + sampled_x;
+                            debug_assert!(index * 4 + 3 < data.len());
+                            pixels[z2 * 4 + y2 * 2 + x2] =
+                                std::array::from_fn(|c| data[index * 4 + c].to_f32());
+                        }
+                    }
+                }
+
+                for (c, value) in average(&pixels).into_iter().enumerate() {
+                    new_data[new_index * 4 + c] = T::from_f32(value);
+                }
+            }
+        }
+    }
+
+    new_data
+}
+
+/// Downsample like [downsample_rgba] but use a 4x4 tent filter for each depth slice.
+/// This produces slightly softer mipmaps with less aliasing than a 2x2 box filter.
+#[cfg(feature = "encode")]
+#[allow(clippy::too_many_arguments)]
+fn downsample_rgba_triangle<T: Channel>(
+    new_width: usize,
+    new_height: usize,
+    new_depth: usize,
+    width: usize,
+    height: usize,
+    depth: usize,
+    data: &[T],
+    address_mode: AddressMode,
+) -> Vec<T> {
+    downsample_rgba_triangle_by(
+        (new_width, new_height, new_depth),
+        (width, height, depth),
+        data,
+        false,
+        address_mode,
+    )
+}
+
+/// Downsample like [downsample_rgba_triangle] but weight the RGB channels by alpha.
+#[cfg(feature = "encode")]
+#[allow(clippy::too_many_arguments)]
+fn downsample_rgba_triangle_alpha_weighted<T: Channel>(
+    new_width: usize,
+    new_height: usize,
+    new_depth: usize,
+    width: usize,
+    height: usize,
+    depth: usize,
+    data: &[T],
+    address_mode: AddressMode,
+) -> Vec<T> {
+    downsample_rgba_triangle_by(
+        (new_width, new_height, new_depth),
+        (width, height, depth),
+        data,
+        true,
+        address_mode,
+    )
+}
+
+#[cfg(feature = "encode")]
+fn downsample_rgba_triangle_by<T: Channel>(
+    new_dimensions: (usize, usize, usize),
+    dimensions: (usize, usize, usize),
+    data: &[T],
+    alpha_weighted: bool,
+    address_mode: AddressMode,
+) -> Vec<T> {
+    let (new_width, new_height, new_depth) = new_dimensions;
+    let (width, height, depth) = dimensions;
+
+    let mut new_data = vec![T::ZERO; new_width * new_height * new_depth * 4];
+    if width == 0 || height == 0 || depth == 0 {
+        return new_data;
+    }
+
+    // Sample pixels 2x-1 to 2x+2 with weights for a tent centered between 2x and 2x+1.
+    // Depth still uses a box filter since volume textures are rarely viewed along Z.
+    const WEIGHTS: [f32; 4] = [1.0, 3.0, 3.0, 1.0];
+    let sample = |i: usize, offset: usize, size: usize| {
+        address_mode.sample((i * 2 + offset) as isize - 1, size)
+    };
+
+    for z in 0..new_depth {
+        for y in 0..new_height {
+            for x in 0..new_width {
+                let mut sum = [0.0f32; 4];
+                let mut weight_sum = 0.0;
+                let mut weighted_rgb = [0.0f32; 3];
+                let mut alpha_weight_sum = 0.0;
+                for z2 in 0..2 {
+                    let sampled_z = address_mode.sample((z * 2 + z2) as isize, depth);
+                    for (y2, weight_y) in WEIGHTS.iter().enumerate() {
+                        let sampled_y = sample(y, y2, height);
+                        for (x2, weight_x) in WEIGHTS.iter().enumerate() {
+                            let sampled_x = sample(x, x2, width);
+                            let index =
+                                (sampled_z * width * height) + (sampled_y * width) + sampled_x;
+                            let pixel: [f32; 4] =
+                                std::array::from_fn(|c| data[index * 4 + c].to_f32());
+
+                            let weight = weight_x * weight_y;
+                            for c in 0..4 {
+                                sum[c] += pixel[c] * weight;
+                            }
+                            weight_sum += weight;
+
+                            let alpha_weight = weight * pixel[3].max(0.0);
+                            for c in 0..3 {
+                                weighted_rgb[c] += pixel[c] * alpha_weight;
                             }
+                            alpha_weight_sum += alpha_weight;
                         }
                     }
-                    new_data[new_index * 4 + c] = T::from_f32(sum / count.max(1) as f32);
+                }
+
+                let mut average = sum.map(|c| c / weight_sum);
+                if alpha_weighted && alpha_weight_sum > 0.0 {
+                    for c in 0..3 {
+                        average[c] = weighted_rgb[c] / alpha_weight_sum;
+                    }
+                }
+
+                let new_index = (z * new_width * new_height) + y * new_width + x;
+                for (c, value) in average.into_iter().enumerate() {
+                    new_data[new_index * 4 + c] = T::from_f32(value);
                 }
             }
         }
@@ -339,6 +1128,7 @@ fn downsample_rgba<T: Channel>(
     new_data
 }
 
+#[allow(clippy::too_many_arguments)]
 fn calculate_offset(
     layer: u32,
     depth_level: u32,
@@ -347,11 +1137,12 @@ fn calculate_offset(
     block_dimensions: (u32, u32, u32),
     block_size_in_bytes: usize,
     mipmaps_per_layer: u32,
+    layout: DataLayout,
+    mip_order: MipOrder,
 ) -> Option<usize> {
     // Surfaces typically use a row-major memory layout like surface[layer][mipmap][z][y][x].
     // Not all mipmaps are the same size, so the offset calculation is slightly more complex.
     let (width, height, depth) = dimensions;
-    let (block_width, block_height, block_depth) = block_dimensions;
 
     let mip_sizes = (0..mipmaps_per_layer)
         .map(|i| {
@@ -359,13 +1150,10 @@ fn calculate_offset(
             let mip_height = mip_dimension(height, i) as usize;
             let mip_depth = mip_dimension(depth, i) as usize;
 
-            mip_size(
-                mip_width,
-                mip_height,
-                mip_depth,
-                block_width as usize,
-                block_height as usize,
-                block_depth as usize,
+            layout_mip_size(
+                layout,
+                (mip_width, mip_height, mip_depth),
+                block_dimensions,
                 block_size_in_bytes,
             )
         })
@@ -374,27 +1162,50 @@ fn calculate_offset(
     // Each depth level adds another rounded 2D slice.
     let mip_width = mip_dimension(width, mipmap) as usize;
     let mip_height = mip_dimension(height, mipmap) as usize;
-    let mip_size2d = mip_size(
-        mip_width,
-        mip_height,
-        1,
-        block_width as usize,
-        block_height as usize,
-        block_depth as usize,
+    let mip_size2d = layout_mip_size(
+        layout,
+        (mip_width, mip_height, 1),
+        block_dimensions,
         block_size_in_bytes,
     )?;
 
-    // Assume mipmaps are tightly packed.
+    // Mipmaps are packed without padding between mipmaps.
     // This is the case for DDS surface data.
     let layer_size: usize = mip_sizes.iter().sum();
 
     // Each layer should have the same number of mipmaps.
     let layer_offset = layer as usize * layer_size;
-    let mip_offset: usize = mip_sizes.get(0..mipmap as usize)?.iter().sum();
+    let mip_offset: usize = match mip_order {
+        MipOrder::LargestFirst => mip_sizes.get(0..mipmap as usize)?.iter().sum(),
+        MipOrder::SmallestFirst => mip_sizes.get(mipmap as usize + 1..)?.iter().sum(),
+    };
     let depth_offset = mip_size2d * depth_level as usize;
     Some(layer_offset + mip_offset + depth_offset)
 }
 
+fn layout_mip_size(
+    layout: DataLayout,
+    dimensions: (usize, usize, usize),
+    block_dimensions: (u32, u32, u32),
+    block_size_in_bytes: usize,
+) -> Option<usize> {
+    let (width, _, _) = dimensions;
+    let (block_width, block_height, block_depth) = block_dimensions;
+    let block_dimensions = (
+        block_width as usize,
+        block_height as usize,
+        block_depth as usize,
+    );
+    let (_, height, depth) = block_align_dimensions(dimensions, block_dimensions)?;
+
+    // Padding only applies to each row of pixels or blocks.
+    let row_size = mip_size(width, 1, 1, block_width as usize, 1, 1, block_size_in_bytes)?;
+    layout
+        .row_pitch(row_size)?
+        .checked_mul(height / block_dimensions.1)?
+        .checked_mul(depth / block_dimensions.2)
+}
+
 fn mip_size(
     width: usize,
     height: usize,
@@ -404,11 +1215,29 @@ fn mip_size(
     block_depth: usize,
     block_size_in_bytes: usize,
 ) -> Option<usize> {
-    width
-        .div_ceil(block_width)
-        .checked_mul(height.div_ceil(block_height))
-        .and_then(|v| v.checked_mul(depth.div_ceil(block_depth)))
-        .and_then(|v| v.checked_mul(block_size_in_bytes))
+    let (width, height, depth) = block_align_dimensions(
+        (width, height, depth),
+        (block_width, block_height, block_depth),
+    )?;
+    (width / block_width)
+        .checked_mul(height / block_height)?
+        .checked_mul(depth / block_depth)?
+        .checked_mul(block_size_in_bytes)
+}
+
+// Round each dimension up to a multiple of the block dimensions.
+// This is the shared implementation for all padded size calculations.
+fn block_align_dimensions(
+    dimensions: (usize, usize, usize),
+    block_dimensions: (usize, usize, usize),
+) -> Option<(usize, usize, usize)> {
+    let (width, height, depth) = dimensions;
+    let (block_width, block_height, block_depth) = block_dimensions;
+    Some((
+        width.checked_next_multiple_of(block_width)?,
+        height.checked_next_multiple_of(block_height)?,
+        depth.checked_next_multiple_of(block_depth)?,
+    ))
 }
 
 #[cfg(test)]
@@ -430,6 +1259,84 @@ mod tests {
         assert_eq!(4, max_mipmap_count(12));
     }
 
+    #[test]
+    fn max_mipmap_count_to_min_dimension() {
+        assert_eq!(3, max_mipmap_count_to(256, 64, 1, 16));
+        assert_eq!(5, max_mipmap_count_to(256, 256, 1, 16));
+        assert_eq!(9, max_mipmap_count_to(256, 256, 1, 1));
+        assert_eq!(9, max_mipmap_count_to(256, 256, 1, 0));
+        // Surfaces already smaller than the minimum only have the base level.
+        assert_eq!(1, max_mipmap_count_to(8, 8, 1, 16));
+        assert_eq!(1, max_mipmap_count_to(128, 8, 1, 16));
+        assert_eq!(2, max_mipmap_count_to(32, 32, 32, 16));
+        assert_eq!(1, max_mipmap_count_to(0, 0, 0, 16));
+    }
+
+    #[test]
+    fn mip_dimension_large_mipmap() {
+        assert_eq!(1, mip_dimension(u32::MAX, 32));
+        assert_eq!(1, mip_dimension(4, u32::MAX));
+    }
+
+    #[test]
+    fn format_category() {
+        assert_eq!(FormatCategory::Hdr, ImageFormat::BC6hRgbSfloat.category());
+        assert_eq!(FormatCategory::Hdr, ImageFormat::Rgba32Float.category());
+        assert_eq!(
+            FormatCategory::BlockCompressed,
+            ImageFormat::BC7RgbaUnorm.category()
+        );
+        assert_eq!(
+            FormatCategory::BlockCompressed,
+            ImageFormat::EacR11Unorm.category()
+        );
+        assert_eq!(
+            FormatCategory::Uncompressed,
+            ImageFormat::Rgba16Unorm.category()
+        );
+        assert_eq!(
+            FormatCategory::Uncompressed,
+            ImageFormat::R8Snorm.category()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "encode")]
+    fn hdr_formats_are_high_precision() {
+        use strum::IntoEnumIterator;
+        for format in ImageFormat::iter().filter(ImageFormat::is_hdr) {
+            assert!(format.is_high_precision(), "{format:?}");
+        }
+    }
+
+    #[test]
+    fn recommended_quality_is_encodable() {
+        // Lossy formats should never recommend a lossless setting.
+        use strum::IntoEnumIterator;
+        for format in ImageFormat::iter() {
+            let quality = format.recommended_quality();
+            assert_eq!(
+                format.is_lossless_rgba8(),
+                quality == Quality::Lossless,
+                "{format:?}"
+            );
+            if format.block_dimensions() != (1, 1, 1) {
+                assert_ne!(Quality::Lossless, quality, "{format:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn bc6_scale_exposure() {
+        let options = EncodeOptions {
+            bc6_exposure: -3,
+            ..Default::default()
+        };
+        assert_eq!(0.125, options.bc6_scale());
+        assert_eq!(8.0, options.bc6_inverse_scale());
+        assert_eq!(1.0, EncodeOptions::default().bc6_scale());
+    }
+
     #[test]
     fn downsample_rgba8_4x4() {
         // Test that a checkerboard is averaged.
@@ -439,7 +1346,7 @@ mod tests {
             .collect();
         assert_eq!(
             vec![127u8; 2 * 2 * 1 * 4],
-            downsample_rgba(2, 2, 1, 4, 4, 1, &original)
+            downsample_rgba(2, 2, 1, 4, 4, 1, &original, AddressMode::Clamp)
         );
     }
 
@@ -454,10 +1361,32 @@ mod tests {
         .collect();
         assert_eq!(
             vec![127u8; 1 * 1 * 4],
-            downsample_rgba(1, 1, 1, 3, 3, 1, &original)
+            downsample_rgba(1, 1, 1, 3, 3, 1, &original, AddressMode::Clamp)
+        );
+    }
+
+    #[test]
+    fn downsample_rgba8_3x3_to_2x2() {
+        // Odd dimensions clamp to the last row and column.
+        let original: Vec<_> = (0..9u8).flat_map(|i| [i * 10; 4]).collect();
+        assert_eq!(
+            [[20u8; 4], [35u8; 4], [65u8; 4], [80u8; 4]].concat(),
+            downsample_rgba(2, 2, 1, 3, 3, 1, &original, AddressMode::Clamp)
         );
     }
 
+    #[test]
+    fn downsample_rgba8_3x3_to_1x1() {
+        // The box filter only samples the first 2x2 pixels for both address modes.
+        let original: Vec<_> = (0..9u8).flat_map(|i| [i * 10; 4]).collect();
+        for address_mode in [AddressMode::Clamp, AddressMode::Wrap] {
+            assert_eq!(
+                vec![20u8; 4],
+                downsample_rgba(1, 1, 1, 3, 3, 1, &original, address_mode)
+            );
+        }
+    }
+
     #[test]
     fn downsample_rgba8_2x2x2() {
         // Test that two slices of 2x2 pixels are averaged.
@@ -467,13 +1396,140 @@ mod tests {
         ];
         assert_eq!(
             vec![127u8; 1 * 1 * 1 * 4],
-            downsample_rgba(1, 1, 1, 2, 2, 2, &original)
+            downsample_rgba(1, 1, 1, 2, 2, 2, &original, AddressMode::Clamp)
         );
     }
 
     #[test]
     fn downsample_rgba8_0x0() {
-        assert_eq!(vec![0u8; 4], downsample_rgba(1, 1, 1, 0, 0, 1, &[]));
+        assert_eq!(
+            vec![0u8; 4],
+            downsample_rgba(1, 1, 1, 0, 0, 1, &[], AddressMode::Clamp)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "encode")]
+    fn downsample_rgba8_alpha_weighted() {
+        // A white pixel next to transparent black pixels.
+        let mut original = vec![0u8; 2 * 2 * 4];
+        original[..4].copy_from_slice(&[255, 255, 255, 255]);
+        assert_eq!(
+            vec![63u8, 63, 63, 63],
+            downsample_rgba(1, 1, 1, 2, 2, 1, &original, AddressMode::Clamp)
+        );
+        assert_eq!(
+            vec![255u8, 255, 255, 63],
+            downsample_rgba_alpha_weighted(1, 1, 1, 2, 2, 1, &original, AddressMode::Clamp)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "encode")]
+    fn downsample_rgba8_alpha_weighted_transparent() {
+        // Zero total alpha falls back to an unweighted average.
+        let original = [255u8, 255, 255, 0, 0, 0, 0, 0, 255, 255, 255, 0, 0, 0, 0, 0];
+        assert_eq!(
+            vec![127u8, 127, 127, 0],
+            downsample_rgba_alpha_weighted(1, 1, 1, 2, 2, 1, &original, AddressMode::Clamp)
+        );
+    }
+
+    #[test]
+    fn downsample_rgba8_3x1_wrap() {
+        let original = [0u8, 0, 0, 255, 255, 255, 255, 255, 255, 255, 255, 255];
+        assert_eq!(
+            vec![127, 127, 127, 255, 255, 255, 255, 255],
+            downsample_rgba(2, 1, 1, 3, 1, 1, &original, AddressMode::Clamp)
+        );
+        // The last pixel is averaged with the first pixel.
+        assert_eq!(
+            vec![127, 127, 127, 255, 127, 127, 127, 255],
+            downsample_rgba(2, 1, 1, 3, 1, 1, &original, AddressMode::Wrap)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "encode")]
+    fn downsample_rgba8_triangle_wrap() {
+        let original = [255u8, 0, 0, 0]
+            .into_iter()
+            .flat_map(|c| [c, c, c, 255])
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![127, 127, 127, 255],
+            downsample_rgba_triangle(2, 1, 1, 4, 1, 1, &original, AddressMode::Clamp)[..4].to_vec()
+        );
+        // The first pixel samples the last pixel instead of repeating itself.
+        assert_eq!(
+            vec![95, 95, 95, 255],
+            downsample_rgba_triangle(2, 1, 1, 4, 1, 1, &original, AddressMode::Wrap)[..4].to_vec()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "encode")]
+    fn downsample_rgba8_triangle_3x3_to_1x1() {
+        // Samples before the first pixel read the edge or the opposite side.
+        let original: Vec<_> = (0..9u8).flat_map(|i| [i * 10; 4]).collect();
+        assert_eq!(
+            vec![25u8; 4],
+            downsample_rgba_triangle(1, 1, 1, 3, 3, 1, &original, AddressMode::Clamp)
+        );
+        assert_eq!(
+            vec![35u8; 4],
+            downsample_rgba_triangle(1, 1, 1, 3, 3, 1, &original, AddressMode::Wrap)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "encode")]
+    fn downsample_rgba8_triangle_aliasing() {
+        // Stripes with a period of 4 pixels alias to full contrast with a box filter.
+        let original: Vec<_> = [0u8, 0, 255, 255]
+            .repeat(2)
+            .into_iter()
+            .flat_map(|c| [c, c, c, 255])
+            .collect();
+        let contrast = |data: Vec<u8>| {
+            let red = data.iter().step_by(4);
+            red.clone().max().unwrap() - red.min().unwrap()
+        };
+
+        let box_filtered = downsample_rgba(4, 1, 1, 8, 1, 1, &original, AddressMode::Clamp);
+        let triangle = downsample_rgba_triangle(4, 1, 1, 8, 1, 1, &original, AddressMode::Clamp);
+        assert_eq!(255, contrast(box_filtered));
+        assert_eq!(vec![31u8, 31, 31, 255], triangle[..4].to_vec());
+        assert!(contrast(triangle) < 255);
+    }
+
+    #[test]
+    #[cfg(feature = "encode")]
+    fn downsample_rgba8_triangle_constant() {
+        // Clamping at the edges preserves constant colors.
+        let original = [10u8, 20, 30, 40].repeat(3 * 3);
+        assert_eq!(
+            [10u8, 20, 30, 40].repeat(2 * 2),
+            downsample_rgba_triangle(2, 2, 1, 3, 3, 1, &original, AddressMode::Clamp)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "encode")]
+    fn downsample_rgba8_triangle_alpha_weighted() {
+        let mut original = vec![0u8; 2 * 2 * 4];
+        original[..4].copy_from_slice(&[255, 255, 255, 255]);
+        let downsampled = downsample_rgba_triangle_alpha_weighted(
+            1,
+            1,
+            1,
+            2,
+            2,
+            1,
+            &original,
+            AddressMode::Clamp,
+        );
+        assert_eq!(vec![255u8, 255, 255], downsampled[..3].to_vec());
     }
 
     #[test]
@@ -487,7 +1543,7 @@ mod tests {
         .collect();
         assert_eq!(
             vec![0.5; 2 * 2 * 1 * 4],
-            downsample_rgba(2, 2, 1, 4, 4, 1, &original)
+            downsample_rgba(2, 2, 1, 4, 4, 1, &original, AddressMode::Clamp)
         );
     }
 
@@ -503,7 +1559,7 @@ mod tests {
         .collect();
         assert_eq!(
             vec![0.5; 1 * 1 * 4],
-            downsample_rgba(1, 1, 1, 3, 3, 1, &original)
+            downsample_rgba(1, 1, 1, 3, 3, 1, &original, AddressMode::Clamp)
         );
     }
 
@@ -517,20 +1573,34 @@ mod tests {
         ];
         assert_eq!(
             vec![0.5; 1 * 1 * 1 * 4],
-            downsample_rgba(1, 1, 1, 2, 2, 2, &original)
+            downsample_rgba(1, 1, 1, 2, 2, 2, &original, AddressMode::Clamp)
         );
     }
 
     #[test]
     fn downsample_rgbaf32_0x0() {
-        assert_eq!(vec![0.0f32; 4], downsample_rgba(1, 1, 1, 0, 0, 1, &[]));
+        assert_eq!(
+            vec![0.0f32; 4],
+            downsample_rgba(1, 1, 1, 0, 0, 1, &[], AddressMode::Clamp)
+        );
     }
 
     #[test]
     fn calculate_offset_layer0_mip0() {
         assert_eq!(
             0,
-            calculate_offset(0, 0, 0, (8, 8, 8), (4, 4, 4), 16, 4).unwrap()
+            calculate_offset(
+                0,
+                0,
+                0,
+                (8, 8, 8),
+                (4, 4, 4),
+                16,
+                4,
+                DataLayout::Tight,
+                MipOrder::LargestFirst
+            )
+            .unwrap()
         );
     }
 
@@ -539,7 +1609,38 @@ mod tests {
         // The sum of the first 2 mipmaps.
         assert_eq!(
             128 + 16,
-            calculate_offset(0, 0, 2, (8, 8, 8), (4, 4, 4), 16, 4).unwrap()
+            calculate_offset(
+                0,
+                0,
+                2,
+                (8, 8, 8),
+                (4, 4, 4),
+                16,
+                4,
+                DataLayout::Tight,
+                MipOrder::LargestFirst
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn calculate_offset_layer0_mip2_smallest_first() {
+        // The sum of the last mipmap.
+        assert_eq!(
+            16,
+            calculate_offset(
+                0,
+                0,
+                2,
+                (8, 8, 8),
+                (4, 4, 4),
+                16,
+                4,
+                DataLayout::Tight,
+                MipOrder::SmallestFirst
+            )
+            .unwrap()
         );
     }
 
@@ -549,7 +1650,18 @@ mod tests {
         // Each mipmap must have at least a full block of data.
         assert_eq!(
             (128 + 16 + 16 + 16) * 2,
-            calculate_offset(2, 0, 0, (8, 8, 8), (4, 4, 4), 16, 4).unwrap()
+            calculate_offset(
+                2,
+                0,
+                0,
+                (8, 8, 8),
+                (4, 4, 4),
+                16,
+                4,
+                DataLayout::Tight,
+                MipOrder::LargestFirst
+            )
+            .unwrap()
         );
     }
 
@@ -559,7 +1671,18 @@ mod tests {
         // Each mipmap must have at least a full block of data.
         assert_eq!(
             (128 + 16 + 16 + 16) * 2 + 128 + 16,
-            calculate_offset(2, 0, 2, (8, 8, 8), (4, 4, 4), 16, 4).unwrap()
+            calculate_offset(
+                2,
+                0,
+                2,
+                (8, 8, 8),
+                (4, 4, 4),
+                16,
+                4,
+                DataLayout::Tight,
+                MipOrder::LargestFirst
+            )
+            .unwrap()
         );
     }
 
@@ -568,7 +1691,18 @@ mod tests {
         // Each 2D level is rounded up to 16x16 pixels.
         assert_eq!(
             16 * 16 * 2,
-            calculate_offset(0, 2, 0, (15, 15, 15), (4, 4, 4), 16, 1).unwrap()
+            calculate_offset(
+                0,
+                2,
+                0,
+                (15, 15, 15),
+                (4, 4, 4),
+                16,
+                1,
+                DataLayout::Tight,
+                MipOrder::LargestFirst
+            )
+            .unwrap()
         );
     }
 
@@ -577,7 +1711,39 @@ mod tests {
         // Each 2D level is 16x16 pixels.
         assert_eq!(
             16 * 16 * 3 * 4,
-            calculate_offset(0, 3, 0, (16, 16, 16), (1, 1, 1), 4, 1).unwrap()
+            calculate_offset(
+                0,
+                3,
+                0,
+                (16, 16, 16),
+                (1, 1, 1),
+                4,
+                1,
+                DataLayout::Tight,
+                MipOrder::LargestFirst
+            )
+            .unwrap()
         );
     }
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn types_are_send_sync() {
+        // Fails to compile if a field prevents sharing these types across threads.
+        assert_send_sync::<Surface<Vec<u8>>>();
+        assert_send_sync::<Surface<&[u8]>>();
+        assert_send_sync::<SurfaceRgba8<Vec<u8>>>();
+        assert_send_sync::<SurfaceRgba16Float<Vec<half::f16>>>();
+        assert_send_sync::<SurfaceRgba32Float<Vec<f32>>>();
+        assert_send_sync::<ImageFormat>();
+        assert_send_sync::<Quality>();
+        assert_send_sync::<Mipmaps>();
+        assert_send_sync::<EncodeOptions>();
+        assert_send_sync::<DecodeOptions>();
+        assert_send_sync::<SurfaceError>();
+        assert_send_sync::<CreateImageError>();
+        #[cfg(feature = "ddsfile")]
+        assert_send_sync::<CreateDdsError>();
+    }
 }
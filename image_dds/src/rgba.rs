@@ -48,6 +48,14 @@ pub struct Bgra4([u8; 2]);
 #[derive(Clone, Copy, Pod, Zeroable)]
 pub struct Bgr5A1([u8; 2]);
 
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct Rgb9e5([u8; 4]);
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct Rg11b10([u8; 4]);
+
 pub trait GetPixel {
     fn get_pixel(data: &[u8], index: usize) -> Self;
 }
@@ -88,7 +96,9 @@ pixel_impl!(
     Rgf32,
     Rgbf32,
     Rgbaf32,
-    Bgr5A1
+    Bgr5A1,
+    Rgb9e5,
+    Rg11b10
 );
 
 pub trait ToRgba<T> {
@@ -369,6 +379,57 @@ impl FromRgba<u8> for Bgr5A1 {
     }
 }
 
+impl ToRgba<f32> for Rgb9e5 {
+    fn to_rgba(self) -> [f32; 4] {
+        // The RGB channels share a single 5 bit exponent.
+        let [r, g, b] = rgb9e5_to_float(u32::from_le_bytes(self.0));
+        [r, g, b, 1.0]
+    }
+}
+
+impl FromRgba<f32> for Rgb9e5 {
+    fn from_rgba(rgba: [f32; 4]) -> Self {
+        Self(float_to_rgb9e5([rgba[0], rgba[1], rgba[2]]).to_le_bytes())
+    }
+}
+
+impl ToRgba<u8> for Rgb9e5 {
+    fn to_rgba(self) -> [u8; 4] {
+        ToRgba::<f32>::to_rgba(self).map(f32::to_unorm8)
+    }
+}
+
+impl FromRgba<u8> for Rgb9e5 {
+    fn from_rgba(rgba: [u8; 4]) -> Self {
+        Self::from_rgba(rgba.map(f32::from_unorm8))
+    }
+}
+
+impl ToRgba<f32> for Rg11b10 {
+    fn to_rgba(self) -> [f32; 4] {
+        let [r, g, b] = rg11b10_to_float(u32::from_le_bytes(self.0));
+        [r, g, b, 1.0]
+    }
+}
+
+impl FromRgba<f32> for Rg11b10 {
+    fn from_rgba(rgba: [f32; 4]) -> Self {
+        Self(float_to_rg11b10([rgba[0], rgba[1], rgba[2]]).to_le_bytes())
+    }
+}
+
+impl ToRgba<u8> for Rg11b10 {
+    fn to_rgba(self) -> [u8; 4] {
+        ToRgba::<f32>::to_rgba(self).map(f32::to_unorm8)
+    }
+}
+
+impl FromRgba<u8> for Rg11b10 {
+    fn from_rgba(rgba: [u8; 4]) -> Self {
+        Self::from_rgba(rgba.map(f32::from_unorm8))
+    }
+}
+
 pub fn encode_rgba<P, T>(width: u32, height: u32, data: &[T]) -> Result<Vec<u8>, SurfaceError>
 where
     P: FromRgba<T> + Pod,
@@ -394,6 +455,25 @@ where
         .collect::<Vec<_>>())
 }
 
+/// Decode `data` and write the RGBA result to `rgba` with a length of `width * height * 4`.
+pub fn decode_rgba_into<P, T>(
+    width: u32,
+    height: u32,
+    data: &[u8],
+    rgba: &mut [T],
+) -> Result<(), SurfaceError>
+where
+    P: GetPixel + ToRgba<T>,
+    T: Copy,
+{
+    validate_length(width, height, std::mem::size_of::<P>(), data)?;
+    debug_assert_eq!(width as usize * height as usize * 4, rgba.len());
+    for (i, pixel) in rgba.chunks_exact_mut(4).enumerate() {
+        pixel.copy_from_slice(&P::get_pixel(data, i).to_rgba());
+    }
+    Ok(())
+}
+
 fn validate_length<T>(
     width: u32,
     height: u32,
@@ -1183,4 +1263,53 @@ mod tests {
             decode_rgba::<Rgba16Snorm, f32>(1, 1, &[1, 128, 0, 0, 0, 128, 255, 127]).unwrap()
         );
     }
+
+    #[test]
+    fn rgbaf32_from_rgb9e5() {
+        assert_eq!(
+            vec![1.0, 0.5, 0.25, 1.0],
+            decode_rgba::<Rgb9e5, f32>(1, 1, &[0, 1, 1, 129]).unwrap()
+        );
+    }
+
+    #[test]
+    fn rgb9e5_from_rgbaf32() {
+        assert_eq!(
+            vec![0, 1, 1, 129],
+            encode_rgba::<Rgb9e5, f32>(1, 1, &[1.0, 0.5, 0.25, 0.0]).unwrap()
+        );
+    }
+
+    #[test]
+    fn rgba8_from_rgb9e5() {
+        assert_eq!(
+            vec![255, 127, 63, 255],
+            decode_rgba::<Rgb9e5, u8>(1, 1, &[0, 1, 1, 129]).unwrap()
+        );
+    }
+
+    #[test]
+    fn rgbaf32_from_rg11b10() {
+        // 1.0, 0.5, and 0.25 have mantissas of 0 and exponents of 15, 14, and 13.
+        assert_eq!(
+            vec![1.0, 0.5, 0.25, 1.0],
+            decode_rgba::<Rg11b10, f32>(1, 1, &[192, 3, 28, 104]).unwrap()
+        );
+    }
+
+    #[test]
+    fn rg11b10_from_rgbaf32() {
+        assert_eq!(
+            vec![192, 3, 28, 104],
+            encode_rgba::<Rg11b10, f32>(1, 1, &[1.0, 0.5, 0.25, 0.0]).unwrap()
+        );
+    }
+
+    #[test]
+    fn rgba8_from_rg11b10() {
+        assert_eq!(
+            vec![255, 127, 63, 255],
+            decode_rgba::<Rg11b10, u8>(1, 1, &[192, 3, 28, 104]).unwrap()
+        );
+    }
 }
@@ -200,10 +200,143 @@ pub fn unorm8_to_snorm16(x: u8) -> i16 {
     (((x as f32 / 255.0) * 2.0 - 1.0) * 32767.0).round() as i16
 }
 
+// https://learn.microsoft.com/en-us/windows/win32/direct3d10/d3d10-graphics-programming-guide-resources-data-conversion
+const RGB9E5_MANTISSA_BITS: i32 = 9;
+const RGB9E5_EXPONENT_BIAS: i32 = 15;
+const RGB9E5_MAX_EXPONENT: i32 = 31;
+
+pub fn float_to_rgb9e5(rgb: [f32; 3]) -> u32 {
+    // The largest value has a mantissa of 511 / 512 and the largest exponent.
+    let max_value = (511.0 / 512.0) * 2.0f32.powi(RGB9E5_MAX_EXPONENT - RGB9E5_EXPONENT_BIAS);
+    // NaN and negative values are clamped to 0.
+    let rgb = rgb.map(|c| if c > 0.0 { c.min(max_value) } else { 0.0 });
+
+    let max_channel = rgb[0].max(rgb[1]).max(rgb[2]);
+    let mut exponent = if max_channel > 0.0 {
+        (max_channel.log2().floor() as i32).max(-RGB9E5_EXPONENT_BIAS - 1)
+            + 1
+            + RGB9E5_EXPONENT_BIAS
+    } else {
+        0
+    };
+
+    // Rounding can overflow the mantissa, which requires the next exponent.
+    let mut scale = 2.0f32.powi(exponent - RGB9E5_EXPONENT_BIAS - RGB9E5_MANTISSA_BITS);
+    if (max_channel / scale + 0.5).floor() as i32 == 1 << RGB9E5_MANTISSA_BITS {
+        scale *= 2.0;
+        exponent += 1;
+    }
+
+    let [r, g, b] = rgb.map(|c| (c / scale + 0.5).floor() as u32);
+    r | (g << 9) | (b << 18) | ((exponent as u32) << 27)
+}
+
+pub fn rgb9e5_to_float(x: u32) -> [f32; 3] {
+    let exponent = (x >> 27) as i32;
+    let scale = 2.0f32.powi(exponent - RGB9E5_EXPONENT_BIAS - RGB9E5_MANTISSA_BITS);
+    [x & 0x1FF, (x >> 9) & 0x1FF, (x >> 18) & 0x1FF].map(|m| m as f32 * scale)
+}
+
+// The 11 and 10 bit floats share the exponent layout of f16 without a sign bit.
+// https://learn.microsoft.com/en-us/windows/win32/direct3d10/d3d10-graphics-programming-guide-resources-float-rules
+fn float_to_packed_float(x: f32, mantissa_bits: u32) -> u32 {
+    // The largest finite value has the largest mantissa and an exponent of 30.
+    let max_value = (2.0 - 0.5f32.powi(mantissa_bits as i32)) * 32768.0;
+    // NaN and negative values are clamped to 0.
+    let x = if x > 0.0 { x.min(max_value) } else { 0.0 };
+
+    // Round to nearest even when removing the extra mantissa bits.
+    let shift = 10 - mantissa_bits;
+    let bits = f16::from_f32(x).to_bits() as u32;
+    (bits + (1 << (shift - 1)) - 1 + ((bits >> shift) & 1)) >> shift
+}
+
+fn packed_float_to_float(x: u32, mantissa_bits: u32) -> f32 {
+    f16::from_bits((x << (10 - mantissa_bits)) as u16).to_f32()
+}
+
+pub fn float_to_rg11b10(rgb: [f32; 3]) -> u32 {
+    let [r, g, b] = rgb;
+    float_to_packed_float(r, 6)
+        | (float_to_packed_float(g, 6) << 11)
+        | (float_to_packed_float(b, 5) << 22)
+}
+
+pub fn rg11b10_to_float(x: u32) -> [f32; 3] {
+    [
+        packed_float_to_float(x & 0x7FF, 6),
+        packed_float_to_float((x >> 11) & 0x7FF, 6),
+        packed_float_to_float(x >> 22, 5),
+    ]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn rg11b10_exact_values() {
+        for rgb in [
+            [0.0, 0.0, 0.0],
+            [1.0, 0.5, 0.25],
+            [65024.0, 0.0078125, 64512.0],
+        ] {
+            assert_eq!(rgb, rg11b10_to_float(float_to_rg11b10(rgb)));
+        }
+    }
+
+    #[test]
+    fn rg11b10_clamp() {
+        assert_eq!(
+            [65024.0, 0.0, 0.0],
+            rg11b10_to_float(float_to_rg11b10([f32::INFINITY, -1.0, f32::NAN]))
+        );
+    }
+
+    #[test]
+    fn rg11b10_round_trip_error() {
+        // The relative error is limited by the 6 and 5 bit mantissas.
+        for rgb in [
+            [0.1f32, 0.2, 0.3],
+            [2.5, 1.75, 0.9],
+            [120.0, 64.5, 8.25],
+            [1500.0, 3.3, 0.02],
+        ] {
+            let result = rg11b10_to_float(float_to_rg11b10(rgb));
+            for (i, (expected, actual)) in rgb.iter().zip(result).enumerate() {
+                let mantissa_bits = if i == 2 { 5 } else { 6 };
+                assert!((expected - actual).abs() <= expected * 0.5f32.powi(mantissa_bits + 1));
+            }
+        }
+    }
+
+    #[test]
+    fn rgb9e5_exact_values() {
+        for rgb in [[0.0, 0.0, 0.0], [1.0, 0.5, 0.25], [65408.0, 0.0, 128.0]] {
+            assert_eq!(rgb, rgb9e5_to_float(float_to_rgb9e5(rgb)));
+        }
+    }
+
+    #[test]
+    fn rgb9e5_clamp() {
+        assert_eq!(
+            [65408.0, 0.0, 0.0],
+            rgb9e5_to_float(float_to_rgb9e5([f32::INFINITY, -1.0, f32::NAN]))
+        );
+    }
+
+    #[test]
+    fn rgb9e5_round_trip_error() {
+        // The relative error is limited by the 9 bit mantissa of the largest channel.
+        for rgb in [[0.1f32, 0.2, 0.3], [2.5, 1.75, 0.9], [120.0, 64.5, 8.25]] {
+            let max_channel = rgb[0].max(rgb[1]).max(rgb[2]);
+            let result = rgb9e5_to_float(float_to_rgb9e5(rgb));
+            for (expected, actual) in rgb.iter().zip(result) {
+                assert!((expected - actual).abs() <= max_channel / 256.0);
+            }
+        }
+    }
+
     fn snorm8_to_unorm8_reference(x: u8) -> u8 {
         // Remap [-1, 1] to [0, 1] to fit in an unsigned integer.
         ((snorm8_to_float(x) * 0.5 + 0.5) * 255.0).round() as u8
@@ -1,6 +1,10 @@
+use std::cmp::Ordering;
+
+use half::f16;
+
 use crate::{
-    calculate_offset, error::CreateImageError, max_mipmap_count, mip_dimension, mip_size,
-    ImageFormat, SurfaceError,
+    calculate_offset, error::CreateImageError, layout_mip_size, max_mipmap_count, mip_dimension,
+    EdgePadding, ImageFormat, SurfaceError,
 };
 
 /// A surface with an image format known at runtime.
@@ -16,7 +20,7 @@ pub struct Surface<T> {
     /// This should be `1` for 2D surfaces.
     pub depth: u32,
     /// The number of array layers in the surface.
-    /// This should be `1` for most surfaces and `6` for cube maps.
+    /// This should be `1` for most surfaces, `6` for cube maps, and `6 * N` for arrays of `N` cube maps.
     pub layers: u32,
     /// The number of mipmaps in the surface.
     /// This should be `1` if the surface has only the base mip level.
@@ -24,13 +28,141 @@ pub struct Surface<T> {
     pub mipmaps: u32,
     /// The format of the bytes in [data](#structfield.data).
     pub image_format: ImageFormat,
-    /// The combined image data ordered by layer and then mipmap without additional padding.
+    /// The padding used for [data](#structfield.data).
+    /// This should be [DataLayout::Tight] for most surfaces.
+    pub layout: DataLayout,
+    /// The order of the mipmaps for each layer in [data](#structfield.data).
+    /// This should be [MipOrder::LargestFirst] for most surfaces.
+    pub mip_order: MipOrder,
+    /// The combined image data ordered by layer and then mipmap.
     ///
     /// A surface with L layers and M mipmaps would have the following layout:
     /// Layer 0 Mip 0, Layer 0 Mip 1,  ..., Layer L-1 Mip M-1
+    ///
+    /// The mipmaps for each layer are reversed for [MipOrder::SmallestFirst].
     pub data: T,
 }
 
+/// The dimensions and format of a [Surface] without its data.
+///
+/// The C compatible layout allows passing surface descriptions across FFI.
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SurfaceInfo {
+    pub width: u32,
+    pub height: u32,
+    pub depth: u32,
+    pub layers: u32,
+    pub mipmaps: u32,
+    /// The value of the [ImageFormat] as `u32`.
+    pub image_format: u32,
+    /// The row alignment in bytes for [DataLayout::RowAligned] or `0` for [DataLayout::Tight].
+    pub row_alignment: u32,
+    /// `0` for [MipOrder::LargestFirst] or `1` for [MipOrder::SmallestFirst].
+    pub mip_order: u32,
+}
+
+/// The range and average of a single channel returned by [SurfaceRgba8::channel_stats].
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ChannelStats {
+    pub min: u8,
+    pub max: u8,
+    pub mean: f32,
+}
+
+impl ChannelStats {
+    /// Returns `true` if every pixel has the same value for this channel.
+    pub fn is_constant(&self) -> bool {
+        self.min == self.max
+    }
+}
+
+/// The location of a block within a surface.
+///
+/// Uncompressed formats use blocks of a single pixel.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct BlockLocation {
+    /// The array layer containing the block.
+    pub layer: u32,
+    /// The depth slice containing the block.
+    pub depth_level: u32,
+    /// The mipmap containing the block.
+    pub mipmap: u32,
+    /// The horizontal position of the block in blocks.
+    pub x: u32,
+    /// The vertical position of the block in blocks.
+    pub y: u32,
+}
+
+impl BlockLocation {
+    /// The cube index and face for the block's layer if the surface is a cube map array.
+    ///
+    /// Faces are ordered +X, -X, +Y, -Y, +Z, -Z with 6 faces for each cube.
+    /// See [Surface::get_cube_face].
+    pub fn cube_face(&self) -> (u32, u32) {
+        (self.layer / 6, self.layer % 6)
+    }
+}
+
+/// A single block of image data returned by [Surface::iter_blocks].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct BlockRef<'a> {
+    /// The location of the block within the surface.
+    pub location: BlockLocation,
+    /// The encoded bytes for the block with a length of the format's block size.
+    pub data: &'a [u8],
+}
+
+/// The padding for the image data of a [Surface].
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum DataLayout {
+    /// Rows, depth slices, mipmaps, and layers are packed without any padding.
+    /// This is the layout used for DDS files.
+    #[default]
+    Tight,
+    /// Each row of pixels or blocks starts at a multiple of `row_alignment` bytes.
+    /// The end of each row is padded to the row pitch.
+    /// There is no additional padding between depth slices, mipmaps, or layers.
+    RowAligned { row_alignment: u32 },
+}
+
+impl DataLayout {
+    /// The number of bytes from the start of one row to the start of the next row
+    /// for rows with `row_size` bytes of image data.
+    pub fn row_pitch(&self, row_size: usize) -> Option<usize> {
+        match self {
+            DataLayout::Tight => Some(row_size),
+            DataLayout::RowAligned { row_alignment } => {
+                row_size.checked_next_multiple_of((*row_alignment).max(1) as usize)
+            }
+        }
+    }
+}
+
+/// The order of the mipmaps within each layer of a [Surface].
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum MipOrder {
+    /// The base mip level is stored first. This is the order used for DDS files.
+    #[default]
+    LargestFirst,
+    /// The smallest mip level is stored first. This is the order used for KTX2 files.
+    SmallestFirst,
+}
+
+impl MipOrder {
+    /// The mipmap indices in the order they are stored.
+    pub(crate) fn mipmaps(&self, mipmaps: u32) -> Vec<u32> {
+        match self {
+            MipOrder::LargestFirst => (0..mipmaps).collect(),
+            MipOrder::SmallestFirst => (0..mipmaps).rev().collect(),
+        }
+    }
+}
+
 impl<T: AsRef<[u8]>> Surface<T> {
     /// Get the range of image data corresponding to the specified `layer`, `depth_level`, and `mipmap`.
     ///
@@ -42,14 +174,262 @@ impl<T: AsRef<[u8]>> Surface<T> {
             (self.width, self.height, self.depth),
             self.mipmaps,
             self.image_format,
+            self.layout,
+            self.mip_order,
             layer,
             depth_level,
             mipmap,
         )
     }
 
+    /// Get the range of image data corresponding to the specified `face` of a cube map.
+    ///
+    /// Faces are ordered +X, -X, +Y, -Y, +Z, -Z and have the layer `6 * cube_index + face`.
+    /// Returns [None] if the face or cube is out of range or the expected range is not fully contained within the buffer.
+    pub fn get_cube_face(&self, cube_index: u32, face: u32, mipmap: u32) -> Option<&[u8]> {
+        let layer = cube_face_layer(self.layers, cube_index, face)?;
+        self.get(layer, 0, mipmap)
+    }
+
+    /// The number of complete cube maps with 6 faces for each cube.
+    ///
+    /// Any remaining layers that do not form a complete cube map are not counted.
+    pub fn cube_count(&self) -> u32 {
+        self.layers / 6
+    }
+
+    /// Iterate over the blocks of all layers, mipmaps, and depth slices in the order they are stored.
+    ///
+    /// Uncompressed formats use blocks of a single pixel.
+    /// Any row padding is skipped, and mipmaps that are not fully contained within the data are ignored.
+    pub fn iter_blocks(&self) -> impl Iterator<Item = BlockRef<'_>> + '_ {
+        let (block_width, block_height, _) = self.image_format.block_dimensions();
+        let block_size = self.image_format.block_size_in_bytes();
+
+        (0..self.layers).flat_map(move |layer| {
+            (0..self.mipmaps).flat_map(move |mipmap| {
+                let blocks_wide = mip_dimension(self.width, mipmap).div_ceil(block_width);
+                let blocks_high = mip_dimension(self.height, mipmap).div_ceil(block_height);
+                let pitch = self
+                    .layout
+                    .row_pitch(blocks_wide as usize * block_size)
+                    .unwrap_or_default();
+
+                (0..mip_dimension(self.depth, mipmap)).flat_map(move |depth_level| {
+                    let data = self.get(layer, depth_level, mipmap).unwrap_or_default();
+                    (0..blocks_high)
+                        .flat_map(move |y| (0..blocks_wide).map(move |x| (x, y)))
+                        .map_while(move |(x, y)| {
+                            let start = y as usize * pitch + x as usize * block_size;
+                            Some(BlockRef {
+                                location: BlockLocation {
+                                    layer,
+                                    depth_level,
+                                    mipmap,
+                                    x,
+                                    y,
+                                },
+                                data: data.get(start..start + block_size)?,
+                            })
+                        })
+                })
+            })
+        })
+    }
+
+    /// Returns `true` if the surface data is already in the given `format`.
+    pub fn format_matches(&self, format: ImageFormat) -> bool {
+        self.image_format == format
+    }
+
+    /// The width, height, and depth in pixels of the given `mipmap`.
+    ///
+    /// Each dimension is halved for each mipmap and is at least `1`.
+    pub fn dimensions_at_mip(&self, mipmap: u32) -> (u32, u32, u32) {
+        (
+            mip_dimension(self.width, mipmap),
+            mip_dimension(self.height, mipmap),
+            mip_dimension(self.depth, mipmap),
+        )
+    }
+
+    /// The width, height, and depth in pixels of the given `mipmap`
+    /// rounded up to a multiple of the block dimensions for the format.
+    ///
+    /// This is the size of the data actually stored for the mipmap.
+    pub fn padded_dimensions_at_mip(&self, mipmap: u32) -> (u32, u32, u32) {
+        let (width, height, depth) = self.dimensions_at_mip(mipmap);
+        self.image_format.block_align(width, height, depth)
+    }
+
+    /// The dimensions and format of the surface for use with FFI.
+    pub fn info(&self) -> SurfaceInfo {
+        SurfaceInfo {
+            width: self.width,
+            height: self.height,
+            depth: self.depth,
+            layers: self.layers,
+            mipmaps: self.mipmaps,
+            image_format: self.image_format as u32,
+            row_alignment: match self.layout {
+                DataLayout::Tight => 0,
+                DataLayout::RowAligned { row_alignment } => row_alignment,
+            },
+            mip_order: match self.mip_order {
+                MipOrder::LargestFirst => 0,
+                MipOrder::SmallestFirst => 1,
+            },
+        }
+    }
+
+    /// Calculate a hash of the dimensions, format, layout, and data for use as a cache key.
+    ///
+    /// The hash uses 64-bit FNV-1a and is stable across runs and platforms
+    /// but may change between versions of image_dds.
+    pub fn checksum(&self) -> u64 {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+        const FNV_PRIME: u64 = 0x100000001b3;
+
+        // The same bytes can represent different images with a different layout or order.
+        let (layout, row_alignment) = match self.layout {
+            DataLayout::Tight => (0, 0),
+            DataLayout::RowAligned { row_alignment } => (1, row_alignment),
+        };
+        let mip_order = match self.mip_order {
+            MipOrder::LargestFirst => 0,
+            MipOrder::SmallestFirst => 1,
+        };
+        let header = [
+            self.width,
+            self.height,
+            self.depth,
+            self.layers,
+            self.mipmaps,
+            self.image_format as u32,
+            layout,
+            row_alignment,
+            mip_order,
+        ];
+
+        header
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .chain(self.data.as_ref().iter().copied())
+            .fold(FNV_OFFSET_BASIS, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+            })
+    }
+
+    /// Copy the data to a new surface with the given `layout`.
+    ///
+    /// Use [DataLayout::Tight] to remove any row padding before saving or uploading the data.
+    pub fn with_layout(&self, layout: DataLayout) -> Result<Surface<Vec<u8>>, SurfaceError> {
+        self.repack(layout, self.mip_order)
+    }
+
+    /// Copy the data to a new surface with the mipmaps for each layer in the given `mip_order`.
+    pub fn with_mip_order(&self, mip_order: MipOrder) -> Result<Surface<Vec<u8>>, SurfaceError> {
+        self.repack(self.layout, mip_order)
+    }
+
+    pub(crate) fn repack(
+        &self,
+        layout: DataLayout,
+        mip_order: MipOrder,
+    ) -> Result<Surface<Vec<u8>>, SurfaceError> {
+        self.validate_base()?;
+
+        let (block_width, _, _) = self.image_format.block_dimensions();
+        let block_size_in_bytes = self.image_format.block_size_in_bytes();
+
+        let mut data = Vec::new();
+        for layer in 0..self.layers {
+            for mipmap in mip_order.mipmaps(self.mipmaps) {
+                let width = mip_dimension(self.width, mipmap) as usize;
+                let depth = mip_dimension(self.depth, mipmap);
+
+                let row_size = layout_mip_size(
+                    DataLayout::Tight,
+                    (width, 1, 1),
+                    (block_width, 1, 1),
+                    block_size_in_bytes,
+                )
+                .ok_or(SurfaceError::MipmapDataOutOfBounds { layer, mipmap })?;
+                let src_pitch = self
+                    .layout
+                    .row_pitch(row_size)
+                    .ok_or(SurfaceError::MipmapDataOutOfBounds { layer, mipmap })?;
+                let dst_pitch = layout
+                    .row_pitch(row_size)
+                    .ok_or(SurfaceError::MipmapDataOutOfBounds { layer, mipmap })?;
+
+                for level in 0..depth {
+                    let slice = self
+                        .get(layer, level, mipmap)
+                        .ok_or(SurfaceError::MipmapDataOutOfBounds { layer, mipmap })?;
+
+                    for row in slice.chunks_exact(src_pitch) {
+                        data.extend_from_slice(&row[..row_size]);
+                        data.resize(data.len() + dst_pitch - row_size, 0u8);
+                    }
+                }
+            }
+        }
+
+        Ok(Surface {
+            width: self.width,
+            height: self.height,
+            depth: self.depth,
+            layers: self.layers,
+            mipmaps: self.mipmaps,
+            image_format: self.image_format,
+            layout,
+            mip_order,
+            data,
+        })
+    }
+
+    /// Check that the dimensions, layers, and mipmaps describe a valid surface
+    /// and that [data](#structfield.data) has exactly the expected length
+    /// for the format, layout, layers, and mipmaps.
+    ///
+    /// Surfaces returned by [Surface::from_layers], [Surface::with_layout],
+    /// [Surface::with_mip_order], and encoding are always valid.
+    /// Surfaces from [Surface::from_dds] or constructed directly
+    /// may have missing or trailing data and should be checked before use.
+    ///
+    /// Decoding and other methods only require the data to be large enough,
+    /// so this check is stricter than necessary for those operations.
+    pub fn validate(&self) -> Result<(), SurfaceError> {
+        self.validate_base()?;
+
+        let expected = calculate_offset(
+            self.layers,
+            0,
+            0,
+            (self.width, self.height, self.depth),
+            self.image_format.block_dimensions(),
+            self.image_format.block_size_in_bytes(),
+            self.mipmaps,
+            self.layout,
+            MipOrder::LargestFirst,
+        )
+        .ok_or(SurfaceError::PixelCountWouldOverflow {
+            width: self.width,
+            height: self.height,
+            depth: self.depth,
+        })?;
+
+        let actual = self.data.as_ref().len();
+        match actual.cmp(&expected) {
+            Ordering::Less => Err(SurfaceError::NotEnoughData { expected, actual }),
+            Ordering::Greater => Err(SurfaceError::UnexpectedDataLength { expected, actual }),
+            Ordering::Equal => Ok(()),
+        }
+    }
+
     // TODO: Add tests for each of these cases.
-    pub(crate) fn validate(&self) -> Result<(), SurfaceError> {
+    pub(crate) fn validate_base(&self) -> Result<(), SurfaceError> {
         if self.width == 0 || self.height == 0 || self.depth == 0 {
             return Err(SurfaceError::ZeroSizedSurface {
                 width: self.width,
@@ -58,6 +438,14 @@ impl<T: AsRef<[u8]>> Surface<T> {
             });
         }
 
+        // An empty surface is likely from an incorrectly calculated layer or mipmap count.
+        if self.layers == 0 || self.mipmaps == 0 {
+            return Err(SurfaceError::ZeroLayersOrMipmaps {
+                layers: self.layers,
+                mipmaps: self.mipmaps,
+            });
+        }
+
         let max_mipmaps = max_mipmap_count(self.width.max(self.height).max(self.depth));
         if self.mipmaps > max_mipmaps {
             return Err(SurfaceError::UnexpectedMipmapCount {
@@ -66,15 +454,15 @@ impl<T: AsRef<[u8]>> Surface<T> {
             });
         }
 
-        let (block_width, block_height, block_depth) = self.image_format.block_dimensions();
         let block_size_in_bytes = self.image_format.block_size_in_bytes();
-        let base_layer_size = mip_size(
-            self.width as usize,
-            self.height as usize,
-            self.depth as usize,
-            block_width as usize,
-            block_height as usize,
-            block_depth as usize,
+        let base_layer_size = layout_mip_size(
+            self.layout,
+            (
+                self.width as usize,
+                self.height as usize,
+                self.depth as usize,
+            ),
+            self.image_format.block_dimensions(),
             block_size_in_bytes,
         )
         .ok_or(SurfaceError::PixelCountWouldOverflow {
@@ -97,6 +485,25 @@ impl<T: AsRef<[u8]>> Surface<T> {
     }
 }
 
+impl<T: Into<Vec<u8>>> Surface<T> {
+    /// Convert to a surface that owns its data.
+    ///
+    /// Borrowed data like `&[u8]` is copied. Owned data like `Vec<u8>` is moved without copying.
+    pub fn into_owned(self) -> Surface<Vec<u8>> {
+        Surface {
+            width: self.width,
+            height: self.height,
+            depth: self.depth,
+            layers: self.layers,
+            mipmaps: self.mipmaps,
+            image_format: self.image_format,
+            layout: self.layout,
+            mip_order: self.mip_order,
+            data: self.data.into(),
+        }
+    }
+}
+
 impl<T> Surface<Vec<T>> {
     /// Convert to a surface with borrowed data.
     pub fn as_ref(&self) -> Surface<&[T]> {
@@ -107,11 +514,97 @@ impl<T> Surface<Vec<T>> {
             layers: self.layers,
             mipmaps: self.mipmaps,
             image_format: self.image_format,
+            layout: self.layout,
+            mip_order: self.mip_order,
             data: self.data.as_ref(),
         }
     }
 }
 
+impl Surface<Vec<u8>> {
+    /// Combine the array layers of each surface in `surfaces` into a single array surface.
+    ///
+    /// All surfaces must have the same format, dimensions, mipmaps, layout, and mip order.
+    /// This can be used to create a cube map from six separately encoded faces.
+    pub fn from_layers<T: AsRef<[u8]>>(surfaces: &[Surface<T>]) -> Result<Self, SurfaceError> {
+        let first = surfaces.first().ok_or(SurfaceError::ZeroLayersOrMipmaps {
+            layers: 0,
+            mipmaps: 0,
+        })?;
+
+        let mut layers = 0u32;
+        let mut data = Vec::new();
+        for (index, surface) in surfaces.iter().enumerate() {
+            if (
+                surface.width,
+                surface.height,
+                surface.depth,
+                surface.mipmaps,
+                surface.image_format,
+                surface.layout,
+                surface.mip_order,
+            ) != (
+                first.width,
+                first.height,
+                first.depth,
+                first.mipmaps,
+                first.image_format,
+                first.layout,
+                first.mip_order,
+            ) {
+                return Err(SurfaceError::MismatchedLayer { index });
+            }
+            surface.validate_base()?;
+
+            // Exclude any trailing data so the next surface's layers start at the expected offset.
+            let size = calculate_offset(
+                surface.layers,
+                0,
+                0,
+                (surface.width, surface.height, surface.depth),
+                surface.image_format.block_dimensions(),
+                surface.image_format.block_size_in_bytes(),
+                surface.mipmaps,
+                surface.layout,
+                MipOrder::LargestFirst,
+            )
+            .ok_or(SurfaceError::PixelCountWouldOverflow {
+                width: surface.width,
+                height: surface.height,
+                depth: surface.depth,
+            })?;
+            let surface_data = surface.data.as_ref();
+            let surface_data = surface_data
+                .get(..size)
+                .ok_or(SurfaceError::NotEnoughData {
+                    expected: size,
+                    actual: surface_data.len(),
+                })?;
+
+            data.extend_from_slice(surface_data);
+            layers = layers.checked_add(surface.layers).ok_or(
+                SurfaceError::PixelCountWouldOverflow {
+                    width: surface.width,
+                    height: surface.height,
+                    depth: surface.depth,
+                },
+            )?;
+        }
+
+        Ok(Surface {
+            width: first.width,
+            height: first.height,
+            depth: first.depth,
+            layers,
+            mipmaps: first.mipmaps,
+            image_format: first.image_format,
+            layout: first.layout,
+            mip_order: first.mip_order,
+            data,
+        })
+    }
+}
+
 /// An uncompressed [ImageFormat::Rgba8Unorm] surface with 4 bytes per pixel.
 #[derive(Debug, PartialEq, Clone, Copy)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
@@ -125,7 +618,7 @@ pub struct SurfaceRgba8<T> {
     /// This should be `1` for 2D surfaces.
     pub depth: u32,
     /// The number of array layers in the surface.
-    /// This should be `1` for most surfaces and `6` for cube maps.
+    /// This should be `1` for most surfaces, `6` for cube maps, and `6 * N` for arrays of `N` cube maps.
     pub layers: u32,
     /// The number of mipmaps in the surface.
     /// This should be `1` if the surface has only the base mip level.
@@ -152,6 +645,20 @@ impl<T> SurfaceRgba8<Vec<T>> {
     }
 }
 
+impl<T: Clone> SurfaceRgba8<&[T]> {
+    /// Convert to a surface that owns a copy of the data.
+    pub fn into_owned(self) -> SurfaceRgba8<Vec<T>> {
+        SurfaceRgba8 {
+            width: self.width,
+            height: self.height,
+            depth: self.depth,
+            layers: self.layers,
+            mipmaps: self.mipmaps,
+            data: self.data.to_vec(),
+        }
+    }
+}
+
 impl<T: AsRef<[u8]>> SurfaceRgba8<T> {
     /// Get the range of 2D image data corresponding to the specified `layer`, `depth_level`, and `mipmap`.
     ///
@@ -163,12 +670,23 @@ impl<T: AsRef<[u8]>> SurfaceRgba8<T> {
             (self.width, self.height, self.depth),
             self.mipmaps,
             ImageFormat::Rgba8Unorm,
+            DataLayout::Tight,
+            MipOrder::LargestFirst,
             layer,
             depth_level,
             mipmap,
         )
     }
 
+    /// Get the range of 2D image data corresponding to the specified `face` of a cube map.
+    ///
+    /// Faces are ordered +X, -X, +Y, -Y, +Z, -Z and have the layer `6 * cube_index + face`.
+    /// Returns [None] if the face or cube is out of range or the expected range is not fully contained within the buffer.
+    pub fn get_cube_face(&self, cube_index: u32, face: u32, mipmap: u32) -> Option<&[u8]> {
+        let layer = cube_face_layer(self.layers, cube_index, face)?;
+        self.get(layer, 0, mipmap)
+    }
+
     /// Get the image corresponding to the specified `layer`, `depth_level`, and `mipmap`.
     ///
     /// Returns [None] if the expected range is not fully contained within the buffer.
@@ -191,9 +709,262 @@ impl<T: AsRef<[u8]>> SurfaceRgba8<T> {
             layers: self.layers,
             mipmaps: self.mipmaps,
             image_format: ImageFormat::Rgba8Unorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
             data: self.data.as_ref(),
         }
-        .validate()
+        .validate_base()
+    }
+
+    // The data for all layers and mipmaps without any trailing bytes.
+    fn pixel_data(&self) -> Result<&[u8], SurfaceError> {
+        self.validate()?;
+
+        let size = calculate_offset(
+            self.layers,
+            0,
+            0,
+            (self.width, self.height, self.depth),
+            (1, 1, 1),
+            4,
+            self.mipmaps,
+            DataLayout::Tight,
+            MipOrder::LargestFirst,
+        )
+        .ok_or(SurfaceError::PixelCountWouldOverflow {
+            width: self.width,
+            height: self.height,
+            depth: self.depth,
+        })?;
+
+        let data = self.data.as_ref();
+        data.get(..size).ok_or(SurfaceError::NotEnoughData {
+            expected: size,
+            actual: data.len(),
+        })
+    }
+
+    /// Check that the data contains all layers, depth slices, and mipmaps before encoding.
+    ///
+    /// Only the base level is required if missing mipmaps will be generated.
+    #[cfg(feature = "encode")]
+    pub(crate) fn validate_encode(
+        &self,
+        format: ImageFormat,
+        options: &crate::EncodeOptions,
+    ) -> Result<(), SurfaceError> {
+        self.validate()?;
+        validate_rgba_length(
+            (self.width, self.height, self.depth),
+            input_block_dimensions(format, options),
+            self.layers,
+            if options.generate_missing_mipmaps {
+                1
+            } else {
+                self.mipmaps
+            },
+            options.strict_data_length.then_some(self.mipmaps),
+            self.data.as_ref().len(),
+        )
+    }
+
+    /// Create a surface after checking that `data` has exactly the expected length
+    /// for the dimensions, layers, and mipmaps.
+    pub fn from_raw(
+        width: u32,
+        height: u32,
+        depth: u32,
+        layers: u32,
+        mipmaps: u32,
+        data: T,
+    ) -> Result<Self, SurfaceError> {
+        let surface = SurfaceRgba8 {
+            width,
+            height,
+            depth,
+            layers,
+            mipmaps,
+            data,
+        };
+        surface.validate()?;
+
+        // The total size is the offset of the layer after the last layer.
+        let expected = calculate_offset(
+            layers,
+            0,
+            0,
+            (width, height, depth),
+            (1, 1, 1),
+            4,
+            mipmaps,
+            DataLayout::Tight,
+            MipOrder::LargestFirst,
+        )
+        .ok_or(SurfaceError::PixelCountWouldOverflow {
+            width,
+            height,
+            depth,
+        })?;
+
+        let actual = surface.data.as_ref().len();
+        match actual.cmp(&expected) {
+            Ordering::Less => Err(SurfaceError::NotEnoughData { expected, actual }),
+            Ordering::Greater => Err(SurfaceError::UnexpectedDataLength { expected, actual }),
+            Ordering::Equal => Ok(surface),
+        }
+    }
+}
+
+impl<T: AsRef<[u8]>> SurfaceRgba8<T> {
+    /// Copy the region of the base mip level with the given pixel position and dimensions
+    /// for all layers and depth levels.
+    pub fn crop(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<SurfaceRgba8<Vec<u8>>, SurfaceError> {
+        self.crop_mipmaps_range(x, y, width, height, 1)
+    }
+
+    /// Copy the region with the given pixel position and dimensions for all mipmaps.
+    ///
+    /// The region is halved for each mip level to match [mip_dimension].
+    /// The result has the same number of mipmaps as the original surface
+    /// unless the region is too small to have that many mipmaps.
+    pub fn crop_mipmaps(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<SurfaceRgba8<Vec<u8>>, SurfaceError> {
+        let max_mipmaps = max_mipmap_count(width.max(height).max(self.depth));
+        self.crop_mipmaps_range(x, y, width, height, self.mipmaps.min(max_mipmaps))
+    }
+
+    fn crop_mipmaps_range(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        mipmaps: u32,
+    ) -> Result<SurfaceRgba8<Vec<u8>>, SurfaceError> {
+        self.validate()?;
+
+        if width == 0 || height == 0 {
+            return Err(SurfaceError::ZeroSizedSurface {
+                width,
+                height,
+                depth: self.depth,
+            });
+        }
+
+        let in_bounds =
+            |start: u32, size: u32, max: u32| start.checked_add(size).is_some_and(|end| end <= max);
+        if !in_bounds(x, width, self.width) || !in_bounds(y, height, self.height) {
+            return Err(SurfaceError::CropOutOfBounds {
+                x,
+                y,
+                width,
+                height,
+            });
+        }
+
+        let mut data = Vec::new();
+        for layer in 0..self.layers {
+            for mipmap in 0..mipmaps {
+                let mip_width = mip_dimension(self.width, mipmap) as usize;
+
+                // Keep the region within the mipmap after rounding down the position.
+                let crop_width = mip_dimension(width, mipmap) as usize;
+                let crop_height = mip_dimension(height, mipmap) as usize;
+                let crop_x = ((x >> mipmap) as usize).min(mip_width - crop_width);
+                let crop_y = ((y >> mipmap) as usize)
+                    .min(mip_dimension(self.height, mipmap) as usize - crop_height);
+
+                for level in 0..mip_dimension(self.depth, mipmap) {
+                    let image = self
+                        .get(layer, level, mipmap)
+                        .ok_or(SurfaceError::MipmapDataOutOfBounds { layer, mipmap })?;
+
+                    for row in image
+                        .chunks_exact(mip_width * 4)
+                        .skip(crop_y)
+                        .take(crop_height)
+                    {
+                        data.extend_from_slice(&row[crop_x * 4..(crop_x + crop_width) * 4]);
+                    }
+                }
+            }
+        }
+
+        Ok(SurfaceRgba8 {
+            width,
+            height,
+            depth: self.depth,
+            layers: self.layers,
+            mipmaps,
+            data,
+        })
+    }
+
+    /// Copy the base mip level for all layers and depth levels
+    /// and pad the width and height to a multiple of the block dimensions of `format`.
+    ///
+    /// Encoding the result does not require any additional padding.
+    /// The added pixels are filled based on `padding`.
+    pub fn pad_block_edges(
+        &self,
+        format: ImageFormat,
+        padding: EdgePadding,
+    ) -> Result<SurfaceRgba8<Vec<u8>>, SurfaceError> {
+        self.validate()?;
+
+        let (new_width, new_height, _) = format.block_align(self.width, self.height, 1);
+        let (width, height) = (self.width as usize, self.height as usize);
+
+        let mut data = Vec::with_capacity(
+            new_width as usize
+                * new_height as usize
+                * self.depth as usize
+                * self.layers as usize
+                * 4,
+        );
+        for layer in 0..self.layers {
+            for level in 0..self.depth {
+                let image = self
+                    .get(layer, level, 0)
+                    .ok_or(SurfaceError::MipmapDataOutOfBounds { layer, mipmap: 0 })?;
+
+                for y in 0..new_height as usize {
+                    for x in 0..new_width as usize {
+                        let pixel = match padding {
+                            EdgePadding::Zero => (x < width && y < height).then_some((x, y)),
+                            EdgePadding::Clamp => Some((x.min(width - 1), y.min(height - 1))),
+                        };
+                        match pixel {
+                            Some((x, y)) => {
+                                let i = (y * width + x) * 4;
+                                data.extend_from_slice(&image[i..i + 4]);
+                            }
+                            None => data.extend_from_slice(&[0u8; 4]),
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(SurfaceRgba8 {
+            width: new_width,
+            height: new_height,
+            depth: self.depth,
+            layers: self.layers,
+            mipmaps: 1,
+            data,
+        })
     }
 }
 
@@ -273,16 +1044,244 @@ impl<T: AsRef<[u8]>> SurfaceRgba8<T> {
     }
 }
 
-#[cfg(feature = "image")]
-impl SurfaceRgba8<Vec<u8>> {
-    /// Create an image for all layers and depth slices without copying.
+impl<T: AsRef<[u8]>> SurfaceRgba8<T> {
+    /// Calculate the minimum, maximum, and mean of the R, G, B, and A channels
+    /// for the base mip level of all layers and depth slices.
     ///
-    /// Fails if the surface has more than one mipmap.
-    /// Array layers and depth slices are arranged vertically from top to bottom.
-    pub fn into_image(self) -> Result<image::RgbaImage, CreateImageError> {
-        // Arrange depth and array layers vertically.
-        // This layout allows copyless conversions to an RGBA8 surface.
-        let width = self.width;
+    /// This can detect unused channels before encoding
+    /// like an alpha channel that is always `255`.
+    pub fn channel_stats(&self) -> Result<[ChannelStats; 4], SurfaceError> {
+        self.validate()?;
+
+        let mut min = [u8::MAX; 4];
+        let mut max = [u8::MIN; 4];
+        let mut sum = [0u64; 4];
+        let mut count = 0u64;
+        for layer in 0..self.layers {
+            for level in 0..self.depth {
+                let data = self
+                    .get(layer, level, 0)
+                    .ok_or(SurfaceError::MipmapDataOutOfBounds { layer, mipmap: 0 })?;
+                for pixel in data.chunks_exact(4) {
+                    for c in 0..4 {
+                        min[c] = min[c].min(pixel[c]);
+                        max[c] = max[c].max(pixel[c]);
+                        sum[c] += pixel[c] as u64;
+                    }
+                    count += 1;
+                }
+            }
+        }
+
+        Ok(std::array::from_fn(|c| ChannelStats {
+            min: min[c],
+            max: max[c],
+            mean: (sum[c] as f64 / count as f64) as f32,
+        }))
+    }
+
+    /// Split the red, green, blue, and alpha channels of all layers and mipmaps
+    /// into separate [ImageFormat::R8Unorm] surfaces.
+    ///
+    /// This is useful for rearranging the channels of packed textures.
+    /// Decode each channel with [Surface::decode_rgba8] and use [SurfaceRgba8::combine_channels]
+    /// to combine the channels again.
+    pub fn split_channels(&self) -> Result<[Surface<Vec<u8>>; 4], SurfaceError> {
+        let pixels = self.pixel_data()?;
+
+        Ok(std::array::from_fn(|c| Surface {
+            width: self.width,
+            height: self.height,
+            depth: self.depth,
+            layers: self.layers,
+            mipmaps: self.mipmaps,
+            image_format: ImageFormat::R8Unorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: pixels.iter().skip(c).step_by(4).copied().collect(),
+        }))
+    }
+
+    /// Copy the base mip level of all layers and depth slices
+    /// with the alpha channel replaced by the values in `alpha`.
+    ///
+    /// The `alpha` mask has one byte for each pixel of the base mip level
+    /// in the same order as the RGBA data and must match the base level dimensions exactly.
+    /// The result has a single mipmap, so generate mipmaps when encoding if needed.
+    pub fn with_alpha_mask(&self, alpha: &[u8]) -> Result<SurfaceRgba8<Vec<u8>>, SurfaceError> {
+        self.validate()?;
+
+        let expected =
+            self.width as usize * self.height as usize * self.depth as usize * self.layers as usize;
+        if alpha.len() != expected {
+            return Err(SurfaceError::UnexpectedDataLength {
+                expected,
+                actual: alpha.len(),
+            });
+        }
+
+        let mut data = Vec::with_capacity(expected * 4);
+        for layer in 0..self.layers {
+            for level in 0..self.depth {
+                let pixels = self
+                    .get(layer, level, 0)
+                    .ok_or(SurfaceError::MipmapDataOutOfBounds { layer, mipmap: 0 })?;
+                data.extend_from_slice(pixels);
+            }
+        }
+        for (pixel, a) in data.chunks_exact_mut(4).zip(alpha) {
+            pixel[3] = *a;
+        }
+
+        Ok(SurfaceRgba8 {
+            width: self.width,
+            height: self.height,
+            depth: self.depth,
+            layers: self.layers,
+            mipmaps: 1,
+            data,
+        })
+    }
+}
+
+impl SurfaceRgba8<Vec<u8>> {
+    /// Create a surface from the red channel of `r`, `g`, `b`, and `a`
+    /// for all layers and mipmaps.
+    ///
+    /// Grayscale images converted to RGBA8 store their values in the red channel.
+    /// All surfaces must have the same dimensions, layers, and mipmaps.
+    pub fn combine_channels<U: AsRef<[u8]>>(
+        r: &SurfaceRgba8<U>,
+        g: &SurfaceRgba8<U>,
+        b: &SurfaceRgba8<U>,
+        a: &SurfaceRgba8<U>,
+    ) -> Result<SurfaceRgba8<Vec<u8>>, SurfaceError> {
+        let sources = [r, g, b, a];
+        let mut channels = Vec::with_capacity(4);
+        for (index, surface) in sources.iter().enumerate() {
+            if (
+                surface.width,
+                surface.height,
+                surface.depth,
+                surface.layers,
+                surface.mipmaps,
+            ) != (r.width, r.height, r.depth, r.layers, r.mipmaps)
+            {
+                return Err(SurfaceError::MismatchedLayer { index });
+            }
+            channels.push(surface.pixel_data()?);
+        }
+
+        let data = (0..channels[0].len())
+            .step_by(4)
+            .flat_map(|i| {
+                [
+                    channels[0][i],
+                    channels[1][i],
+                    channels[2][i],
+                    channels[3][i],
+                ]
+            })
+            .collect();
+
+        Ok(SurfaceRgba8 {
+            width: r.width,
+            height: r.height,
+            depth: r.depth,
+            layers: r.layers,
+            mipmaps: r.mipmaps,
+            data,
+        })
+    }
+}
+
+impl<T: AsRef<[u8]> + AsMut<[u8]>> SurfaceRgba8<T> {
+    /// Apply `f` in place to each pixel of all layers, depth slices, and mipmaps.
+    ///
+    /// Any data after the last mipmap of the last layer is left unchanged.
+    pub fn map_pixels(&mut self, f: impl Fn([u8; 4]) -> [u8; 4]) -> Result<(), SurfaceError> {
+        self.validate()?;
+
+        let size = calculate_offset(
+            self.layers,
+            0,
+            0,
+            (self.width, self.height, self.depth),
+            (1, 1, 1),
+            4,
+            self.mipmaps,
+            DataLayout::Tight,
+            MipOrder::LargestFirst,
+        )
+        .ok_or(SurfaceError::PixelCountWouldOverflow {
+            width: self.width,
+            height: self.height,
+            depth: self.depth,
+        })?;
+
+        let data = self.data.as_mut();
+        let actual = data.len();
+        let pixels = data.get_mut(..size).ok_or(SurfaceError::NotEnoughData {
+            expected: size,
+            actual,
+        })?;
+
+        for pixel in pixels.chunks_exact_mut(4) {
+            let rgba = f([pixel[0], pixel[1], pixel[2], pixel[3]]);
+            pixel.copy_from_slice(&rgba);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "image")]
+impl SurfaceRgba8<Vec<u8>> {
+    /// Create a 2D array surface with a layer for each of the `images`.
+    ///
+    /// All images must have the same dimensions.
+    /// This can be used to create texture arrays or cube maps from separate images.
+    pub fn from_images(images: &[image::RgbaImage]) -> Result<Self, SurfaceError> {
+        let first = images.first().ok_or(SurfaceError::ZeroLayersOrMipmaps {
+            layers: 0,
+            mipmaps: 0,
+        })?;
+
+        let mut data = Vec::with_capacity(first.as_raw().len() * images.len());
+        for (index, image) in images.iter().enumerate() {
+            if image.dimensions() != first.dimensions() {
+                return Err(SurfaceError::MismatchedLayer { index });
+            }
+            data.extend_from_slice(image.as_raw());
+        }
+
+        let surface = SurfaceRgba8 {
+            width: first.width(),
+            height: first.height(),
+            depth: 1,
+            layers: images
+                .len()
+                .try_into()
+                .map_err(|_| SurfaceError::PixelCountWouldOverflow {
+                    width: first.width(),
+                    height: first.height(),
+                    depth: 1,
+                })?,
+            mipmaps: 1,
+            data,
+        };
+        surface.validate()?;
+        Ok(surface)
+    }
+
+    /// Create an image for all layers and depth slices without copying.
+    ///
+    /// Fails if the surface has more than one mipmap.
+    /// Array layers and depth slices are arranged vertically from top to bottom.
+    pub fn into_image(self) -> Result<image::RgbaImage, CreateImageError> {
+        // Arrange depth and array layers vertically.
+        // This layout allows copyless conversions to an RGBA8 surface.
+        let width = self.width;
         let height = self.height * self.depth * self.layers;
 
         if self.mipmaps > 1 {
@@ -316,7 +1315,7 @@ pub struct SurfaceRgba32Float<T> {
     /// This should be `1` for 2D surfaces.
     pub depth: u32,
     /// The number of array layers in the surface.
-    /// This should be `1` for most surfaces and `6` for cube maps.
+    /// This should be `1` for most surfaces, `6` for cube maps, and `6 * N` for arrays of `N` cube maps.
     pub layers: u32,
     /// The number of mipmaps in the surface.
     /// This should be `1` if the surface has only the base mip level.
@@ -354,12 +1353,23 @@ impl<T: AsRef<[f32]>> SurfaceRgba32Float<T> {
             (self.width, self.height, self.depth),
             self.mipmaps,
             ImageFormat::Rgba32Float,
+            DataLayout::Tight,
+            MipOrder::LargestFirst,
             layer,
             depth_level,
             mipmap,
         )
     }
 
+    /// Get the range of 2D image data corresponding to the specified `face` of a cube map.
+    ///
+    /// Faces are ordered +X, -X, +Y, -Y, +Z, -Z and have the layer `6 * cube_index + face`.
+    /// Returns [None] if the face or cube is out of range or the expected range is not fully contained within the buffer.
+    pub fn get_cube_face(&self, cube_index: u32, face: u32, mipmap: u32) -> Option<&[f32]> {
+        let layer = cube_face_layer(self.layers, cube_index, face)?;
+        self.get(layer, 0, mipmap)
+    }
+
     /// Get the image corresponding to the specified `layer`, `depth_level`, and `mipmap`.
     ///
     /// Returns [None] if the expected range is not fully contained within the buffer.
@@ -387,9 +1397,35 @@ impl<T: AsRef<[f32]>> SurfaceRgba32Float<T> {
             layers: self.layers,
             mipmaps: self.mipmaps,
             image_format: ImageFormat::Rgba32Float,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
             data: bytemuck::cast_slice(self.data.as_ref()),
         }
-        .validate()
+        .validate_base()
+    }
+
+    /// Check that the data contains all layers, depth slices, and mipmaps before encoding.
+    ///
+    /// Only the base level is required if missing mipmaps will be generated.
+    #[cfg(feature = "encode")]
+    pub(crate) fn validate_encode(
+        &self,
+        format: ImageFormat,
+        options: &crate::EncodeOptions,
+    ) -> Result<(), SurfaceError> {
+        self.validate()?;
+        validate_rgba_length(
+            (self.width, self.height, self.depth),
+            input_block_dimensions(format, options),
+            self.layers,
+            if options.generate_missing_mipmaps {
+                1
+            } else {
+                self.mipmaps
+            },
+            options.strict_data_length.then_some(self.mipmaps),
+            self.data.as_ref().len(),
+        )
     }
 }
 
@@ -497,18 +1533,335 @@ impl SurfaceRgba32Float<Vec<f32>> {
     }
 }
 
+/// An uncompressed [ImageFormat::Rgba16Float] surface with 8 bytes per pixel.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SurfaceRgba16Float<T> {
+    /// The width of the surface in pixels.
+    pub width: u32,
+    /// The height of the surface in pixels.
+    pub height: u32,
+    /// The depth of the surface in pixels.
+    /// This should be `1` for 2D surfaces.
+    pub depth: u32,
+    /// The number of array layers in the surface.
+    /// This should be `1` for most surfaces, `6` for cube maps, and `6 * N` for arrays of `N` cube maps.
+    pub layers: u32,
+    /// The number of mipmaps in the surface.
+    /// This should be `1` if the surface has only the base mip level.
+    /// All array layers are assumed to have the same number of mipmaps.
+    pub mipmaps: u32,
+    /// The combined [f16](half::f16) image data ordered by layer and then mipmap without additional padding.
+    ///
+    /// A surface with L layers and M mipmaps would have the following layout:
+    /// Layer 0 Mip 0, Layer 0 Mip 1,  ..., Layer L-1 Mip M-1
+    pub data: T,
+}
+
+impl<T> SurfaceRgba16Float<Vec<T>> {
+    /// Convert to a surface with borrowed data.
+    pub fn as_ref(&self) -> SurfaceRgba16Float<&[T]> {
+        SurfaceRgba16Float {
+            width: self.width,
+            height: self.height,
+            depth: self.depth,
+            layers: self.layers,
+            mipmaps: self.mipmaps,
+            data: self.data.as_ref(),
+        }
+    }
+}
+
+impl<T: AsRef<[f16]>> SurfaceRgba16Float<T> {
+    /// Get the range of 2D image data corresponding to the specified `layer`, `depth_level`, and `mipmap`.
+    ///
+    /// The dimensions of the returned data should be calculated using [mip_dimension].
+    /// Returns [None] if the expected range is not fully contained within the buffer.
+    pub fn get(&self, layer: u32, depth_level: u32, mipmap: u32) -> Option<&[f16]> {
+        get_mipmap(
+            self.data.as_ref(),
+            (self.width, self.height, self.depth),
+            self.mipmaps,
+            ImageFormat::Rgba16Float,
+            DataLayout::Tight,
+            MipOrder::LargestFirst,
+            layer,
+            depth_level,
+            mipmap,
+        )
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), SurfaceError> {
+        Surface {
+            width: self.width,
+            height: self.height,
+            depth: self.depth,
+            layers: self.layers,
+            mipmaps: self.mipmaps,
+            image_format: ImageFormat::Rgba16Float,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: bytemuck::cast_slice(self.data.as_ref()),
+        }
+        .validate_base()
+    }
+
+    /// Check that the data contains all layers, depth slices, and mipmaps before encoding.
+    ///
+    /// Only the base level is required if missing mipmaps will be generated.
+    #[cfg(feature = "encode")]
+    pub(crate) fn validate_encode(
+        &self,
+        format: ImageFormat,
+        options: &crate::EncodeOptions,
+    ) -> Result<(), SurfaceError> {
+        self.validate()?;
+        validate_rgba_length(
+            (self.width, self.height, self.depth),
+            input_block_dimensions(format, options),
+            self.layers,
+            if options.generate_missing_mipmaps {
+                1
+            } else {
+                self.mipmaps
+            },
+            options.strict_data_length.then_some(self.mipmaps),
+            self.data.as_ref().len(),
+        )
+    }
+}
+
+/// A 2D RGBA8 surface with each channel stored in a separate plane.
+///
+/// Each plane stores one byte per pixel in row-major order
+/// and should have exactly `width * height` bytes.
+/// Use [SurfaceRgba8Planar::to_rgba8] to interleave the planes for encoding.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SurfaceRgba8Planar<T> {
+    /// The width of the surface in pixels.
+    pub width: u32,
+    /// The height of the surface in pixels.
+    pub height: u32,
+    /// The red channel data.
+    pub r: T,
+    /// The green channel data.
+    pub g: T,
+    /// The blue channel data.
+    pub b: T,
+    /// The alpha channel data.
+    pub a: T,
+}
+
+impl<T: AsRef<[u8]>> SurfaceRgba8Planar<T> {
+    /// Interleave the planes into a single RGBA8 surface with only the base mip level.
+    ///
+    /// Returns [SurfaceError::UnexpectedDataLength] if any plane does not have `width * height` bytes.
+    pub fn to_rgba8(&self) -> Result<SurfaceRgba8<Vec<u8>>, SurfaceError> {
+        let expected = (self.width as usize)
+            .checked_mul(self.height as usize)
+            .ok_or(SurfaceError::PixelCountWouldOverflow {
+                width: self.width,
+                height: self.height,
+                depth: 1,
+            })?;
+
+        let planes = [
+            self.r.as_ref(),
+            self.g.as_ref(),
+            self.b.as_ref(),
+            self.a.as_ref(),
+        ];
+        if let Some(plane) = planes.iter().find(|p| p.len() != expected) {
+            return Err(SurfaceError::UnexpectedDataLength {
+                expected,
+                actual: plane.len(),
+            });
+        }
+
+        let data = (0..expected)
+            .flat_map(|i| planes.map(|plane| plane[i]))
+            .collect();
+
+        Ok(SurfaceRgba8 {
+            width: self.width,
+            height: self.height,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            data,
+        })
+    }
+}
+
+/// The dimensions that each mipmap of the RGBA input data is rounded up to a multiple of.
+#[cfg(feature = "encode")]
+pub(crate) fn input_block_dimensions(
+    format: ImageFormat,
+    options: &crate::EncodeOptions,
+) -> (u32, u32, u32) {
+    match options.input_alignment {
+        crate::InputAlignment::Tight => (1, 1, 1),
+        crate::InputAlignment::AlreadyBlockAligned => format.block_dimensions(),
+    }
+}
+
+/// Get the RGBA data for `mipmap` where each mipmap is rounded up to a multiple of `block_dimensions`.
+#[cfg(feature = "encode")]
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn get_rgba_mipmap<T>(
+    data: &[T],
+    dimensions: (u32, u32, u32),
+    block_dimensions: (u32, u32, u32),
+    mipmaps: u32,
+    layer: u32,
+    depth_level: u32,
+    mipmap: u32,
+) -> Option<&[T]> {
+    let (width, height, depth) = dimensions;
+    if mipmaps > max_mipmap_count(width.max(height).max(depth)) {
+        return None;
+    }
+
+    let block_size_in_bytes = rgba_block_size(block_dimensions) * std::mem::size_of::<T>();
+    let offset_in_bytes = calculate_offset(
+        layer,
+        depth_level,
+        mipmap,
+        dimensions,
+        block_dimensions,
+        block_size_in_bytes,
+        mipmaps,
+        DataLayout::Tight,
+        MipOrder::LargestFirst,
+    )?;
+    let size_in_bytes = layout_mip_size(
+        DataLayout::Tight,
+        (
+            mip_dimension(width, mipmap) as usize,
+            mip_dimension(height, mipmap) as usize,
+            1,
+        ),
+        block_dimensions,
+        block_size_in_bytes,
+    )?;
+
+    let start = offset_in_bytes / std::mem::size_of::<T>();
+    let count = size_in_bytes / std::mem::size_of::<T>();
+    data.get(start..start + count)
+}
+
+/// The number of mipmaps stored for each layer in RGBA data with `length` elements.
+///
+/// Each layer stores the same number of leading mipmaps, so data with missing mipmaps
+/// can still be indexed by layer.
+#[cfg(feature = "encode")]
+pub(crate) fn stored_rgba_mipmap_count(
+    dimensions: (u32, u32, u32),
+    block_dimensions: (u32, u32, u32),
+    layers: u32,
+    mipmaps: u32,
+    length: usize,
+) -> u32 {
+    (1..=mipmaps)
+        .take_while(|m| {
+            calculate_offset(
+                layers,
+                0,
+                0,
+                dimensions,
+                block_dimensions,
+                rgba_block_size(block_dimensions),
+                *m,
+                DataLayout::Tight,
+                MipOrder::LargestFirst,
+            )
+            .is_some_and(|expected| expected <= length)
+        })
+        .last()
+        .unwrap_or(0)
+}
+
+#[cfg(feature = "encode")]
+fn rgba_block_size(block_dimensions: (u32, u32, u32)) -> usize {
+    let (block_width, block_height, block_depth) = block_dimensions;
+    block_width as usize * block_height as usize * block_depth as usize * 4
+}
+
+#[cfg(feature = "encode")]
+fn validate_rgba_length(
+    dimensions: (u32, u32, u32),
+    block_dimensions: (u32, u32, u32),
+    layers: u32,
+    mipmaps: u32,
+    strict_mipmaps: Option<u32>,
+    length: usize,
+) -> Result<(), SurfaceError> {
+    // The length is in elements with 4 elements per pixel.
+    let (width, height, depth) = dimensions;
+    let expected_length = |mipmaps| {
+        calculate_offset(
+            layers,
+            0,
+            0,
+            dimensions,
+            block_dimensions,
+            rgba_block_size(block_dimensions),
+            mipmaps,
+            DataLayout::Tight,
+            MipOrder::LargestFirst,
+        )
+        .ok_or(SurfaceError::PixelCountWouldOverflow {
+            width,
+            height,
+            depth,
+        })
+    };
+
+    let expected = expected_length(mipmaps)?;
+    if length < expected {
+        return Err(SurfaceError::NotEnoughData {
+            expected,
+            actual: length,
+        });
+    }
+
+    // Missing mipmaps may be generated, so only data past all mipmaps is unexpected.
+    if let Some(mipmaps) = strict_mipmaps {
+        let expected = expected_length(mipmaps)?;
+        if length > expected {
+            return Err(SurfaceError::UnexpectedDataLength {
+                expected,
+                actual: length,
+            });
+        }
+    }
+
+    Ok(())
+}
+
 // TODO: Add tests for this.
+#[allow(clippy::too_many_arguments)]
 fn get_mipmap<T>(
     data: &[T],
     dimensions: (u32, u32, u32),
     mipmaps: u32,
     format: ImageFormat,
+    layout: DataLayout,
+    mip_order: MipOrder,
     layer: u32,
     depth_level: u32,
     mipmap: u32,
 ) -> Option<&[T]> {
     let (width, height, depth) = dimensions;
 
+    // Avoid calculating offsets for impossible mipmap counts from corrupted headers.
+    if mipmaps > max_mipmap_count(width.max(height).max(depth)) {
+        return None;
+    }
+
     let block_size_in_bytes = format.block_size_in_bytes();
     let block_dimensions = format.block_dimensions();
 
@@ -521,6 +1874,8 @@ fn get_mipmap<T>(
         block_dimensions,
         block_size_in_bytes,
         mipmaps,
+        layout,
+        mip_order,
     )?;
 
     // The returned slice is always 2D.
@@ -528,13 +1883,10 @@ fn get_mipmap<T>(
     let mip_height = mip_dimension(height, mipmap);
 
     // TODO: Create an error for overflow?
-    let size_in_bytes = mip_size(
-        mip_width as usize,
-        mip_height as usize,
-        1,
-        block_dimensions.0 as usize,
-        block_dimensions.1 as usize,
-        block_dimensions.2 as usize,
+    let size_in_bytes = layout_mip_size(
+        layout,
+        (mip_width as usize, mip_height as usize, 1),
+        block_dimensions,
         block_size_in_bytes,
     )?;
 
@@ -542,3 +1894,957 @@ fn get_mipmap<T>(
     let count = size_in_bytes / std::mem::size_of::<T>();
     data.get(start..start + count)
 }
+
+fn cube_face_layer(layers: u32, cube_index: u32, face: u32) -> Option<u32> {
+    // Cube map arrays store all 6 faces for each cube map contiguously.
+    let layer = cube_index.checked_mul(6)?.checked_add(face)?;
+    (face < 6 && cube_index < layers / 6).then_some(layer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn surface(image_format: ImageFormat, data: &[u8]) -> Surface<&[u8]> {
+        Surface {
+            width: 1,
+            height: 1,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data,
+        }
+    }
+
+    #[test]
+    fn checksum_stable() {
+        assert_eq!(
+            0x2a05b13fcc03120c,
+            surface(ImageFormat::Rgba8Unorm, &[1, 2, 3, 4]).checksum()
+        );
+    }
+
+    #[test]
+    fn checksum_format_data() {
+        let checksum = surface(ImageFormat::Rgba8Unorm, &[1, 2, 3, 4]).checksum();
+        assert_ne!(
+            checksum,
+            surface(ImageFormat::Bgra8Unorm, &[1, 2, 3, 4]).checksum()
+        );
+        assert_ne!(
+            checksum,
+            surface(ImageFormat::Rgba8Unorm, &[1, 2, 3, 5]).checksum()
+        );
+    }
+
+    #[test]
+    fn checksum_layout_mip_order() {
+        let checksum = surface(ImageFormat::Rgba8Unorm, &[1, 2, 3, 4]).checksum();
+        assert_ne!(
+            checksum,
+            Surface {
+                layout: DataLayout::RowAligned { row_alignment: 4 },
+                ..surface(ImageFormat::Rgba8Unorm, &[1, 2, 3, 4])
+            }
+            .checksum()
+        );
+        assert_ne!(
+            checksum,
+            Surface {
+                mip_order: MipOrder::SmallestFirst,
+                ..surface(ImageFormat::Rgba8Unorm, &[1, 2, 3, 4])
+            }
+            .checksum()
+        );
+    }
+
+    #[test]
+    fn get_row_aligned() {
+        // 2x2 R8 with rows padded to 4 bytes.
+        let surface = Surface {
+            width: 2,
+            height: 2,
+            depth: 1,
+            layers: 1,
+            mipmaps: 2,
+            image_format: ImageFormat::R8Unorm,
+            layout: DataLayout::RowAligned { row_alignment: 4 },
+            mip_order: MipOrder::LargestFirst,
+            data: &[1u8, 2, 0, 0, 3, 4, 0, 0, 5, 0, 0, 0][..],
+        };
+        assert_eq!(Some(&[1, 2, 0, 0, 3, 4, 0, 0][..]), surface.get(0, 0, 0));
+        assert_eq!(Some(&[5, 0, 0, 0][..]), surface.get(0, 0, 1));
+    }
+
+    #[test]
+    fn with_layout_round_trip() {
+        let surface = Surface {
+            width: 3,
+            height: 2,
+            depth: 1,
+            layers: 2,
+            mipmaps: 1,
+            image_format: ImageFormat::R8Unorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: &[1u8, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12][..],
+        };
+
+        let aligned = surface
+            .with_layout(DataLayout::RowAligned { row_alignment: 4 })
+            .unwrap();
+        assert_eq!(
+            vec![1, 2, 3, 0, 4, 5, 6, 0, 7, 8, 9, 0, 10, 11, 12, 0],
+            aligned.data
+        );
+
+        let tight = aligned.with_layout(DataLayout::Tight).unwrap();
+        assert_eq!(surface.data, tight.data);
+        assert_eq!(DataLayout::Tight, tight.layout);
+    }
+
+    #[test]
+    fn with_mip_order_round_trip() {
+        let surface = Surface {
+            width: 2,
+            height: 2,
+            depth: 1,
+            layers: 2,
+            mipmaps: 2,
+            image_format: ImageFormat::R8Unorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: &[1u8, 2, 3, 4, 5, 6, 7, 8, 9, 10][..],
+        };
+
+        let reversed = surface.with_mip_order(MipOrder::SmallestFirst).unwrap();
+        assert_eq!(vec![5, 1, 2, 3, 4, 10, 6, 7, 8, 9], reversed.data);
+        assert_eq!(surface.get(1, 0, 0), reversed.get(1, 0, 0));
+        assert_eq!(surface.get(1, 0, 1), reversed.get(1, 0, 1));
+
+        let original = reversed.with_mip_order(MipOrder::LargestFirst).unwrap();
+        assert_eq!(surface.data, original.data);
+    }
+
+    #[test]
+    fn validate_data_length() {
+        // 8x8 and 4x4 BC1 mipmaps for 2 layers.
+        let surface = |data| Surface {
+            width: 8,
+            height: 8,
+            depth: 1,
+            layers: 2,
+            mipmaps: 2,
+            image_format: ImageFormat::BC1RgbaUnorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::SmallestFirst,
+            data,
+        };
+        assert_eq!(Ok(()), surface(&[0u8; 80][..]).validate());
+        assert_eq!(
+            Err(SurfaceError::NotEnoughData {
+                expected: 80,
+                actual: 79
+            }),
+            surface(&[0u8; 79][..]).validate()
+        );
+        assert_eq!(
+            Err(SurfaceError::UnexpectedDataLength {
+                expected: 80,
+                actual: 81
+            }),
+            surface(&[0u8; 81][..]).validate()
+        );
+
+        // The last row of each mipmap is also padded.
+        let aligned = Surface {
+            width: 2,
+            height: 2,
+            depth: 1,
+            layers: 1,
+            mipmaps: 2,
+            image_format: ImageFormat::R8Unorm,
+            layout: DataLayout::RowAligned { row_alignment: 4 },
+            mip_order: MipOrder::LargestFirst,
+            data: &[0u8; 12][..],
+        };
+        assert_eq!(Ok(()), aligned.validate());
+    }
+
+    #[test]
+    fn validate_invalid_dimensions() {
+        let valid = surface(ImageFormat::Rgba8Unorm, &[0u8; 4]);
+        assert_eq!(
+            Err(SurfaceError::ZeroSizedSurface {
+                width: 0,
+                height: 1,
+                depth: 1
+            }),
+            Surface { width: 0, ..valid }.validate()
+        );
+        assert_eq!(
+            Err(SurfaceError::ZeroLayersOrMipmaps {
+                layers: 0,
+                mipmaps: 1
+            }),
+            Surface { layers: 0, ..valid }.validate()
+        );
+        assert_eq!(
+            Err(SurfaceError::UnexpectedMipmapCount {
+                mipmaps: 2,
+                max_mipmaps: 1
+            }),
+            Surface {
+                mipmaps: 2,
+                ..valid
+            }
+            .validate()
+        );
+        assert_eq!(
+            Err(SurfaceError::PixelCountWouldOverflow {
+                width: u32::MAX,
+                height: u32::MAX,
+                depth: u32::MAX
+            }),
+            Surface {
+                width: u32::MAX,
+                height: u32::MAX,
+                depth: u32::MAX,
+                layers: u32::MAX,
+                ..valid
+            }
+            .validate()
+        );
+    }
+
+    #[test]
+    fn validate_volume_mipmaps() {
+        // Volume mipmaps continue until the depth is 1.
+        // 4x4x16, 2x2x8, 1x1x4, 1x1x2, 1x1x1
+        let data = [0u8; 16 * 16 + 4 * 8 + 4 + 2 + 1];
+        let volume = Surface {
+            width: 4,
+            height: 4,
+            depth: 16,
+            mipmaps: 5,
+            ..surface(ImageFormat::R8Unorm, &data)
+        };
+        assert_eq!(Ok(()), volume.validate());
+        assert_eq!(Some(&[0u8][..]), volume.get(0, 0, 4));
+        assert_eq!(
+            Err(SurfaceError::UnexpectedMipmapCount {
+                mipmaps: 6,
+                max_mipmaps: 5
+            }),
+            Surface {
+                mipmaps: 6,
+                ..volume
+            }
+            .validate()
+        );
+    }
+
+    #[test]
+    fn from_layers_cube_map() {
+        let faces: Vec<_> = (0..6u8)
+            .map(|i| Surface {
+                width: 2,
+                height: 2,
+                depth: 1,
+                layers: 1,
+                mipmaps: 2,
+                image_format: ImageFormat::R8Unorm,
+                layout: DataLayout::Tight,
+                mip_order: MipOrder::LargestFirst,
+                // Trailing data should not be included.
+                data: vec![i; 6],
+            })
+            .collect();
+
+        let surface = Surface::from_layers(&faces).unwrap();
+        assert_eq!(6, surface.layers);
+        assert_eq!(30, surface.data.len());
+        for face in 0..6 {
+            assert_eq!(
+                Some(&[face as u8; 4][..]),
+                surface.get_cube_face(0, face, 0)
+            );
+            assert_eq!(Some(&[face as u8][..]), surface.get_cube_face(0, face, 1));
+        }
+    }
+
+    #[test]
+    fn iter_blocks_cube_array() {
+        let data: Vec<_> = (0..13u8).collect();
+        let surface = Surface {
+            width: 1,
+            height: 1,
+            depth: 1,
+            layers: 13,
+            mipmaps: 1,
+            image_format: ImageFormat::R8Unorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: data.as_slice(),
+        };
+        assert_eq!(2, surface.cube_count());
+
+        for block in surface.iter_blocks() {
+            let (cube_index, face) = block.location.cube_face();
+            assert_eq!(block.location.layer, cube_index * 6 + face);
+            if cube_index < surface.cube_count() {
+                assert_eq!(Some(block.data), surface.get_cube_face(cube_index, face, 0));
+            }
+        }
+        assert_eq!(Some(&[7u8][..]), surface.get_cube_face(1, 1, 0));
+        assert_eq!(None, surface.get_cube_face(2, 0, 0));
+    }
+
+    #[test]
+    fn from_layers_mismatched() {
+        let surface = |mipmaps| Surface {
+            width: 2,
+            height: 2,
+            depth: 1,
+            layers: 1,
+            mipmaps,
+            image_format: ImageFormat::R8Unorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: &[0u8; 5][..],
+        };
+        assert_eq!(
+            Err(SurfaceError::MismatchedLayer { index: 2 }),
+            Surface::from_layers(&[surface(2), surface(2), surface(1)])
+        );
+        assert_eq!(
+            Err(SurfaceError::ZeroLayersOrMipmaps {
+                layers: 0,
+                mipmaps: 0
+            }),
+            Surface::<Vec<u8>>::from_layers::<&[u8]>(&[])
+        );
+    }
+
+    #[test]
+    fn iter_blocks_row_aligned() {
+        // 8x4 BC1 with 2 mipmaps and each block row padded to 32 bytes.
+        let mut data = [0u8; 32 + 32];
+        data[..8].fill(1);
+        data[8..16].fill(2);
+        data[32..40].fill(3);
+        let surface = Surface {
+            width: 8,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 2,
+            image_format: ImageFormat::BC1RgbaUnorm,
+            layout: DataLayout::RowAligned { row_alignment: 32 },
+            mip_order: MipOrder::LargestFirst,
+            data: &data[..],
+        };
+
+        let blocks: Vec<_> = surface
+            .iter_blocks()
+            .map(|b| (b.location.mipmap, b.location.x, b.location.y, b.data[0]))
+            .collect();
+        assert_eq!(vec![(0, 0, 0, 1), (0, 1, 0, 2), (1, 0, 0, 3)], blocks);
+        assert!(surface.iter_blocks().all(|b| b.data.len() == 8));
+    }
+
+    #[test]
+    fn iter_blocks_uncompressed_layers() {
+        let surface = Surface {
+            width: 2,
+            height: 1,
+            depth: 1,
+            layers: 2,
+            mipmaps: 1,
+            image_format: ImageFormat::R8Unorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: &[1u8, 2, 3, 4][..],
+        };
+
+        let blocks: Vec<_> = surface
+            .iter_blocks()
+            .map(|b| (b.location.layer, b.location.x, b.data))
+            .collect();
+        assert_eq!(
+            vec![(0, 0, &[1u8][..]), (0, 1, &[2]), (1, 0, &[3]), (1, 1, &[4])],
+            blocks
+        );
+    }
+
+    #[test]
+    fn iter_blocks_missing_data() {
+        let surface = Surface {
+            width: 8,
+            height: 8,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format: ImageFormat::BC7RgbaUnorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: &[0u8; 16][..],
+        };
+        assert_eq!(0, surface.iter_blocks().count());
+    }
+
+    #[test]
+    fn surface_into_owned() {
+        let data = vec![1u8; 4 * 4 * 4];
+        let surface = Surface {
+            width: 4,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format: ImageFormat::Rgba8Unorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: data.as_slice(),
+        };
+        let owned = surface.into_owned();
+        assert_eq!(surface, owned.as_ref());
+
+        let cow = Surface {
+            width: 4,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format: ImageFormat::Rgba8Unorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: std::borrow::Cow::Borrowed(data.as_slice()),
+        };
+        assert_eq!(owned, cow.into_owned());
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn from_images() {
+        let images = [
+            image::RgbaImage::from_pixel(2, 1, image::Rgba([1, 2, 3, 4])),
+            image::RgbaImage::from_pixel(2, 1, image::Rgba([5, 6, 7, 8])),
+        ];
+        assert_eq!(
+            Ok(SurfaceRgba8 {
+                width: 2,
+                height: 1,
+                depth: 1,
+                layers: 2,
+                mipmaps: 1,
+                data: vec![1, 2, 3, 4, 1, 2, 3, 4, 5, 6, 7, 8, 5, 6, 7, 8],
+            }),
+            SurfaceRgba8::from_images(&images)
+        );
+
+        assert_eq!(
+            Err(SurfaceError::MismatchedLayer { index: 1 }),
+            SurfaceRgba8::from_images(&[image::RgbaImage::new(2, 1), image::RgbaImage::new(1, 2)])
+        );
+        assert_eq!(
+            Err(SurfaceError::ZeroLayersOrMipmaps {
+                layers: 0,
+                mipmaps: 0
+            }),
+            SurfaceRgba8::from_images(&[])
+        );
+    }
+
+    #[test]
+    fn split_channels() {
+        // Check that all layers and mipmaps are included.
+        let data: Vec<u8> = (0..(2 * 2 + 1) * 2 * 4).collect();
+        let surface = SurfaceRgba8 {
+            width: 2,
+            height: 2,
+            depth: 1,
+            layers: 2,
+            mipmaps: 2,
+            data: &data[..],
+        };
+        let [r, g, b, a] = surface.split_channels().unwrap();
+
+        assert_eq!(ImageFormat::R8Unorm, r.image_format);
+        assert_eq!(
+            (2, 2, 1, 2, 2),
+            (r.width, r.height, r.depth, r.layers, r.mipmaps)
+        );
+        assert_eq!(vec![0, 4, 8, 12, 16, 20, 24, 28, 32, 36], r.data);
+        assert_eq!(vec![1, 5, 9, 13, 17, 21, 25, 29, 33, 37], g.data);
+        assert_eq!(vec![2, 6, 10, 14, 18, 22, 26, 30, 34, 38], b.data);
+        assert_eq!(vec![3, 7, 11, 15, 19, 23, 27, 31, 35, 39], a.data);
+
+        assert_eq!(
+            Err(SurfaceError::NotEnoughData {
+                expected: 40,
+                actual: 39
+            }),
+            SurfaceRgba8 {
+                data: &data[..39],
+                ..surface
+            }
+            .split_channels()
+        );
+    }
+
+    #[test]
+    fn combine_channels() {
+        let gray = |values: [u8; 5]| SurfaceRgba8 {
+            width: 2,
+            height: 2,
+            depth: 1,
+            layers: 1,
+            mipmaps: 2,
+            data: values
+                .iter()
+                .flat_map(|v| [*v, *v, *v, 255])
+                .collect::<Vec<_>>(),
+        };
+        let r = gray([1, 2, 3, 4, 5]);
+        let g = gray([6, 7, 8, 9, 10]);
+        let b = gray([11, 12, 13, 14, 15]);
+        let a = gray([16, 17, 18, 19, 20]);
+
+        let combined = SurfaceRgba8::combine_channels(&r, &g, &b, &a).unwrap();
+        assert_eq!(
+            (2, 2, 1, 1, 2),
+            (
+                combined.width,
+                combined.height,
+                combined.depth,
+                combined.layers,
+                combined.mipmaps
+            )
+        );
+        assert_eq!(
+            vec![1, 6, 11, 16, 2, 7, 12, 17, 3, 8, 13, 18, 4, 9, 14, 19, 5, 10, 15, 20],
+            combined.data
+        );
+
+        // The red channel of each split channel contains the original values.
+        let channels = combined
+            .split_channels()
+            .unwrap()
+            .map(|c| c.decode_rgba8().unwrap());
+        let [r2, g2, b2, a2] = &channels;
+        assert_eq!(
+            combined,
+            SurfaceRgba8::combine_channels(r2, g2, b2, a2).unwrap()
+        );
+
+        let small = SurfaceRgba8 {
+            mipmaps: 1,
+            data: vec![0u8; 16],
+            ..a.clone()
+        };
+        assert_eq!(
+            Err(SurfaceError::MismatchedLayer { index: 3 }),
+            SurfaceRgba8::combine_channels(&r, &g, &b, &small)
+        );
+    }
+
+    #[test]
+    fn pad_block_edges() {
+        let surface = SurfaceRgba8 {
+            width: 3,
+            height: 2,
+            depth: 1,
+            layers: 2,
+            mipmaps: 2,
+            data: (0..(3 * 2 + 1) * 2)
+                .flat_map(|i| [i as u8; 4])
+                .collect::<Vec<_>>(),
+        };
+
+        let padded = surface
+            .pad_block_edges(ImageFormat::BC7RgbaUnorm, EdgePadding::Clamp)
+            .unwrap();
+        assert_eq!(
+            (4, 4, 1, 2, 1),
+            (
+                padded.width,
+                padded.height,
+                padded.depth,
+                padded.layers,
+                padded.mipmaps
+            )
+        );
+        let layer0 = [0, 1, 2, 2, 3, 4, 5, 5, 3, 4, 5, 5, 3, 4, 5, 5];
+        let layer1 = [7, 8, 9, 9, 10, 11, 12, 12, 10, 11, 12, 12, 10, 11, 12, 12];
+        assert_eq!(
+            [layer0, layer1]
+                .concat()
+                .iter()
+                .flat_map(|i| [*i; 4])
+                .collect::<Vec<u8>>(),
+            padded.data
+        );
+
+        let padded = surface
+            .pad_block_edges(ImageFormat::BC7RgbaUnorm, EdgePadding::Zero)
+            .unwrap();
+        let layer0 = [0, 1, 2, 0, 3, 4, 5, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        assert_eq!(
+            layer0.iter().flat_map(|i| [*i; 4]).collect::<Vec<u8>>(),
+            padded.data[..4 * 4 * 4]
+        );
+
+        // Uncompressed formats already use whole blocks.
+        let padded = surface
+            .pad_block_edges(ImageFormat::Rgba8Unorm, EdgePadding::Clamp)
+            .unwrap();
+        assert_eq!((3, 2), (padded.width, padded.height));
+    }
+
+    #[test]
+    fn planar_to_rgba8() {
+        let surface = SurfaceRgba8Planar {
+            width: 2,
+            height: 1,
+            r: &[1u8, 5][..],
+            g: &[2, 6][..],
+            b: &[3, 7][..],
+            a: &[4, 8][..],
+        };
+        assert_eq!(
+            SurfaceRgba8 {
+                width: 2,
+                height: 1,
+                depth: 1,
+                layers: 1,
+                mipmaps: 1,
+                data: vec![1, 2, 3, 4, 5, 6, 7, 8],
+            },
+            surface.to_rgba8().unwrap()
+        );
+    }
+
+    #[test]
+    fn planar_to_rgba8_invalid_length() {
+        let surface = SurfaceRgba8Planar {
+            width: 2,
+            height: 1,
+            r: &[1u8, 5][..],
+            g: &[2, 6][..],
+            b: &[3][..],
+            a: &[4, 8][..],
+        };
+        assert_eq!(
+            Err(SurfaceError::UnexpectedDataLength {
+                expected: 2,
+                actual: 1
+            }),
+            surface.to_rgba8()
+        );
+    }
+
+    #[test]
+    fn rgba8_into_owned_clone() {
+        let data = [1u8, 2, 3, 4].repeat(2 * 2);
+        let surface = SurfaceRgba8 {
+            width: 2,
+            height: 2,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            data: data.as_slice(),
+        };
+        let owned = surface.into_owned();
+        assert_eq!(surface, owned.as_ref());
+
+        let cloned = owned.clone();
+        assert_eq!(owned, cloned);
+    }
+
+    #[test]
+    fn rgba8_with_alpha_mask() {
+        let surface = SurfaceRgba8 {
+            width: 2,
+            height: 1,
+            depth: 1,
+            layers: 2,
+            mipmaps: 2,
+            data: &[1u8, 2, 3, 4].repeat(6)[..],
+        };
+
+        let masked = surface.with_alpha_mask(&[10, 20, 30, 40]).unwrap();
+        assert_eq!(
+            SurfaceRgba8 {
+                width: 2,
+                height: 1,
+                depth: 1,
+                layers: 2,
+                mipmaps: 1,
+                data: vec![1, 2, 3, 10, 1, 2, 3, 20, 1, 2, 3, 30, 1, 2, 3, 40],
+            },
+            masked
+        );
+
+        assert_eq!(
+            Err(SurfaceError::UnexpectedDataLength {
+                expected: 4,
+                actual: 2
+            }),
+            surface.with_alpha_mask(&[10, 20])
+        );
+    }
+
+    #[test]
+    fn rgba8_channel_stats() {
+        // 2 layers of 2x1 pixels and a 1x1 mipmap that is ignored.
+        let surface = SurfaceRgba8 {
+            width: 2,
+            height: 1,
+            depth: 1,
+            layers: 2,
+            mipmaps: 2,
+            data: vec![
+                0u8, 10, 20, 255, 4, 10, 40, 255, 0, 0, 0, 0, //
+                8, 10, 60, 255, 12, 10, 80, 255, 0, 0, 0, 0,
+            ],
+        };
+        let [r, g, b, a] = surface.channel_stats().unwrap();
+        assert_eq!(
+            ChannelStats {
+                min: 0,
+                max: 12,
+                mean: 6.0
+            },
+            r
+        );
+        assert!(g.is_constant());
+        assert_eq!((20, 80, 50.0), (b.min, b.max, b.mean));
+        assert!(a.is_constant());
+        assert_eq!(255, a.min);
+    }
+
+    #[test]
+    fn rgba8_map_pixels() {
+        // 2 layers with 2x2 and 1x1 mipmaps and trailing data.
+        let mut surface = SurfaceRgba8 {
+            width: 2,
+            height: 2,
+            depth: 1,
+            layers: 2,
+            mipmaps: 2,
+            data: vec![1u8; 4 * 5 * 2 + 4],
+        };
+        surface
+            .map_pixels(|[r, g, b, _]| [b, g, r + 1, 255])
+            .unwrap();
+
+        let mut expected = [1, 1, 2, 255].repeat(5 * 2);
+        expected.extend_from_slice(&[1; 4]);
+        assert_eq!(expected, surface.data);
+    }
+
+    #[test]
+    fn rgba8_map_pixels_not_enough_data() {
+        let mut surface = SurfaceRgba8 {
+            width: 2,
+            height: 2,
+            depth: 1,
+            layers: 1,
+            mipmaps: 2,
+            data: vec![0u8; 4 * 4],
+        };
+        assert_eq!(
+            Err(SurfaceError::NotEnoughData {
+                expected: 4 * 5,
+                actual: 4 * 4
+            }),
+            surface.map_pixels(|_| [255; 4])
+        );
+        assert_eq!(vec![0u8; 4 * 4], surface.data);
+    }
+
+    #[test]
+    fn dimensions_at_mip() {
+        let surface = Surface {
+            width: 10,
+            height: 6,
+            depth: 2,
+            layers: 1,
+            mipmaps: 4,
+            image_format: ImageFormat::BC7RgbaUnorm,
+            layout: DataLayout::Tight,
+            mip_order: MipOrder::LargestFirst,
+            data: &[0u8; 0][..],
+        };
+        assert_eq!((10, 6, 2), surface.dimensions_at_mip(0));
+        assert_eq!((5, 3, 1), surface.dimensions_at_mip(1));
+        assert_eq!((1, 1, 1), surface.dimensions_at_mip(3));
+        assert_eq!((1, 1, 1), surface.dimensions_at_mip(40));
+
+        assert_eq!((12, 8, 2), surface.padded_dimensions_at_mip(0));
+        assert_eq!((8, 4, 1), surface.padded_dimensions_at_mip(1));
+        assert_eq!((4, 4, 1), surface.padded_dimensions_at_mip(3));
+    }
+
+    #[test]
+    fn surface_info() {
+        let surface = Surface {
+            width: 8,
+            height: 4,
+            depth: 1,
+            layers: 6,
+            mipmaps: 2,
+            image_format: ImageFormat::R8Snorm,
+            layout: DataLayout::RowAligned { row_alignment: 256 },
+            mip_order: MipOrder::SmallestFirst,
+            data: &[0u8; 0][..],
+        };
+        let info = surface.info();
+        assert_eq!(
+            SurfaceInfo {
+                width: 8,
+                height: 4,
+                depth: 1,
+                layers: 6,
+                mipmaps: 2,
+                image_format: 1,
+                row_alignment: 256,
+                mip_order: 1,
+            },
+            info
+        );
+        assert_eq!(32, bytemuck::bytes_of(&info).len());
+    }
+
+    #[test]
+    fn validate_zero_layers_or_mipmaps() {
+        let surface = |layers, mipmaps| SurfaceRgba8 {
+            width: 1,
+            height: 1,
+            depth: 1,
+            layers,
+            mipmaps,
+            data: &[0u8; 4],
+        };
+        assert_eq!(
+            Err(SurfaceError::ZeroLayersOrMipmaps {
+                layers: 0,
+                mipmaps: 1
+            }),
+            surface(0, 1).validate()
+        );
+        assert_eq!(
+            Err(SurfaceError::ZeroLayersOrMipmaps {
+                layers: 1,
+                mipmaps: 0
+            }),
+            surface(1, 0).validate()
+        );
+        assert_eq!(Ok(()), surface(1, 1).validate());
+    }
+
+    #[test]
+    fn rgba8_crop() {
+        let data: Vec<_> = (0..4 * 4 * 2).flat_map(|i| [i as u8; 4]).collect();
+        let surface = SurfaceRgba8 {
+            width: 4,
+            height: 4,
+            depth: 1,
+            layers: 2,
+            mipmaps: 1,
+            data,
+        };
+
+        let cropped = surface.crop(1, 2, 2, 2).unwrap();
+        assert_eq!(
+            (2, 2, 1, 2, 1),
+            (
+                cropped.width,
+                cropped.height,
+                cropped.depth,
+                cropped.layers,
+                cropped.mipmaps
+            )
+        );
+        let pixels: Vec<_> = cropped.data.chunks_exact(4).map(|p| p[0]).collect();
+        assert_eq!(vec![9, 10, 13, 14, 25, 26, 29, 30], pixels);
+    }
+
+    #[test]
+    fn rgba8_crop_mipmaps() {
+        // Mipmaps for 8x4 with values for each pixel index.
+        let data: Vec<_> = (0..8 * 4 + 4 * 2 + 2 + 1)
+            .flat_map(|i| [i as u8; 4])
+            .collect();
+        let surface = SurfaceRgba8 {
+            width: 8,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 4,
+            data,
+        };
+
+        // The 4x4 region only has 3 mipmaps.
+        let cropped = surface.crop_mipmaps(4, 0, 4, 4).unwrap();
+        assert_eq!(3, cropped.mipmaps);
+
+        let pixels = |mipmap| {
+            cropped
+                .get(0, 0, mipmap)
+                .unwrap()
+                .chunks_exact(4)
+                .map(|p| p[0])
+                .collect::<Vec<_>>()
+        };
+        assert_eq!([4, 5, 6, 7, 12, 13, 14, 15], pixels(0)[..8]);
+        assert_eq!(vec![34, 35, 38, 39], pixels(1));
+        assert_eq!(vec![41], pixels(2));
+    }
+
+    #[test]
+    fn rgba8_crop_out_of_bounds() {
+        let surface = SurfaceRgba8 {
+            width: 4,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            data: vec![0u8; 4 * 4 * 4],
+        };
+        assert_eq!(
+            Err(SurfaceError::CropOutOfBounds {
+                x: 3,
+                y: 0,
+                width: 2,
+                height: 1
+            }),
+            surface.crop(3, 0, 2, 1)
+        );
+    }
+
+    #[test]
+    fn rgba8_from_raw() {
+        // 4x4, 2x2, and 1x1 mipmaps for 2 layers.
+        let data = vec![0u8; (16 + 4 + 1) * 4 * 2];
+        assert!(SurfaceRgba8::from_raw(4, 4, 1, 2, 3, data.as_slice()).is_ok());
+        assert_eq!(
+            Err(SurfaceError::NotEnoughData {
+                expected: (16 + 4 + 1) * 4 * 2,
+                actual: (16 + 4 + 1) * 4 * 2 - 4
+            }),
+            SurfaceRgba8::from_raw(4, 4, 1, 2, 3, &data[4..])
+        );
+        assert_eq!(
+            Err(SurfaceError::UnexpectedDataLength {
+                expected: (16 + 4) * 4 * 2,
+                actual: (16 + 4 + 1) * 4 * 2
+            }),
+            SurfaceRgba8::from_raw(4, 4, 1, 2, 2, data.as_slice())
+        );
+    }
+}